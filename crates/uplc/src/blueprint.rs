@@ -0,0 +1,528 @@
+//! CIP-57 blueprints, the `plutus.json` files compilers emit next to their
+//! validators.
+
+mod schema;
+
+pub use schema::{Definitions, Diagnostic, Schema};
+
+use serde_json::{Map, Value};
+
+use crate::{
+    arena::Arena,
+    binder::DeBruijn,
+    data::PlutusData,
+    hash::{self, ScriptHash},
+    machine::PlutusVersion,
+    program::Program,
+    script::{self, AppliedScript, ScriptError},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum BlueprintError {
+    #[error("Malformed JSON: {0}")]
+    Json(String),
+    #[error("Missing or malformed field {0}")]
+    Field(&'static str),
+    #[error("Unsupported plutus version {0}")]
+    PlutusVersion(String),
+    #[error("Malformed schema: {0}")]
+    MalformedSchema(String),
+    #[error("No validator titled {0}")]
+    UnknownValidator(String),
+    #[error("Validator {title} hashes to {computed}, not the declared {declared}")]
+    HashMismatch {
+        title: String,
+        declared: String,
+        computed: String,
+    },
+    #[error("Validator {title} takes {expected} parameters, {found} given")]
+    ParamCount {
+        title: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Parameter {index} ({name}) of {title} does not match its schema: {}", join(.diagnostics))]
+    InvalidParam {
+        title: String,
+        index: usize,
+        name: String,
+        diagnostics: Vec<Diagnostic>,
+    },
+    #[error("The {argument} of {title} does not match its schema: {}", join(.diagnostics))]
+    InvalidArgument {
+        title: String,
+        argument: &'static str,
+        diagnostics: Vec<Diagnostic>,
+    },
+    #[error(transparent)]
+    Script(#[from] ScriptError),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Blueprint {
+    pub preamble: Preamble,
+    pub validators: Vec<Validator>,
+    pub definitions: Definitions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preamble {
+    pub title: String,
+    pub version: Option<String>,
+    pub plutus_version: PlutusVersion,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validator {
+    pub title: String,
+    pub datum: Option<Argument>,
+    pub redeemer: Option<Argument>,
+    pub parameters: Vec<Argument>,
+    /// The script as the ledger stores it, before parameter application.
+    pub compiled_code: Vec<u8>,
+    pub hash: ScriptHash,
+}
+
+/// A datum, redeemer or parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub title: Option<String>,
+    pub schema: Schema,
+}
+
+impl Blueprint {
+    pub fn from_json(json: &str) -> Result<Self, BlueprintError> {
+        let json: Value =
+            serde_json::from_str(json).map_err(|error| BlueprintError::Json(error.to_string()))?;
+
+        let preamble = json
+            .get("preamble")
+            .and_then(Value::as_object)
+            .ok_or(BlueprintError::Field("preamble"))?;
+
+        let plutus_version = match string(preamble, "plutusVersion")? {
+            "v1" => PlutusVersion::V1,
+            "v2" => PlutusVersion::V2,
+            "v3" => PlutusVersion::V3,
+            other => return Err(BlueprintError::PlutusVersion(other.to_string())),
+        };
+
+        let preamble = Preamble {
+            title: string(preamble, "title")?.to_string(),
+            version: preamble
+                .get("version")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            plutus_version,
+        };
+
+        let validators = json
+            .get("validators")
+            .and_then(Value::as_array)
+            .ok_or(BlueprintError::Field("validators"))?
+            .iter()
+            .map(validator)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Blueprint {
+            preamble,
+            validators,
+            definitions: Definitions::from_json(json.get("definitions"))?,
+        })
+    }
+
+    pub fn validator(&self, title: &str) -> Result<&Validator, BlueprintError> {
+        self.validators
+            .iter()
+            .find(|validator| validator.title == title)
+            .ok_or_else(|| BlueprintError::UnknownValidator(title.to_string()))
+    }
+
+    /// Decode the compiled code of a validator, after checking it against
+    /// the declared hash.
+    pub fn program<'a>(
+        &self,
+        arena: &'a Arena,
+        title: &str,
+        protocol_version: u32,
+    ) -> Result<&'a Program<'a, DeBruijn>, BlueprintError> {
+        let validator = self.validator(title)?;
+
+        self.check_hash(validator)?;
+
+        Ok(script::decode(
+            arena,
+            &validator.compiled_code,
+            self.preamble.plutus_version,
+            protocol_version,
+        )?)
+    }
+
+    /// Check `params` against the declared schemas and apply them to a
+    /// validator.
+    pub fn apply_params<'a>(
+        &self,
+        arena: &'a Arena,
+        title: &str,
+        protocol_version: u32,
+        params: &[&'a PlutusData<'a>],
+    ) -> Result<AppliedScript<'a>, BlueprintError> {
+        let validator = self.validator(title)?;
+
+        if validator.parameters.len() != params.len() {
+            return Err(BlueprintError::ParamCount {
+                title: title.to_string(),
+                expected: validator.parameters.len(),
+                found: params.len(),
+            });
+        }
+
+        for (index, (parameter, param)) in validator.parameters.iter().zip(params).enumerate() {
+            let diagnostics = parameter.schema.validate(param, &self.definitions);
+
+            if !diagnostics.is_empty() {
+                return Err(BlueprintError::InvalidParam {
+                    title: title.to_string(),
+                    index,
+                    name: parameter.title.clone().unwrap_or_default(),
+                    diagnostics,
+                });
+            }
+        }
+
+        self.check_hash(validator)?;
+
+        Ok(script::apply_params(
+            arena,
+            &validator.compiled_code,
+            self.preamble.plutus_version,
+            protocol_version,
+            params,
+        )?)
+    }
+
+    /// Check a datum against the declared schema, if any, before handing
+    /// it to the validator.
+    pub fn check_datum(&self, title: &str, datum: &PlutusData<'_>) -> Result<(), BlueprintError> {
+        let validator = self.validator(title)?;
+
+        self.check_argument(validator, "datum", validator.datum.as_ref(), datum)
+    }
+
+    /// Check a redeemer against the declared schema, if any, before handing
+    /// it to the validator.
+    pub fn check_redeemer(
+        &self,
+        title: &str,
+        redeemer: &PlutusData<'_>,
+    ) -> Result<(), BlueprintError> {
+        let validator = self.validator(title)?;
+
+        self.check_argument(validator, "redeemer", validator.redeemer.as_ref(), redeemer)
+    }
+
+    fn check_argument(
+        &self,
+        validator: &Validator,
+        argument: &'static str,
+        declared: Option<&Argument>,
+        data: &PlutusData<'_>,
+    ) -> Result<(), BlueprintError> {
+        let Some(declared) = declared else {
+            return Ok(());
+        };
+
+        let diagnostics = declared.schema.validate(data, &self.definitions);
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(BlueprintError::InvalidArgument {
+                title: validator.title.clone(),
+                argument,
+                diagnostics,
+            })
+        }
+    }
+
+    fn check_hash(&self, validator: &Validator) -> Result<(), BlueprintError> {
+        let computed = hash::script_hash(
+            self.preamble.plutus_version,
+            script::unwrap_cbor(&validator.compiled_code)?,
+        );
+
+        if computed != validator.hash {
+            return Err(BlueprintError::HashMismatch {
+                title: validator.title.clone(),
+                declared: hex::encode(validator.hash),
+                computed: hex::encode(computed),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn validator(json: &Value) -> Result<Validator, BlueprintError> {
+    let object = json
+        .as_object()
+        .ok_or(BlueprintError::Field("validators"))?;
+
+    let argument = |json: &Value| -> Result<Argument, BlueprintError> {
+        Ok(Argument {
+            title: json
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            schema: Schema::from_json(json.get("schema").ok_or(BlueprintError::Field("schema"))?)?,
+        })
+    };
+
+    let hash = hex::decode(string(object, "hash")?)
+        .ok()
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or(BlueprintError::Field("hash"))?;
+
+    Ok(Validator {
+        title: string(object, "title")?.to_string(),
+        datum: object.get("datum").map(argument).transpose()?,
+        redeemer: object.get("redeemer").map(argument).transpose()?,
+        parameters: object
+            .get("parameters")
+            .map(|parameters| {
+                parameters
+                    .as_array()
+                    .ok_or(BlueprintError::Field("parameters"))?
+                    .iter()
+                    .map(argument)
+                    .collect()
+            })
+            .transpose()?
+            .unwrap_or_default(),
+        compiled_code: hex::decode(string(object, "compiledCode")?)
+            .map_err(|_| BlueprintError::Field("compiledCode"))?,
+        hash,
+    })
+}
+
+fn join(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(Diagnostic::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn string<'j>(
+    object: &'j Map<String, Value>,
+    key: &'static str,
+) -> Result<&'j str, BlueprintError> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or(BlueprintError::Field(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena, data::PlutusData, flat, hash, machine::PlutusVersion, syn::parse_program,
+        term::Term,
+    };
+
+    use super::{Blueprint, BlueprintError, Schema};
+
+    /// Succeeds when its owner parameter signs off on the redeemer, that is
+    /// when the redeemer is `Claim { owner }`.
+    const VALIDATOR: &str = r#"(program 1.1.0
+        (lam owner (lam ctx
+          (force
+            [(force (builtin ifThenElse))
+              [(builtin equalsData)
+                (con data (Constr 0 [B #cafe]))
+                [(builtin constrData) (con integer 0)
+                  [(force (builtin mkCons)) owner (con (list data) [])]]]
+              (delay (con unit ()))
+              (delay (error))]))))"#;
+
+    fn blueprint(hash: Option<&str>) -> String {
+        let arena = Arena::new();
+
+        let program = parse_program(&arena, VALIDATOR).into_result().unwrap();
+
+        let flat = flat::encode(program).unwrap();
+
+        let computed = hex::encode(hash::script_hash(PlutusVersion::V3, &flat));
+
+        format!(
+            r##"{{
+              "preamble": {{
+                "title": "acme/claims",
+                "version": "0.0.0",
+                "plutusVersion": "v3",
+                "compiler": {{ "name": "Aiken", "version": "v1.1.0" }}
+              }},
+              "validators": [
+                {{
+                  "title": "claims.claims.spend",
+                  "redeemer": {{
+                    "title": "redeemer",
+                    "schema": {{ "$ref": "#/definitions/claims~1Action" }}
+                  }},
+                  "parameters": [
+                    {{
+                      "title": "owner",
+                      "schema": {{ "$ref": "#/definitions/ByteArray" }}
+                    }}
+                  ],
+                  "compiledCode": "{}",
+                  "hash": "{}"
+                }}
+              ],
+              "definitions": {{
+                "ByteArray": {{ "dataType": "bytes" }},
+                "claims/Action": {{
+                  "title": "Action",
+                  "anyOf": [
+                    {{
+                      "title": "Claim",
+                      "dataType": "constructor",
+                      "index": 0,
+                      "fields": [{{ "$ref": "#/definitions/ByteArray" }}]
+                    }}
+                  ]
+                }}
+              }}
+            }}"##,
+            hex::encode(hash::wrap_cbor(&flat)),
+            hash.unwrap_or(&computed)
+        )
+    }
+
+    #[test]
+    fn loads_validators() {
+        let blueprint = Blueprint::from_json(&blueprint(None)).unwrap();
+
+        assert_eq!(blueprint.preamble.title, "acme/claims");
+        assert_eq!(blueprint.preamble.plutus_version, PlutusVersion::V3);
+
+        let validator = blueprint.validator("claims.claims.spend").unwrap();
+
+        assert_eq!(
+            validator.redeemer.as_ref().unwrap().schema,
+            Schema::Ref("claims/Action".to_string())
+        );
+        assert_eq!(
+            validator.parameters[0].schema,
+            Schema::Ref("ByteArray".to_string())
+        );
+        assert_eq!(blueprint.definitions.get("ByteArray"), Some(&Schema::Bytes));
+
+        assert!(matches!(
+            blueprint.validator("claims.claims.mint"),
+            Err(BlueprintError::UnknownValidator(_))
+        ));
+    }
+
+    #[test]
+    fn checks_the_declared_hash() {
+        let arena = Arena::new();
+
+        let blueprint = Blueprint::from_json(&blueprint(Some(&"00".repeat(28)))).unwrap();
+
+        assert!(matches!(
+            blueprint.program(&arena, "claims.claims.spend", 10),
+            Err(BlueprintError::HashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn checks_redeemers() {
+        let arena = Arena::new();
+
+        let blueprint = Blueprint::from_json(&blueprint(None)).unwrap();
+
+        let claim = PlutusData::constr(
+            &arena,
+            0,
+            arena.alloc([PlutusData::byte_string(&arena, &[0xca, 0xfe])]),
+        );
+
+        assert!(blueprint
+            .check_redeemer("claims.claims.spend", claim)
+            .is_ok());
+
+        // No datum schema is declared.
+        assert!(blueprint.check_datum("claims.claims.spend", claim).is_ok());
+
+        let claim = PlutusData::constr(
+            &arena,
+            0,
+            arena.alloc([PlutusData::integer_from(&arena, 1)]),
+        );
+
+        match blueprint.check_redeemer("claims.claims.spend", claim) {
+            Err(BlueprintError::InvalidArgument {
+                argument,
+                diagnostics,
+                ..
+            }) => {
+                assert_eq!(argument, "redeemer");
+                assert_eq!(diagnostics[0].to_string(), "fields[0]: expected bytes");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn applies_checked_params() {
+        let arena = Arena::new();
+
+        let blueprint = Blueprint::from_json(&blueprint(None)).unwrap();
+
+        assert!(matches!(
+            blueprint.apply_params(&arena, "claims.claims.spend", 10, &[]),
+            Err(BlueprintError::ParamCount {
+                expected: 1,
+                found: 0,
+                ..
+            })
+        ));
+
+        let integer = PlutusData::integer_from(&arena, 1);
+
+        match blueprint.apply_params(&arena, "claims.claims.spend", 10, &[integer]) {
+            Err(error @ BlueprintError::InvalidParam { .. }) => {
+                assert_eq!(
+                    error.to_string(),
+                    "Parameter 0 (owner) of claims.claims.spend does not match its schema: \
+                     expected bytes"
+                );
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let owner = PlutusData::byte_string(&arena, &[0xca, 0xfe]);
+
+        let applied = blueprint
+            .apply_params(&arena, "claims.claims.spend", 10, &[owner])
+            .unwrap();
+
+        assert_ne!(
+            applied.hash,
+            blueprint.validator("claims.claims.spend").unwrap().hash
+        );
+
+        let result = applied
+            .program
+            .apply(
+                &arena,
+                Term::data(&arena, PlutusData::constr(&arena, 0, &[])),
+            )
+            .eval_version(&arena, PlutusVersion::V3);
+
+        assert!(result.term.is_ok());
+    }
+}
@@ -0,0 +1,532 @@
+//! The subset of JSON schemas CIP-57 uses to describe `Data`.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde_json::{Map, Value};
+
+use crate::data::PlutusData;
+
+use super::BlueprintError;
+
+const DEFINITIONS: &str = "#/definitions/";
+
+/// The keywords a schema of any `Data` may have.
+const ANNOTATIONS: [&str; 2] = ["title", "description"];
+
+/// The JSON schema combinators CIP-57 allows but that are not supported.
+const UNSUPPORTED: [&str; 3] = ["oneOf", "allOf", "not"];
+
+/// The validation keywords CIP-57 allows but that are not enforced: they
+/// are rejected rather than silently dropped.
+const CONSTRAINTS: [&str; 11] = [
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "multipleOf",
+    "minLength",
+    "maxLength",
+    "enum",
+    "minItems",
+    "maxItems",
+    "uniqueItems",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Any `Data`, such as the `Data` definition of Aiken.
+    Any,
+    Integer,
+    Bytes,
+    List(Box<Schema>),
+    /// A list of a fixed length with a schema per item.
+    Tuple(Vec<Schema>),
+    Map {
+        keys: Box<Schema>,
+        values: Box<Schema>,
+    },
+    Constructor {
+        index: u64,
+        fields: Vec<Schema>,
+    },
+    AnyOf(Vec<Schema>),
+    /// A reference to an entry of the blueprint definitions.
+    Ref(String),
+    /// A builtin type that is not `Data`, such as `#integer`: parameters
+    /// of these types cannot be given as `Data`.
+    Builtin(String),
+}
+
+/// The `definitions` of a blueprint, by their unescaped name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Definitions(pub BTreeMap<String, Schema>);
+
+impl Schema {
+    pub(super) fn from_json(json: &Value) -> Result<Self, BlueprintError> {
+        let malformed = |reason: &str| BlueprintError::MalformedSchema(reason.to_string());
+
+        let object = json
+            .as_object()
+            .ok_or_else(|| malformed("schema is not an object"))?;
+
+        if let Some(keyword) = UNSUPPORTED
+            .iter()
+            .chain(&CONSTRAINTS)
+            .find(|k| object.contains_key(**k))
+        {
+            return Err(malformed(&format!("unsupported keyword {keyword}")));
+        }
+
+        if let Some(reference) = object.get("$ref") {
+            let reference = reference
+                .as_str()
+                .and_then(|reference| reference.strip_prefix(DEFINITIONS))
+                .ok_or_else(|| malformed("$ref does not point into #/definitions/"))?;
+
+            return Ok(Schema::Ref(reference.replace("~1", "/").replace("~0", "~")));
+        }
+
+        if let Some(schemas) = object.get("anyOf") {
+            return Ok(Schema::AnyOf(schemas_from_json(schemas)?));
+        }
+
+        let Some(data_type) = object.get("dataType") else {
+            return match object.keys().find(|k| !ANNOTATIONS.contains(&k.as_str())) {
+                Some(keyword) => Err(malformed(&format!("unsupported keyword {keyword}"))),
+                None => Ok(Schema::Any),
+            };
+        };
+
+        match data_type
+            .as_str()
+            .ok_or_else(|| malformed("dataType is not a string"))?
+        {
+            "integer" => Ok(Schema::Integer),
+            "bytes" => Ok(Schema::Bytes),
+            "list" => match object.get("items") {
+                Some(items @ Value::Array(_)) => Ok(Schema::Tuple(schemas_from_json(items)?)),
+                Some(items) => Ok(Schema::List(Box::new(Schema::from_json(items)?))),
+                None => Ok(Schema::List(Box::new(Schema::Any))),
+            },
+            "map" => Ok(Schema::Map {
+                keys: Box::new(optional(object, "keys")?),
+                values: Box::new(optional(object, "values")?),
+            }),
+            "constructor" => Ok(Schema::Constructor {
+                index: object
+                    .get("index")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| malformed("constructor without an index"))?,
+                fields: object
+                    .get("fields")
+                    .map(schemas_from_json)
+                    .transpose()?
+                    .unwrap_or_default(),
+            }),
+            builtin if builtin.starts_with('#') => Ok(Schema::Builtin(builtin.to_string())),
+            other => Err(malformed(&format!("unknown dataType {other}"))),
+        }
+    }
+
+    /// Check that `data` has the shape of this schema, reporting every
+    /// mismatch with its path in the data.
+    pub fn validate(&self, data: &PlutusData<'_>, definitions: &Definitions) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        self.validate_at(data, definitions, &mut String::new(), &mut diagnostics);
+
+        diagnostics
+    }
+
+    fn validate_at(
+        &self,
+        data: &PlutusData<'_>,
+        definitions: &Definitions,
+        path: &mut String,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let schema = match definitions.resolve(self) {
+            Ok(schema) => schema,
+            Err(message) => return report(path, diagnostics, message),
+        };
+
+        let message = match (schema, data) {
+            (Schema::Any, _)
+            | (Schema::Integer, PlutusData::Integer(_))
+            | (Schema::Bytes, PlutusData::ByteString(_)) => return,
+            (Schema::List(item), PlutusData::List(items)) => {
+                for (i, data) in items.iter().enumerate() {
+                    nested(path, &format!("list[{i}]"), |path| {
+                        item.validate_at(data, definitions, path, diagnostics)
+                    });
+                }
+
+                return;
+            }
+            (Schema::Tuple(schemas), PlutusData::List(items)) if schemas.len() != items.len() => {
+                format!(
+                    "expected a list of {} items, found {}",
+                    schemas.len(),
+                    items.len()
+                )
+            }
+            (Schema::Tuple(schemas), PlutusData::List(items)) => {
+                for (i, (schema, data)) in schemas.iter().zip(items.iter()).enumerate() {
+                    nested(path, &format!("list[{i}]"), |path| {
+                        schema.validate_at(data, definitions, path, diagnostics)
+                    });
+                }
+
+                return;
+            }
+            (Schema::Map { keys, values }, PlutusData::Map(entries)) => {
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    nested(path, &format!("map[{i}].key"), |path| {
+                        keys.validate_at(key, definitions, path, diagnostics)
+                    });
+                    nested(path, &format!("map[{i}].value"), |path| {
+                        values.validate_at(value, definitions, path, diagnostics)
+                    });
+                }
+
+                return;
+            }
+            (Schema::Constructor { index, fields }, PlutusData::Constr { tag, fields: data }) => {
+                if index != tag {
+                    format!("expected constructor {index}, found {tag}")
+                } else if fields.len() != data.len() {
+                    format!("expected {} fields, found {}", fields.len(), data.len())
+                } else {
+                    for (i, (schema, data)) in fields.iter().zip(data.iter()).enumerate() {
+                        nested(path, &format!("fields[{i}]"), |path| {
+                            schema.validate_at(data, definitions, path, diagnostics)
+                        });
+                    }
+
+                    return;
+                }
+            }
+            (Schema::AnyOf(schemas), _) => {
+                let attempts: Vec<_> = schemas
+                    .iter()
+                    .map(|schema| {
+                        let mut attempt = Vec::new();
+
+                        schema.validate_at(data, definitions, path, &mut attempt);
+
+                        (schema, attempt)
+                    })
+                    .collect();
+
+                if attempts.iter().any(|(_, attempt)| attempt.is_empty()) {
+                    return;
+                }
+
+                // A value with the index of one of the constructors is most
+                // likely meant to be that one: say what is wrong with it.
+                let chosen: Vec<_> = attempts
+                    .iter()
+                    .filter(|(schema, _)| {
+                        matches!(
+                            (definitions.resolve(schema), data),
+                            (Ok(Schema::Constructor { index, .. }), PlutusData::Constr { tag, .. })
+                                if index == tag
+                        )
+                    })
+                    .collect();
+
+                match chosen[..] {
+                    [(_, chosen)] => return diagnostics.extend(chosen.iter().cloned()),
+                    _ => format!("expected {}", alternatives(schemas, definitions)),
+                }
+            }
+            (Schema::Builtin(name), _) => format!("expected {name}, which is not Data"),
+            (schema, _) => format!("expected {}", kind(schema)),
+        };
+
+        report(path, diagnostics, message)
+    }
+}
+
+/// A mismatch between some data and a schema, such as
+/// `fields[1].map[0].value: expected integer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Empty for the value itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+fn report(path: &str, diagnostics: &mut Vec<Diagnostic>, message: String) {
+    diagnostics.push(Diagnostic {
+        path: path.to_string(),
+        message,
+    })
+}
+
+fn nested(path: &mut String, segment: &str, validate: impl FnOnce(&mut String)) {
+    let len = path.len();
+
+    if len > 0 {
+        path.push('.');
+    }
+
+    path.push_str(segment);
+
+    validate(path);
+
+    path.truncate(len);
+}
+
+fn kind(schema: &Schema) -> &'static str {
+    match schema {
+        Schema::Integer => "integer",
+        Schema::Bytes => "bytes",
+        Schema::List(_) | Schema::Tuple(_) => "list",
+        Schema::Map { .. } => "map",
+        Schema::Constructor { .. } => "constructor",
+        _ => "data",
+    }
+}
+
+fn alternatives(schemas: &[Schema], definitions: &Definitions) -> String {
+    let indices: Option<Vec<_>> = schemas
+        .iter()
+        .map(|schema| match definitions.resolve(schema) {
+            Ok(Schema::Constructor { index, .. }) => Some(index.to_string()),
+            _ => None,
+        })
+        .collect();
+
+    match indices {
+        Some(indices) => format!("one of constructors {}", indices.join(", ")),
+        None => format!("one of {} alternatives", schemas.len()),
+    }
+}
+
+impl Definitions {
+    pub(super) fn from_json(json: Option<&Value>) -> Result<Self, BlueprintError> {
+        let Some(json) = json else {
+            return Ok(Definitions::default());
+        };
+
+        json.as_object()
+            .ok_or_else(|| BlueprintError::MalformedSchema("definitions is not an object".into()))?
+            .iter()
+            .map(|(name, schema)| Ok((name.clone(), Schema::from_json(schema)?)))
+            .collect::<Result<_, _>>()
+            .map(Definitions)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Schema> {
+        self.0.get(name)
+    }
+
+    /// Follow references until a schema that is not one.
+    pub fn resolve<'s>(&'s self, mut schema: &'s Schema) -> Result<&'s Schema, String> {
+        // A chain longer than the definitions goes around in a circle.
+        for _ in 0..=self.0.len() {
+            match schema {
+                Schema::Ref(name) => {
+                    schema = self
+                        .get(name)
+                        .ok_or_else(|| format!("unknown definition {name}"))?;
+                }
+                schema => return Ok(schema),
+            }
+        }
+
+        Err("cyclic definitions".to_string())
+    }
+}
+
+fn schemas_from_json(json: &Value) -> Result<Vec<Schema>, BlueprintError> {
+    json.as_array()
+        .ok_or_else(|| BlueprintError::MalformedSchema("expected an array of schemas".into()))?
+        .iter()
+        .map(Schema::from_json)
+        .collect()
+}
+
+fn optional(object: &Map<String, Value>, key: &str) -> Result<Schema, BlueprintError> {
+    object
+        .get(key)
+        .map(Schema::from_json)
+        .transpose()
+        .map(|schema| schema.unwrap_or(Schema::Any))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{arena::Arena, data::PlutusData};
+
+    use super::{BlueprintError, Definitions, Schema};
+
+    /// The definitions Aiken emits for
+    /// `Order { owner: ByteArray, prices: Dict<ByteArray, Int>, limit: Option<Int> }`.
+    fn definitions() -> Definitions {
+        Definitions::from_json(Some(&json!({
+            "ByteArray": { "dataType": "bytes" },
+            "Int": { "dataType": "integer" },
+            "Pairs$ByteArray_Int": {
+                "dataType": "map",
+                "keys": { "$ref": "#/definitions/ByteArray" },
+                "values": { "$ref": "#/definitions/Int" }
+            },
+            "Option$Int": {
+                "anyOf": [
+                    {
+                        "title": "Some",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [{ "$ref": "#/definitions/Int" }]
+                    },
+                    { "title": "None", "dataType": "constructor", "index": 1, "fields": [] }
+                ]
+            },
+            "orders/Order": {
+                "anyOf": [
+                    {
+                        "title": "Order",
+                        "dataType": "constructor",
+                        "index": 0,
+                        "fields": [
+                            { "title": "owner", "$ref": "#/definitions/ByteArray" },
+                            { "title": "prices", "$ref": "#/definitions/Pairs$ByteArray_Int" },
+                            { "title": "limit", "$ref": "#/definitions/Option$Int" }
+                        ]
+                    }
+                ]
+            }
+        })))
+        .unwrap()
+    }
+
+    fn validate(data: &PlutusData<'_>) -> Vec<String> {
+        Schema::Ref("orders/Order".to_string())
+            .validate(data, &definitions())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_matching_data() {
+        let arena = Arena::new();
+
+        let order = PlutusData::constr(
+            &arena,
+            0,
+            arena.alloc([
+                PlutusData::byte_string(&arena, &[0xca, 0xfe]),
+                PlutusData::map(
+                    &arena,
+                    arena.alloc([(
+                        PlutusData::byte_string(&arena, b"ada"),
+                        PlutusData::integer_from(&arena, 1),
+                    )]),
+                ),
+                PlutusData::constr(&arena, 1, &[]),
+            ]),
+        );
+
+        assert_eq!(validate(order), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_paths_of_mismatches() {
+        let arena = Arena::new();
+
+        let order = PlutusData::constr(
+            &arena,
+            0,
+            arena.alloc([
+                PlutusData::integer_from(&arena, 1),
+                PlutusData::map(
+                    &arena,
+                    arena.alloc([(
+                        PlutusData::byte_string(&arena, b"ada"),
+                        PlutusData::byte_string(&arena, b"1"),
+                    )]),
+                ),
+                PlutusData::constr(&arena, 0, &[]),
+            ]),
+        );
+
+        assert_eq!(
+            validate(order),
+            [
+                "fields[0]: expected bytes",
+                "fields[1].map[0].value: expected integer",
+                "fields[2]: expected 1 fields, found 0",
+            ]
+        );
+
+        let limit = PlutusData::constr(&arena, 2, &[]);
+
+        assert_eq!(
+            Schema::Ref("Option$Int".to_string())
+                .validate(limit, &definitions())
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["expected one of constructors 0, 1"]
+        );
+
+        assert_eq!(
+            validate(PlutusData::list(&arena, &[])),
+            ["expected one of constructors 0"]
+        );
+        assert_eq!(
+            Schema::Ref("Missing".to_string())
+                .validate(limit, &definitions())
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["unknown definition Missing"]
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_keywords() {
+        assert_eq!(
+            Schema::from_json(&json!({ "title": "Data", "description": "Any data" })).unwrap(),
+            Schema::Any
+        );
+
+        for schema in [
+            json!({ "oneOf": [{ "dataType": "integer" }] }),
+            json!({ "allOf": [{ "dataType": "integer" }] }),
+            json!({ "not": { "dataType": "bytes" } }),
+            json!({ "dataType": "integer", "not": { "dataType": "bytes" } }),
+            json!({ "title": "Positive", "minimum": 1 }),
+            json!({ "dataType": "integer", "minimum": 1 }),
+            json!({ "dataType": "integer", "exclusiveMaximum": 256 }),
+            json!({ "dataType": "integer", "multipleOf": 2 }),
+            json!({ "dataType": "bytes", "maxLength": 28 }),
+            json!({ "dataType": "bytes", "enum": ["00", "01"] }),
+            json!({ "maxLength": 28 }),
+            json!({ "dataType": "list", "minItems": 1 }),
+            json!({ "dataType": "list", "items": { "dataType": "integer" }, "uniqueItems": true }),
+            json!({ "uniqueItems": true }),
+        ] {
+            assert!(matches!(
+                Schema::from_json(&schema),
+                Err(BlueprintError::MalformedSchema(message)) if message.starts_with("unsupported keyword")
+            ));
+        }
+    }
+}
@@ -241,6 +241,116 @@ impl DefaultFunction {
         }
     }
 
+    /// The name of the builtin in textual UPLC and in the Plutus cost model
+    /// files.
+    pub fn name(&self) -> &'static str {
+        use DefaultFunction::*;
+
+        match self {
+            AddInteger => "addInteger",
+            SubtractInteger => "subtractInteger",
+            EqualsInteger => "equalsInteger",
+            LessThanEqualsInteger => "lessThanEqualsInteger",
+            MultiplyInteger => "multiplyInteger",
+            DivideInteger => "divideInteger",
+            QuotientInteger => "quotientInteger",
+            RemainderInteger => "remainderInteger",
+            ModInteger => "modInteger",
+            LessThanInteger => "lessThanInteger",
+            IfThenElse => "ifThenElse",
+            AppendByteString => "appendByteString",
+            EqualsByteString => "equalsByteString",
+            ConsByteString => "consByteString",
+            SliceByteString => "sliceByteString",
+            LengthOfByteString => "lengthOfByteString",
+            IndexByteString => "indexByteString",
+            LessThanByteString => "lessThanByteString",
+            LessThanEqualsByteString => "lessThanEqualsByteString",
+            Sha2_256 => "sha2_256",
+            Sha3_256 => "sha3_256",
+            Blake2b_256 => "blake2b_256",
+            Keccak_256 => "keccak_256",
+            Blake2b_224 => "blake2b_224",
+            VerifyEd25519Signature => "verifyEd25519Signature",
+            VerifyEcdsaSecp256k1Signature => "verifyEcdsaSecp256k1Signature",
+            VerifySchnorrSecp256k1Signature => "verifySchnorrSecp256k1Signature",
+            AppendString => "appendString",
+            EqualsString => "equalsString",
+            EncodeUtf8 => "encodeUtf8",
+            DecodeUtf8 => "decodeUtf8",
+            ChooseUnit => "chooseUnit",
+            Trace => "trace",
+            FstPair => "fstPair",
+            SndPair => "sndPair",
+            ChooseList => "chooseList",
+            MkCons => "mkCons",
+            HeadList => "headList",
+            TailList => "tailList",
+            NullList => "nullList",
+            ChooseData => "chooseData",
+            ConstrData => "constrData",
+            MapData => "mapData",
+            ListData => "listData",
+            IData => "iData",
+            BData => "bData",
+            UnConstrData => "unConstrData",
+            UnMapData => "unMapData",
+            UnListData => "unListData",
+            UnIData => "unIData",
+            UnBData => "unBData",
+            EqualsData => "equalsData",
+            MkPairData => "mkPairData",
+            MkNilData => "mkNilData",
+            MkNilPairData => "mkNilPairData",
+            SerialiseData => "serialiseData",
+            Bls12_381_G1_Add => "bls12_381_G1_add",
+            Bls12_381_G1_Neg => "bls12_381_G1_neg",
+            Bls12_381_G1_ScalarMul => "bls12_381_G1_scalarMul",
+            Bls12_381_G1_Equal => "bls12_381_G1_equal",
+            Bls12_381_G1_Compress => "bls12_381_G1_compress",
+            Bls12_381_G1_Uncompress => "bls12_381_G1_uncompress",
+            Bls12_381_G1_HashToGroup => "bls12_381_G1_hashToGroup",
+            Bls12_381_G2_Add => "bls12_381_G2_add",
+            Bls12_381_G2_Neg => "bls12_381_G2_neg",
+            Bls12_381_G2_ScalarMul => "bls12_381_G2_scalarMul",
+            Bls12_381_G2_Equal => "bls12_381_G2_equal",
+            Bls12_381_G2_Compress => "bls12_381_G2_compress",
+            Bls12_381_G2_Uncompress => "bls12_381_G2_uncompress",
+            Bls12_381_G2_HashToGroup => "bls12_381_G2_hashToGroup",
+            Bls12_381_MillerLoop => "bls12_381_millerLoop",
+            Bls12_381_MulMlResult => "bls12_381_mulMlResult",
+            Bls12_381_FinalVerify => "bls12_381_finalVerify",
+            IntegerToByteString => "integerToByteString",
+            ByteStringToInteger => "byteStringToInteger",
+            AndByteString => "andByteString",
+            OrByteString => "orByteString",
+            XorByteString => "xorByteString",
+            ComplementByteString => "complementByteString",
+            ReadBit => "readBit",
+            WriteBits => "writeBits",
+            ReplicateByte => "replicateByte",
+            ShiftByteString => "shiftByteString",
+            RotateByteString => "rotateByteString",
+            CountSetBits => "countSetBits",
+            FindFirstSetBit => "findFirstSetBit",
+            Ripemd_160 => "ripemd_160",
+            ExpModInteger => "expModInteger",
+            DropList => "dropList",
+            LengthOfArray => "lengthOfArray",
+            ListToArray => "listToArray",
+            IndexArray => "indexArray",
+            Bls12_381_G1_MultiScalarMul => "bls12_381_G1_multiScalarMul",
+            Bls12_381_G2_MultiScalarMul => "bls12_381_G2_multiScalarMul",
+            InsertCoin => "insertCoin",
+            LookupCoin => "lookupCoin",
+            UnionValue => "unionValue",
+            ValueContains => "valueContains",
+            ValueData => "valueData",
+            UnValueData => "unValueData",
+            ScaleValue => "scaleValue",
+        }
+    }
+
     pub fn arity(&self) -> usize {
         match self {
             DefaultFunction::AddInteger => 2,
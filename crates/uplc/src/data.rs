@@ -3,9 +3,20 @@ use crate::{
     binder::Eval,
     constant::{integer_from, Constant, Integer},
     flat::Ctx,
+    hash::{self, DatumHash},
     machine::MachineError,
 };
 
+mod diff;
+mod json;
+mod raw;
+mod strict;
+
+pub use diff::{DataDifference, DataDifferenceKind};
+pub use json::DataJsonError;
+pub use raw::RawPlutusData;
+pub use strict::DataDecodeError;
+
 #[derive(Debug, PartialEq)]
 pub enum PlutusData<'a> {
     Constr {
@@ -65,6 +76,29 @@ impl<'a> PlutusData<'a> {
         )
     }
 
+    /// Decode with the rules of the reference ledger, rejecting what
+    /// [`PlutusData::from_cbor`] lets through.
+    pub fn from_cbor_strict(
+        arena: &'a Arena,
+        cbor: &'_ [u8],
+    ) -> Result<&'a PlutusData<'a>, DataDecodeError> {
+        strict::decode(arena, cbor)
+    }
+
+    /// Strict decoding that keeps the bytes of every node, for exact
+    /// re-serialisation and hashing.
+    pub fn from_cbor_raw(
+        arena: &'a Arena,
+        cbor: &'_ [u8],
+    ) -> Result<&'a RawPlutusData<'a>, DataDecodeError> {
+        strict::decode_raw(arena, cbor)
+    }
+
+    /// The datum hash of this data with its canonical encoding.
+    pub fn hash(&self) -> DatumHash {
+        hash::datum_hash(self)
+    }
+
     pub fn unwrap_constr<V>(
         &'a self,
     ) -> Result<(&'a u64, &'a [&'a PlutusData<'a>]), MachineError<'a, V>>
@@ -0,0 +1,397 @@
+//! Where two `Data` values differ, for "datum mismatch" debugging.
+
+use std::{collections::HashMap, fmt};
+
+use serde_json::{json, Value as Json};
+
+use crate::constant::Integer;
+
+use super::PlutusData;
+
+/// One place where two values differ, such as
+/// `fields[1].map[0].value: integer 1 != 2`. The entries of maps with the
+/// same keys in another order are paired by key, so their path names both
+/// positions, as in `map[0->3].value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataDifference {
+    /// Empty for the values themselves.
+    pub path: String,
+    pub kind: DataDifferenceKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataDifferenceKind {
+    /// Different kinds of data, such as a list and a map.
+    Kind {
+        left: &'static str,
+        right: &'static str,
+    },
+    Tag {
+        left: u64,
+        right: u64,
+    },
+    /// Different numbers of fields, items or entries. The common prefix is
+    /// still compared.
+    Length {
+        left: usize,
+        right: usize,
+    },
+    /// The same keys in another order. Values are compared by key.
+    KeyOrder,
+    Integer {
+        left: Integer,
+        right: Integer,
+    },
+    Bytes {
+        left: Vec<u8>,
+        right: Vec<u8>,
+    },
+}
+
+impl<'a> PlutusData<'a> {
+    /// Every place where `other` differs from `self`, outermost first.
+    pub fn diff(&self, other: &PlutusData<'_>) -> Vec<DataDifference> {
+        let mut differences = Vec::new();
+
+        diff(self, other, &mut String::new(), &mut differences);
+
+        differences
+    }
+}
+
+impl DataDifference {
+    pub fn to_json(&self) -> Json {
+        let (kind, left, right) = match &self.kind {
+            DataDifferenceKind::Kind { left, right } => ("kind", json!(left), json!(right)),
+            DataDifferenceKind::Tag { left, right } => ("tag", json!(left), json!(right)),
+            DataDifferenceKind::Length { left, right } => ("length", json!(left), json!(right)),
+            DataDifferenceKind::KeyOrder => ("key_order", Json::Null, Json::Null),
+            DataDifferenceKind::Integer { left, right } => {
+                ("integer", json!(left.to_string()), json!(right.to_string()))
+            }
+            DataDifferenceKind::Bytes { left, right } => {
+                ("bytes", json!(hex::encode(left)), json!(hex::encode(right)))
+            }
+        };
+
+        json!({ "path": self.path, "kind": kind, "left": left, "right": right })
+    }
+}
+
+impl fmt::Display for DataDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+
+        match &self.kind {
+            DataDifferenceKind::Kind { left, right } => write!(f, "{left} != {right}"),
+            DataDifferenceKind::Tag { left, right } => write!(f, "constructor {left} != {right}"),
+            DataDifferenceKind::Length { left, right } => write!(f, "length {left} != {right}"),
+            DataDifferenceKind::KeyOrder => write!(f, "same keys in another order"),
+            DataDifferenceKind::Integer { left, right } => write!(f, "integer {left} != {right}"),
+            DataDifferenceKind::Bytes { left, right } => {
+                write!(f, "bytes #{} != #{}", hex::encode(left), hex::encode(right))
+            }
+        }
+    }
+}
+
+fn diff(
+    left: &PlutusData<'_>,
+    right: &PlutusData<'_>,
+    path: &mut String,
+    differences: &mut Vec<DataDifference>,
+) {
+    if left == right {
+        return;
+    }
+
+    let mut report = |path: &str, kind| {
+        differences.push(DataDifference {
+            path: path.to_string(),
+            kind,
+        })
+    };
+
+    match (left, right) {
+        (
+            PlutusData::Constr {
+                tag: left_tag,
+                fields: left,
+            },
+            PlutusData::Constr {
+                tag: right_tag,
+                fields: right,
+            },
+        ) => {
+            if left_tag != right_tag {
+                report(
+                    path,
+                    DataDifferenceKind::Tag {
+                        left: *left_tag,
+                        right: *right_tag,
+                    },
+                );
+            }
+
+            items(left, right, "fields", path, differences);
+        }
+        (PlutusData::List(left), PlutusData::List(right)) => {
+            items(left, right, "list", path, differences)
+        }
+        (PlutusData::Map(left), PlutusData::Map(right)) => entries(left, right, path, differences),
+        (PlutusData::Integer(left), PlutusData::Integer(right)) => report(
+            path,
+            DataDifferenceKind::Integer {
+                left: (*left).clone(),
+                right: (*right).clone(),
+            },
+        ),
+        (PlutusData::ByteString(left), PlutusData::ByteString(right)) => report(
+            path,
+            DataDifferenceKind::Bytes {
+                left: left.to_vec(),
+                right: right.to_vec(),
+            },
+        ),
+        _ => report(
+            path,
+            DataDifferenceKind::Kind {
+                left: kind(left),
+                right: kind(right),
+            },
+        ),
+    }
+}
+
+fn items(
+    left: &[&PlutusData<'_>],
+    right: &[&PlutusData<'_>],
+    segment: &str,
+    path: &mut String,
+    differences: &mut Vec<DataDifference>,
+) {
+    if left.len() != right.len() {
+        differences.push(DataDifference {
+            path: path.clone(),
+            kind: DataDifferenceKind::Length {
+                left: left.len(),
+                right: right.len(),
+            },
+        });
+    }
+
+    for (i, (left, right)) in left.iter().zip(right.iter()).enumerate() {
+        nested(path, &format!("{segment}[{i}]"), |path| {
+            diff(left, right, path, differences)
+        });
+    }
+}
+
+type Entry<'l> = (&'l PlutusData<'l>, &'l PlutusData<'l>);
+
+fn entries(
+    left: &[Entry<'_>],
+    right: &[Entry<'_>],
+    path: &mut String,
+    differences: &mut Vec<DataDifference>,
+) {
+    let reordered =
+        permutation(left, right).filter(|_| left.iter().zip(right.iter()).any(|(l, r)| l.0 != r.0));
+
+    if let Some(positions) = reordered {
+        differences.push(DataDifference {
+            path: path.clone(),
+            kind: DataDifferenceKind::KeyOrder,
+        });
+
+        for (i, ((_, left), j)) in left.iter().zip(positions).enumerate() {
+            nested(path, &format!("map[{i}->{j}].value"), |path| {
+                diff(left, right[j].1, path, differences)
+            });
+        }
+
+        return;
+    }
+
+    if left.len() != right.len() {
+        differences.push(DataDifference {
+            path: path.clone(),
+            kind: DataDifferenceKind::Length {
+                left: left.len(),
+                right: right.len(),
+            },
+        });
+    }
+
+    for (i, ((left_key, left), (right_key, right))) in left.iter().zip(right.iter()).enumerate() {
+        nested(path, &format!("map[{i}].key"), |path| {
+            diff(left_key, right_key, path, differences)
+        });
+        nested(path, &format!("map[{i}].value"), |path| {
+            diff(left, right, path, differences)
+        });
+    }
+}
+
+/// The position in `right` of the key of every entry of `left`, when the
+/// keys of `right` are those of `left` in some order. Each entry of `right`
+/// is used once, so duplicate keys must come as many times on both sides.
+fn permutation(left: &[Entry<'_>], right: &[Entry<'_>]) -> Option<Vec<usize>> {
+    if left.len() != right.len() {
+        return None;
+    }
+
+    // Keys are indexed by their encoding, which is equal exactly when the
+    // keys are. Positions are stacked last first to hand out the first one.
+    let mut positions: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+
+    for (j, (key, _)) in right.iter().enumerate().rev() {
+        positions
+            .entry(minicbor::to_vec(*key).ok()?)
+            .or_default()
+            .push(j);
+    }
+
+    left.iter()
+        .map(|(key, _)| positions.get_mut(&minicbor::to_vec(*key).ok()?)?.pop())
+        .collect()
+}
+
+fn nested(path: &mut String, segment: &str, f: impl FnOnce(&mut String)) {
+    let len = path.len();
+
+    if len > 0 {
+        path.push('.');
+    }
+
+    path.push_str(segment);
+
+    f(path);
+
+    path.truncate(len);
+}
+
+fn kind(data: &PlutusData<'_>) -> &'static str {
+    match data {
+        PlutusData::Constr { .. } => "constructor",
+        PlutusData::Map(_) => "map",
+        PlutusData::List(_) => "list",
+        PlutusData::Integer(_) => "integer",
+        PlutusData::ByteString(_) => "bytes",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{arena::Arena, data::PlutusData};
+
+    fn diff(left: &str, right: &str) -> Vec<String> {
+        let arena = Arena::new();
+
+        PlutusData::from_json_str(&arena, left)
+            .unwrap()
+            .diff(PlutusData::from_json_str(&arena, right).unwrap())
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn reports_paths_of_differences() {
+        assert_eq!(diff(r#"{"int": 1}"#, r#"{"int": 1}"#), Vec::<String>::new());
+
+        assert_eq!(
+            diff(
+                r#"{"constructor": 0, "fields": [
+                    {"bytes": "cafe"},
+                    {"map": [{"k": {"int": 1}, "v": {"int": 1}}]},
+                    {"list": [{"int": 1}]}
+                ]}"#,
+                r#"{"constructor": 1, "fields": [
+                    {"bytes": "cafd"},
+                    {"map": [{"k": {"int": 1}, "v": {"int": 2}}]},
+                    {"list": [{"int": 1}, {"int": 2}]}
+                ]}"#,
+            ),
+            [
+                "constructor 0 != 1",
+                "fields[0]: bytes #cafe != #cafd",
+                "fields[1].map[0].value: integer 1 != 2",
+                "fields[2]: length 1 != 2",
+            ]
+        );
+
+        assert_eq!(diff(r#"{"list": []}"#, r#"{"map": []}"#), ["list != map"]);
+    }
+
+    #[test]
+    fn reports_reordered_keys() {
+        assert_eq!(
+            diff(
+                r#"{"map": [{"k": {"int": 1}, "v": {"int": 1}}, {"k": {"int": 2}, "v": {"int": 2}}]}"#,
+                r#"{"map": [{"k": {"int": 2}, "v": {"int": 2}}, {"k": {"int": 1}, "v": {"int": 3}}]}"#,
+            ),
+            [
+                "same keys in another order",
+                "map[0->1].value: integer 1 != 3"
+            ]
+        );
+
+        assert_eq!(
+            diff(
+                r#"{"map": [{"k": {"int": 1}, "v": {"int": 1}}, {"k": {"int": 1}, "v": {"int": 2}}]}"#,
+                r#"{"map": [{"k": {"int": 1}, "v": {"int": 1}}, {"k": {"int": 2}, "v": {"int": 2}}]}"#,
+            ),
+            ["map[1].key: integer 1 != 2"]
+        );
+        assert_eq!(
+            diff(
+                r#"{"map": [{"k": {"int": 1}, "v": {"int": 1}}, {"k": {"int": 1}, "v": {"int": 2}}, {"k": {"int": 2}, "v": {"int": 3}}]}"#,
+                r#"{"map": [{"k": {"int": 2}, "v": {"int": 3}}, {"k": {"int": 1}, "v": {"int": 1}}, {"k": {"int": 1}, "v": {"int": 2}}]}"#,
+            ),
+            ["same keys in another order"]
+        );
+
+        let entry = |k: usize| format!(r#"{{"k": {{"int": {k}}}, "v": {{"int": {k}}}}}"#);
+        let map = |keys: Vec<usize>| {
+            let entries: Vec<String> = keys.into_iter().map(entry).collect();
+
+            format!(r#"{{"map": [{}]}}"#, entries.join(","))
+        };
+
+        assert_eq!(
+            diff(
+                &map((0..20_000).collect()),
+                &map((0..20_000).rev().collect())
+            ),
+            ["same keys in another order"]
+        );
+    }
+
+    #[test]
+    fn renders_json() {
+        let arena = Arena::new();
+
+        let left = PlutusData::integer(
+            &arena,
+            arena.alloc_integer("340282366920938463463374607431768211456".parse().unwrap()),
+        );
+
+        let differences = left.diff(PlutusData::integer_from(&arena, 0));
+
+        assert_eq!(
+            differences[0].to_json(),
+            json!({
+                "path": "",
+                "kind": "integer",
+                "left": "340282366920938463463374607431768211456",
+                "right": "0"
+            })
+        );
+    }
+}
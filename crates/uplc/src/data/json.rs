@@ -0,0 +1,444 @@
+//! The "detailed schema" JSON of cardano-cli: `{"constructor": 0, "fields":
+//! [...]}`, `{"map": [{"k": .., "v": ..}]}`, `{"list": [...]}`, `{"int": ..}`
+//! and `{"bytes": "cafe"}`.
+//!
+//! Integers are JSON numbers of any size. A [`Json`] value only holds 64-bit
+//! numbers, so [`PlutusData::to_json`] writes larger ones as decimal strings,
+//! which [`PlutusData::from_json`] reads back. The text conversions keep them
+//! as numbers, and none goes through a float.
+
+use std::fmt;
+
+use bumpalo::collections::Vec as BumpVec;
+use num::{Signed, ToPrimitive};
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser, Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{value::RawValue, Map, Value as Json};
+
+use crate::{arena::Arena, constant::Integer};
+
+use super::PlutusData;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DataJsonError {
+    #[error("Malformed JSON: {0}")]
+    Json(String),
+    #[error("{reason} at {path}")]
+    Malformed { path: String, reason: String },
+}
+
+impl<'a> PlutusData<'a> {
+    /// Read detailed JSON text, keeping integers of any size exact.
+    pub fn from_json_str(arena: &'a Arena, json: &str) -> Result<&'a Self, DataJsonError> {
+        let Exact(json) =
+            serde_json::from_str(json).map_err(|error| DataJsonError::Json(error.to_string()))?;
+
+        Self::from_json(arena, &json)
+    }
+
+    pub fn from_json(arena: &'a Arena, json: &Json) -> Result<&'a Self, DataJsonError> {
+        from_json(arena, json, &mut String::from("$"))
+    }
+
+    /// Write detailed JSON text, with integers of any size as numbers.
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(&Numbers(&self.to_json(), false))
+            .expect("detailed JSON always serializes")
+    }
+
+    /// Detailed JSON, with integers beyond 64 bits as decimal strings.
+    pub fn to_json(&self) -> Json {
+        let mut object = Map::new();
+
+        match self {
+            PlutusData::Constr { tag, fields } => {
+                object.insert("constructor".into(), Json::from(*tag));
+                object.insert("fields".into(), list(fields));
+            }
+            PlutusData::Map(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        let mut entry = Map::new();
+
+                        entry.insert("k".into(), k.to_json());
+                        entry.insert("v".into(), v.to_json());
+
+                        Json::Object(entry)
+                    })
+                    .collect();
+
+                object.insert("map".into(), Json::Array(entries));
+            }
+            PlutusData::List(items) => {
+                object.insert("list".into(), list(items));
+            }
+            PlutusData::Integer(i) => {
+                object.insert("int".into(), int_json(i));
+            }
+            PlutusData::ByteString(bytes) => {
+                object.insert("bytes".into(), Json::String(hex::encode(bytes)));
+            }
+        }
+
+        Json::Object(object)
+    }
+}
+
+fn list(items: &[&PlutusData<'_>]) -> Json {
+    Json::Array(items.iter().map(|item| item.to_json()).collect())
+}
+
+fn from_json<'a>(
+    arena: &'a Arena,
+    json: &Json,
+    path: &mut String,
+) -> Result<&'a PlutusData<'a>, DataJsonError> {
+    let malformed = |path: &str, reason: &str| DataJsonError::Malformed {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let object = json
+        .as_object()
+        .ok_or_else(|| malformed(path, "expected an object"))?;
+
+    let mut keys: Vec<_> = object.keys().map(String::as_str).collect();
+
+    keys.sort_unstable();
+
+    match keys[..] {
+        ["constructor", "fields"] => {
+            let tag = object["constructor"]
+                .as_u64()
+                .ok_or_else(|| malformed(path, "constructor is not a 64-bit natural"))?;
+
+            let fields = items(arena, &object["fields"], path, "fields")?;
+
+            Ok(PlutusData::constr(arena, tag, fields))
+        }
+        ["map"] => {
+            let entries = object["map"]
+                .as_array()
+                .ok_or_else(|| malformed(path, "map is not an array"))?;
+
+            let mut pairs = BumpVec::with_capacity_in(entries.len(), arena.as_bump());
+
+            for (i, entry) in entries.iter().enumerate() {
+                let pair = nested(path, &format!(".map[{i}]"), |path| {
+                    let (k, v) = match entry.as_object() {
+                        Some(entry) if entry.len() == 2 => entry.get("k").zip(entry.get("v")),
+                        _ => None,
+                    }
+                    .ok_or_else(|| malformed(path, "expected an object of k and v"))?;
+
+                    Ok((
+                        nested(path, ".k", |path| from_json(arena, k, path))?,
+                        nested(path, ".v", |path| from_json(arena, v, path))?,
+                    ))
+                })?;
+
+                pairs.push(pair);
+            }
+
+            Ok(PlutusData::map(arena, arena.alloc(pairs)))
+        }
+        ["list"] => Ok(PlutusData::list(
+            arena,
+            items(arena, &object["list"], path, "list")?,
+        )),
+        ["int"] => {
+            let i: Integer = match &object["int"] {
+                Json::Number(n) => n
+                    .as_i64()
+                    .map(Integer::from)
+                    .or_else(|| n.as_u64().map(Integer::from)),
+                Json::String(s) => s.parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| malformed(path, "int is not an integer"))?;
+
+            Ok(PlutusData::integer(arena, arena.alloc_integer(i)))
+        }
+        ["bytes"] => {
+            let bytes = object["bytes"]
+                .as_str()
+                .and_then(|bytes| hex::decode(bytes).ok())
+                .ok_or_else(|| malformed(path, "bytes is not a hex string"))?;
+
+            Ok(PlutusData::byte_string(
+                arena,
+                arena.alloc(BumpVec::from_iter_in(bytes, arena.as_bump())),
+            ))
+        }
+        _ => Err(malformed(
+            path,
+            "expected one of constructor and fields, map, list, int or bytes",
+        )),
+    }
+}
+
+fn items<'a>(
+    arena: &'a Arena,
+    json: &Json,
+    path: &mut String,
+    key: &str,
+) -> Result<&'a [&'a PlutusData<'a>], DataJsonError> {
+    let items = json.as_array().ok_or_else(|| DataJsonError::Malformed {
+        path: path.clone(),
+        reason: format!("{key} is not an array"),
+    })?;
+
+    let mut data = BumpVec::with_capacity_in(items.len(), arena.as_bump());
+
+    for (i, item) in items.iter().enumerate() {
+        data.push(nested(path, &format!(".{key}[{i}]"), |path| {
+            from_json(arena, item, path)
+        })?);
+    }
+
+    Ok(arena.alloc(data))
+}
+
+fn int_json(i: &Integer) -> Json {
+    if let Some(i) = i.to_i64() {
+        Json::from(i)
+    } else if let (false, Some(u)) = (i.is_negative(), i.to_u64()) {
+        Json::from(u)
+    } else {
+        Json::String(i.to_string())
+    }
+}
+
+/// JSON read with the raw token of every `int`, so that integers beyond 64
+/// bits become decimal strings rather than floats.
+struct Exact(Json);
+
+impl<'de> Deserialize<'de> for Exact {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ExactVisitor).map(Exact)
+    }
+}
+
+struct ExactVisitor;
+
+impl<'de> Visitor<'de> for ExactVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Json, E> {
+        Ok(Json::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Json, E> {
+        Ok(Json::from(i))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Json, E> {
+        Ok(Json::from(u))
+    }
+
+    fn visit_f64<E>(self, f: f64) -> Result<Json, E> {
+        Ok(Json::from(f))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Json, E> {
+        Ok(Json::from(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut items = Vec::new();
+
+        while let Some(Exact(item)) = seq.next_element()? {
+            items.push(item);
+        }
+
+        Ok(Json::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut object = Map::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            let value = if key == "int" {
+                let raw: Box<RawValue> = map.next_value()?;
+
+                match raw.get().parse() {
+                    Ok(i) => int_json(&i),
+                    Err(_) => {
+                        serde_json::from_str::<Exact>(raw.get())
+                            .map_err(de::Error::custom)?
+                            .0
+                    }
+                }
+            } else {
+                map.next_value::<Exact>()?.0
+            };
+
+            object.insert(key, value);
+        }
+
+        Ok(Json::Object(object))
+    }
+}
+
+/// Detailed JSON written with the decimal strings of `int`s as numbers.
+struct Numbers<'j>(&'j Json, bool);
+
+impl Serialize for Numbers<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Numbers(Json::String(i), true) => RawValue::from_string(i.clone())
+                .map_err(ser::Error::custom)?
+                .serialize(serializer),
+            Numbers(Json::Array(items), _) => {
+                serializer.collect_seq(items.iter().map(|item| Numbers(item, false)))
+            }
+            Numbers(Json::Object(object), _) => serializer.collect_map(
+                object
+                    .iter()
+                    .map(|(key, value)| (key, Numbers(value, key == "int"))),
+            ),
+            Numbers(json, _) => json.serialize(serializer),
+        }
+    }
+}
+
+fn nested<T>(path: &mut String, segment: &str, f: impl FnOnce(&mut String) -> T) -> T {
+    let len = path.len();
+
+    path.push_str(segment);
+
+    let result = f(path);
+
+    path.truncate(len);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{arena::Arena, data::PlutusData};
+
+    use super::DataJsonError;
+
+    #[test]
+    fn round_trips_detailed_json() {
+        let arena = Arena::new();
+
+        let json = json!({
+            "constructor": 1,
+            "fields": [
+                { "bytes": "cafe" },
+                { "list": [{ "int": -1 }, { "int": 0 }] },
+                { "map": [{ "k": { "bytes": "" }, "v": { "constructor": 0, "fields": [] } }] }
+            ]
+        });
+
+        let data = PlutusData::from_json(&arena, &json).unwrap();
+
+        assert_eq!(
+            data,
+            PlutusData::constr(
+                &arena,
+                1,
+                arena.alloc([
+                    PlutusData::byte_string(&arena, &[0xca, 0xfe]),
+                    PlutusData::list(
+                        &arena,
+                        arena.alloc([
+                            PlutusData::integer_from(&arena, -1),
+                            PlutusData::integer_from(&arena, 0),
+                        ])
+                    ),
+                    PlutusData::map(
+                        &arena,
+                        arena.alloc([(
+                            PlutusData::byte_string(&arena, &[]),
+                            PlutusData::constr(&arena, 0, &[]),
+                        )])
+                    ),
+                ])
+            )
+        );
+        assert_eq!(data.to_json(), json);
+    }
+
+    #[test]
+    fn keeps_big_integers_exact() {
+        let arena = Arena::new();
+
+        let text = r#"{"int":-340282366920938463463374607431768211457}"#;
+
+        let data = PlutusData::from_json_str(&arena, text).unwrap();
+
+        assert_eq!(
+            data,
+            PlutusData::integer(
+                &arena,
+                arena.alloc_integer("-340282366920938463463374607431768211457".parse().unwrap())
+            )
+        );
+        assert_eq!(data.to_json_string(), text);
+        assert_eq!(
+            PlutusData::from_json(&arena, &data.to_json()).unwrap(),
+            data
+        );
+        assert_eq!(
+            data.to_json(),
+            json!({ "int": "-340282366920938463463374607431768211457" })
+        );
+
+        let text = r#"{"list":[{"int":18446744073709551616},{"int":-1},{"bytes":"00"}]}"#;
+
+        assert_eq!(
+            PlutusData::from_json_str(&arena, text)
+                .unwrap()
+                .to_json_string(),
+            text
+        );
+    }
+
+    #[test]
+    fn reports_where_the_json_is_malformed() {
+        let arena = Arena::new();
+
+        let malformed = |json| match PlutusData::from_json(&arena, &json) {
+            Err(DataJsonError::Malformed { path, reason }) => format!("{path}: {reason}"),
+            other => panic!("unexpected {other:?}"),
+        };
+
+        assert_eq!(
+            malformed(json!({ "list": [{ "int": 1 }, { "bytes": "xyz" }] })),
+            "$.list[1]: bytes is not a hex string"
+        );
+        assert_eq!(
+            malformed(json!({ "map": [{ "k": { "int": 1 }, "v": { "int": 1.5 } }] })),
+            "$.map[0].v: int is not an integer"
+        );
+        assert_eq!(
+            malformed(json!({ "constructor": -1, "fields": [] })),
+            "$: constructor is not a 64-bit natural"
+        );
+        assert_eq!(
+            malformed(json!({ "int": 1, "bytes": "" })),
+            "$: expected one of constructor and fields, map, list, int or bytes"
+        );
+        assert!(matches!(
+            PlutusData::from_json_str(&arena, "{"),
+            Err(DataJsonError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::hash::{self, DatumHash};
+
+use super::PlutusData;
+
+/// Data decoded from the wire along with the exact bytes of every node, so
+/// that it re-encodes and hashes like the original even when the encoder
+/// would make different choices (definite lengths, chunking, tags).
+#[derive(Debug, PartialEq)]
+pub struct RawPlutusData<'a> {
+    pub data: &'a PlutusData<'a>,
+    pub cbor: &'a [u8],
+    /// The constructor fields or list items, or the keys and values of a
+    /// map interleaved.
+    pub children: &'a [&'a RawPlutusData<'a>],
+}
+
+impl RawPlutusData<'_> {
+    /// The datum hash of the original bytes, as the ledger computes it.
+    pub fn hash(&self) -> DatumHash {
+        hash::blake2b_256(self.cbor)
+    }
+}
+
+impl<C> minicbor::encode::Encode<C> for RawPlutusData<'_> {
+    fn encode<W: minicbor::encode::Write>(
+        &self,
+        e: &mut minicbor::Encoder<W>,
+        _ctx: &mut C,
+    ) -> Result<(), minicbor::encode::Error<W::Error>> {
+        e.writer_mut()
+            .write_all(self.cbor)
+            .map_err(minicbor::encode::Error::write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{arena::Arena, data::PlutusData};
+
+    #[test]
+    fn keeps_the_original_encoding() {
+        let arena = Arena::new();
+
+        // A definite list of fields, a chunked short byte string and a
+        // bignum for a small integer: none of which the encoder produces.
+        let cbor = hex::decode("d879835f4101ffc24101a1009f01ff").unwrap();
+
+        let raw = PlutusData::from_cbor_raw(&arena, &cbor).unwrap();
+
+        assert_eq!(raw.cbor, &cbor[..]);
+        assert_eq!(minicbor::to_vec(raw).unwrap(), cbor);
+        assert_ne!(minicbor::to_vec(raw.data).unwrap(), cbor);
+        assert_eq!(raw.data, PlutusData::from_cbor(&arena, &cbor).unwrap());
+
+        let fields: Vec<_> = raw
+            .children
+            .iter()
+            .map(|child| hex::encode(child.cbor))
+            .collect();
+
+        assert_eq!(fields, ["5f4101ff", "c24101", "a1009f01ff"]);
+
+        let map = raw.children[2];
+
+        assert_eq!(map.children.len(), 2);
+        assert_eq!(map.children[1].cbor, [0x9f, 0x01, 0xff]);
+        assert_eq!(map.children[1].children[0].cbor, [0x01]);
+    }
+}
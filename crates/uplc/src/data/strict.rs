@@ -0,0 +1,441 @@
+//! Decoding of `Data` CBOR with the rules of the reference ledger, see
+//! `PlutusCore.Data.decodeData`: byte strings and bignum payloads come in
+//! chunks of at most 64 bytes, constructor indices of tag 102 are unsigned
+//! 64-bit integers and nothing may follow the encoded value.
+
+use bumpalo::collections::Vec as BumpVec;
+use minicbor::{data::Type, Decoder};
+
+use crate::{arena::Arena, constant::Integer};
+
+use super::{PlutusData, RawPlutusData};
+
+const CHUNK_SIZE: usize = 64;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DataDecodeError {
+    #[error("Malformed CBOR: {0}")]
+    Cbor(String),
+    #[error("Byte string chunk of {length} bytes at offset {offset} exceeds {CHUNK_SIZE} bytes")]
+    ChunkTooLong { offset: usize, length: usize },
+    #[error(
+        "Bignum payload chunk of {length} bytes at offset {offset} exceeds {CHUNK_SIZE} bytes"
+    )]
+    BignumTooLong { offset: usize, length: usize },
+    #[error("Constructor index at offset {offset} is not an unsigned 64-bit integer")]
+    InvalidConstructorIndex { offset: usize },
+    #[error("Tag 102 at offset {offset} is not followed by [index, fields]")]
+    MalformedConstructor { offset: usize },
+    #[error("Unknown tag {tag} at offset {offset}")]
+    UnknownTag { offset: usize, tag: u64 },
+    #[error("Unexpected {found} at offset {offset}")]
+    UnexpectedType { offset: usize, found: String },
+    #[error("Trailing bytes at offset {offset}")]
+    TrailingBytes { offset: usize },
+}
+
+impl From<minicbor::decode::Error> for DataDecodeError {
+    fn from(error: minicbor::decode::Error) -> Self {
+        DataDecodeError::Cbor(error.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, DataDecodeError>;
+
+pub(super) fn decode<'a>(arena: &'a Arena, cbor: &[u8]) -> Result<&'a PlutusData<'a>> {
+    Strict::new(arena, cbor, false)
+        .decode()
+        .map(|(data, _)| data)
+}
+
+pub(super) fn decode_raw<'a>(arena: &'a Arena, cbor: &[u8]) -> Result<&'a RawPlutusData<'a>> {
+    Strict::new(arena, cbor, true)
+        .decode()
+        .map(|(_, raw)| raw.expect("raw nodes are kept"))
+}
+
+type Node<'a> = (&'a PlutusData<'a>, Option<&'a RawPlutusData<'a>>);
+
+struct Strict<'a, 'b> {
+    arena: &'a Arena,
+    input: &'b [u8],
+    /// A copy of the input that raw nodes borrow from, when they are kept.
+    raw: Option<&'a [u8]>,
+}
+
+impl<'a, 'b> Strict<'a, 'b> {
+    fn new(arena: &'a Arena, input: &'b [u8], keep_raw: bool) -> Self {
+        let raw = keep_raw.then(|| {
+            let copy: &'a [u8] = arena.alloc(BumpVec::from_iter_in(
+                input.iter().copied(),
+                arena.as_bump(),
+            ));
+
+            copy
+        });
+
+        Strict { arena, input, raw }
+    }
+
+    fn decode(&mut self) -> Result<Node<'a>> {
+        let mut d = Decoder::new(self.input);
+
+        let node = self.data(&mut d)?;
+
+        if d.position() != self.input.len() {
+            return Err(DataDecodeError::TrailingBytes {
+                offset: d.position(),
+            });
+        }
+
+        Ok(node)
+    }
+
+    fn data(&mut self, d: &mut Decoder<'b>) -> Result<Node<'a>> {
+        let arena = self.arena;
+
+        let offset = d.position();
+
+        let mut children = BumpVec::new_in(arena.as_bump());
+
+        let data = match d.datatype()? {
+            Type::Tag => {
+                let tag = d.tag()?.as_u64();
+
+                match tag {
+                    121..=127 => PlutusData::constr(arena, tag - 121, self.list(d, &mut children)?),
+                    1280..=1400 => {
+                        PlutusData::constr(arena, tag - 1280 + 7, self.list(d, &mut children)?)
+                    }
+                    102 => {
+                        // Either a definite array of two items, or an
+                        // indefinite one that must end after them.
+                        let len = d.array()?;
+
+                        if !matches!(len, Some(2) | None) {
+                            return Err(DataDecodeError::MalformedConstructor { offset });
+                        }
+
+                        let index_offset = d.position();
+
+                        let index = match d.datatype()? {
+                            Type::U8 | Type::U16 | Type::U32 | Type::U64 => d.u64()?,
+                            _ => {
+                                return Err(DataDecodeError::InvalidConstructorIndex {
+                                    offset: index_offset,
+                                })
+                            }
+                        };
+
+                        let fields = self.list(d, &mut children)?;
+
+                        if len.is_none() {
+                            if d.datatype()? != Type::Break {
+                                return Err(DataDecodeError::MalformedConstructor { offset });
+                            }
+
+                            d.skip()?;
+                        }
+
+                        PlutusData::constr(arena, index, fields)
+                    }
+                    2 | 3 => {
+                        let bytes = bounded_bytes(arena, d, true)?;
+
+                        let n = Integer::from_bytes_be(num_bigint::Sign::Plus, &bytes);
+
+                        let n = if tag == 2 { n } else { -n - 1 };
+
+                        PlutusData::integer(arena, arena.alloc_integer(n))
+                    }
+                    _ => return Err(DataDecodeError::UnknownTag { offset, tag }),
+                }
+            }
+            Type::Map | Type::MapIndef => {
+                let len = d.map()?;
+
+                let mut entries = BumpVec::new_in(arena.as_bump());
+
+                items(d, len, |d| {
+                    let (key, raw_key) = self.data(d)?;
+                    let (value, raw_value) = self.data(d)?;
+
+                    entries.push((key, value));
+                    children.extend(raw_key.into_iter().chain(raw_value));
+
+                    Ok(())
+                })?;
+
+                PlutusData::map(arena, arena.alloc(entries))
+            }
+            Type::Bytes | Type::BytesIndef => {
+                let bytes = bounded_bytes(arena, d, false)?;
+
+                PlutusData::byte_string(arena, arena.alloc(bytes))
+            }
+            Type::Array | Type::ArrayIndef => PlutusData::list(arena, self.list(d, &mut children)?),
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::Int => {
+                let i: i128 = d.int()?.into();
+
+                PlutusData::integer_from(arena, i)
+            }
+            found => {
+                return Err(DataDecodeError::UnexpectedType {
+                    offset,
+                    found: found.to_string(),
+                })
+            }
+        };
+
+        let raw = self.raw.map(|input| {
+            let raw: &'a RawPlutusData<'a> = arena.alloc(RawPlutusData {
+                data,
+                cbor: &input[offset..d.position()],
+                children: arena.alloc(children),
+            });
+
+            raw
+        });
+
+        Ok((data, raw))
+    }
+
+    fn list(
+        &mut self,
+        d: &mut Decoder<'b>,
+        children: &mut BumpVec<'a, &'a RawPlutusData<'a>>,
+    ) -> Result<&'a [&'a PlutusData<'a>]> {
+        let offset = d.position();
+
+        match d.datatype()? {
+            Type::Array | Type::ArrayIndef => {
+                let len = d.array()?;
+
+                let mut fields = BumpVec::new_in(self.arena.as_bump());
+
+                items(d, len, |d| {
+                    let (field, raw) = self.data(d)?;
+
+                    fields.push(field);
+                    children.extend(raw);
+
+                    Ok(())
+                })?;
+
+                Ok(self.arena.alloc(fields))
+            }
+            found => Err(DataDecodeError::UnexpectedType {
+                offset,
+                found: found.to_string(),
+            }),
+        }
+    }
+}
+
+fn items<'b>(
+    d: &mut Decoder<'b>,
+    len: Option<u64>,
+    mut item: impl FnMut(&mut Decoder<'b>) -> Result<()>,
+) -> Result<()> {
+    match len {
+        Some(len) => {
+            for _ in 0..len {
+                item(d)?;
+            }
+        }
+        None => {
+            while d.datatype()? != Type::Break {
+                item(d)?;
+            }
+
+            d.skip()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A definite byte string of at most 64 bytes, or an indefinite one made of
+/// such chunks.
+fn bounded_bytes<'a>(
+    arena: &'a Arena,
+    d: &mut Decoder<'_>,
+    bignum: bool,
+) -> Result<BumpVec<'a, u8>> {
+    let too_long = |offset, length| {
+        if bignum {
+            DataDecodeError::BignumTooLong { offset, length }
+        } else {
+            DataDecodeError::ChunkTooLong { offset, length }
+        }
+    };
+
+    let mut bytes = BumpVec::new_in(arena.as_bump());
+
+    let mut chunk = |d: &mut Decoder<'_>| {
+        let offset = d.position();
+
+        if d.datatype()? != Type::Bytes {
+            return Err(DataDecodeError::UnexpectedType {
+                offset,
+                found: d.datatype()?.to_string(),
+            });
+        }
+
+        let chunk = d.bytes()?;
+
+        if chunk.len() > CHUNK_SIZE {
+            return Err(too_long(offset, chunk.len()));
+        }
+
+        bytes.extend_from_slice(chunk);
+
+        Ok(())
+    };
+
+    if d.datatype()? == Type::BytesIndef {
+        // The header of an indefinite byte string is a single byte.
+        d.set_position(d.position() + 1);
+
+        while d.datatype()? != Type::Break {
+            chunk(d)?;
+        }
+
+        d.skip()?;
+    } else {
+        chunk(d)?;
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{arena::Arena, data::PlutusData};
+
+    use super::DataDecodeError;
+
+    fn strict(cbor: &str) -> Result<String, DataDecodeError> {
+        let arena = Arena::new();
+
+        PlutusData::from_cbor_strict(&arena, &hex::decode(cbor).unwrap())
+            .map(|data| hex::encode(minicbor::to_vec(data).unwrap()))
+    }
+
+    fn lenient(cbor: &str) -> bool {
+        let arena = Arena::new();
+
+        PlutusData::from_cbor(&arena, &hex::decode(cbor).unwrap()).is_ok()
+    }
+
+    #[test]
+    fn accepts_what_the_encoder_produces() {
+        for cbor in [
+            "d87980",
+            "d87a9f4100420001ff",
+            "d8668218809f0001ff",
+            "d8799fc24c033b2e3c9fd0803ce7ffffffff",
+            "a3010203800440",
+            "3903e7",
+        ] {
+            assert_eq!(strict(cbor), Ok(cbor.to_string()));
+        }
+
+        let long = format!("5f5840{}4101ff", "00".repeat(64));
+
+        assert_eq!(strict(&long), Ok(long.clone()));
+    }
+
+    #[test]
+    fn accepts_indefinite_constructors() {
+        assert_eq!(strict("d8669f0080ff"), Ok("d87980".to_string()));
+        assert_eq!(
+            strict("d8669f18809f01ffff"),
+            Ok("d8668218809f01ff".to_string())
+        );
+        assert_eq!(
+            strict("d8669f008000ff"),
+            Err(DataDecodeError::MalformedConstructor { offset: 0 })
+        );
+        assert!(strict("d8669f0080").is_err());
+    }
+
+    #[test]
+    fn bounds_byte_strings() {
+        let definite = format!("5841{}", "00".repeat(65));
+
+        assert!(lenient(&definite));
+        assert_eq!(
+            strict(&definite),
+            Err(DataDecodeError::ChunkTooLong {
+                offset: 0,
+                length: 65
+            })
+        );
+
+        let chunked = format!("5f4100 5841{}ff", "00".repeat(65)).replace(' ', "");
+
+        assert!(lenient(&chunked));
+        assert_eq!(
+            strict(&chunked),
+            Err(DataDecodeError::ChunkTooLong {
+                offset: 3,
+                length: 65
+            })
+        );
+
+        let bignum = format!("d8799fc25841{}ff", "01".repeat(65));
+
+        assert!(lenient(&bignum));
+        assert_eq!(
+            strict(&bignum),
+            Err(DataDecodeError::BignumTooLong {
+                offset: 4,
+                length: 65
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_structure() {
+        // A negative index after tag 102.
+        assert_eq!(
+            strict("d866822080"),
+            Err(DataDecodeError::InvalidConstructorIndex { offset: 3 })
+        );
+        assert_eq!(
+            strict("d8668318800080"),
+            Err(DataDecodeError::MalformedConstructor { offset: 0 })
+        );
+        assert_eq!(
+            strict("c100"),
+            Err(DataDecodeError::UnknownTag { offset: 0, tag: 1 })
+        );
+        assert_eq!(
+            strict("d87900"),
+            Err(DataDecodeError::UnexpectedType {
+                offset: 2,
+                found: "u8".to_string()
+            })
+        );
+        assert!(lenient("0000"));
+        assert_eq!(
+            strict("0000"),
+            Err(DataDecodeError::TrailingBytes { offset: 1 })
+        );
+        assert!(matches!(
+            strict("f5"),
+            Err(DataDecodeError::UnexpectedType { .. })
+        ));
+    }
+}
@@ -254,3 +254,32 @@ pub fn try_from_tag(arena: &Arena, v: u8) -> Result<&DefaultFunction, FlatDecode
         _ => Err(FlatDecodeError::DefaultFunctionNotFound(v)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{arena::Arena, syn::builtin_from_str, term::Term};
+
+    use super::try_from_tag;
+
+    #[test]
+    fn names_parse_back() {
+        let arena = Arena::new();
+
+        let builtins: Vec<_> = (0..=u8::MAX)
+            .filter_map(|tag| try_from_tag(&arena, tag).ok())
+            .collect();
+
+        assert_eq!(builtins.len(), 101);
+
+        for builtin in builtins {
+            assert!(
+                matches!(
+                    builtin_from_str(&arena, builtin.name()),
+                    Some(Term::Builtin(parsed)) if *parsed == builtin
+                ),
+                "{builtin:?} is named {}",
+                builtin.name()
+            );
+        }
+    }
+}
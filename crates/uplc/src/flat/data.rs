@@ -222,9 +222,9 @@ impl<C> minicbor::encode::Encode<C> for PlutusData<'_> {
                 if fields.is_empty() {
                     e.array(0)?;
                 } else {
-                    // TODO: figure out if we need to care about def vs indef
                     // The encoding implementation in plutus-core uses indefinite here,
-                    // though both forms are accepted when decoding
+                    // though both forms are accepted when decoding. Data that must keep
+                    // its original encoding is decoded with `PlutusData::from_cbor_raw`
                     // https://github.com/IntersectMBO/plutus/blob/9538fc9829426b2ecb0628d352e2d7af96ec8204/plutus-core/plutus-core/src/PlutusCore/Data.hs#L198
                     e.begin_array()?;
                     for f in fields.iter() {
@@ -391,7 +391,7 @@ mod tests {
             num_bigint::Sign::Plus,
             &hex::decode("033b2e3c9fd0803ce7ffffff").unwrap(),
         ) - num::BigInt::from(1);
-        let encoded = minicbor::to_vec(&PlutusData::Integer(&n)).expect("encode failed");
+        let encoded = minicbor::to_vec(PlutusData::Integer(&n)).expect("encode failed");
         let arena = Arena::new();
         let decoded = PlutusData::from_cbor(&arena, &encoded).expect("decode failed");
         assert_eq!(decoded, &PlutusData::Integer(&n));
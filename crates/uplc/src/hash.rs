@@ -0,0 +1,141 @@
+//! Ledger hashes of scripts and datums.
+//!
+//! The ledger hashes a Plutus script as stored on chain: its flat encoding
+//! wrapped in a CBOR byte string, prefixed with the language tag.
+
+use cryptoxide::{blake2b::Blake2b, digest::Digest};
+
+use crate::{data::PlutusData, machine::PlutusVersion};
+
+pub type ScriptHash = [u8; 28];
+pub type DatumHash = [u8; 32];
+
+/// The ledger hash of a flat-encoded script.
+pub fn script_hash(plutus_version: PlutusVersion, flat: &[u8]) -> ScriptHash {
+    script_hash_cbor(plutus_version, &wrap_cbor(flat))
+}
+
+/// The ledger hash of a script already wrapped in a CBOR byte string, as
+/// found in witness sets and reference scripts.
+pub fn script_hash_cbor(plutus_version: PlutusVersion, cbor: &[u8]) -> ScriptHash {
+    let mut preimage = Vec::with_capacity(cbor.len() + 1);
+
+    preimage.push(language_tag(plutus_version));
+    preimage.extend_from_slice(cbor);
+
+    blake2b_224(&preimage)
+}
+
+/// The hash of a datum re-encoded with the canonical encoder. Datums from
+/// the wire should be hashed with their original bytes, see
+/// [`RawPlutusData`](crate::data::RawPlutusData).
+pub fn datum_hash(data: &PlutusData<'_>) -> DatumHash {
+    let cbor = minicbor::to_vec(data).expect("encoding to a vector cannot fail");
+
+    blake2b_256(&cbor)
+}
+
+/// A flat-encoded script as the ledger stores it: in a CBOR byte string.
+pub fn wrap_cbor(flat: &[u8]) -> Vec<u8> {
+    let mut e = minicbor::Encoder::new(Vec::with_capacity(flat.len() + 9));
+
+    e.bytes(flat).expect("encoding to a vector cannot fail");
+
+    e.into_writer()
+}
+
+fn language_tag(plutus_version: PlutusVersion) -> u8 {
+    match plutus_version {
+        PlutusVersion::V1 => 1,
+        PlutusVersion::V2 => 2,
+        PlutusVersion::V3 => 3,
+    }
+}
+
+pub(crate) fn blake2b_224(bytes: &[u8]) -> [u8; 28] {
+    let mut digest = [0; 28];
+
+    let mut context = Blake2b::new(28);
+
+    context.input(bytes);
+    context.result(&mut digest);
+
+    digest
+}
+
+pub(crate) fn blake2b_256(bytes: &[u8]) -> [u8; 32] {
+    let mut digest = [0; 32];
+
+    let mut context = Blake2b::new(32);
+
+    context.input(bytes);
+    context.result(&mut digest);
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{arena::Arena, data::PlutusData, flat, machine::PlutusVersion, syn::parse_program};
+
+    use super::{datum_hash, script_hash, script_hash_cbor, wrap_cbor};
+
+    #[test]
+    fn hashes_always_succeeds_scripts() {
+        let arena = Arena::new();
+
+        // The V1 "always succeeds" script, locking funds at the testnet
+        // address addr_test1wpnlxv2xv9a9ucvnvzqakwepzl9ltx7jzgm53av2e9ncv4sysemm8.
+        let flat = hex::decode("01000033222220051200120011").unwrap();
+
+        assert_eq!(
+            wrap_cbor(&flat),
+            hex::decode("4d01000033222220051200120011").unwrap()
+        );
+        assert_eq!(
+            hex::encode(script_hash(PlutusVersion::V1, &flat)),
+            "67f33146617a5e61936081db3b2117cbf59bd2123748f58ac9678656"
+        );
+
+        let program = parse_program(&arena, "(program 1.1.0 (lam ctx (con unit ())))")
+            .into_result()
+            .unwrap();
+
+        let flat = flat::encode(program).unwrap();
+
+        assert_eq!(
+            script_hash(PlutusVersion::V3, &flat),
+            script_hash_cbor(PlutusVersion::V3, &wrap_cbor(&flat))
+        );
+        assert_ne!(
+            script_hash(PlutusVersion::V3, &flat),
+            script_hash(PlutusVersion::V2, &flat)
+        );
+        assert_eq!(
+            program.script_hash(PlutusVersion::V3).unwrap(),
+            script_hash(PlutusVersion::V3, &flat)
+        );
+    }
+
+    #[test]
+    fn hashes_datums() {
+        let arena = Arena::new();
+
+        // The hash of the unit datum, `Constr 0 []`.
+        let unit = PlutusData::constr(&arena, 0, &[]);
+
+        assert_eq!(
+            hex::encode(datum_hash(unit)),
+            "923918e403bf43c34b4ef6b48eb2ee04babed17320d8d1b9ff9ad086e86f44ec"
+        );
+        assert_eq!(unit.hash(), datum_hash(unit));
+
+        // A definite list of fields is kept as is by the raw decoding.
+        let raw = PlutusData::from_cbor_raw(&arena, &[0xd8, 0x79, 0x81, 0x00]).unwrap();
+
+        assert_ne!(raw.hash(), raw.data.hash());
+        assert_eq!(raw.hash(), super::blake2b_256(&[0xd8, 0x79, 0x81, 0x00]));
+    }
+}
@@ -7,6 +7,11 @@ use crate::{
     data::PlutusData,
 };
 
+mod codec;
+mod ops;
+
+pub use codec::ValueCodecError;
+
 #[derive(thiserror::Error, Debug)]
 pub enum UnValueDataError {
     #[error("non-Map constructor")]
@@ -0,0 +1,505 @@
+//! Conversions between [`LedgerValue`] and the ledger's encodings of a
+//! value: lovelace plus a multi-asset map, as CBOR or as the JSON printed by
+//! `cardano-cli`.
+//!
+//! Ada is the entry with an empty currency symbol and token name, as in the
+//! `ValueData` encoding.
+
+use std::collections::BTreeMap;
+
+use bumpalo::collections::Vec as BumpVec;
+use minicbor::{data::Type, Decoder, Encoder};
+use num::{Signed, ToPrimitive, Zero};
+use serde_json::{Map, Value as Json};
+
+use crate::{arena::Arena, constant::Integer};
+
+use super::{
+    check_quantity_range, count_stats, CurrencyEntry, LedgerValue, TokenEntry, ValueError,
+};
+
+const POLICY_ID_LENGTH: usize = 28;
+const ASSET_NAME_MAX_LENGTH: usize = 32;
+const LOVELACE: &str = "lovelace";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ValueCodecError {
+    #[error("Malformed value CBOR: {0}")]
+    Cbor(String),
+    #[error("Malformed value JSON: {0}")]
+    Json(String),
+    #[error("Policy id '{0}' is not {POLICY_ID_LENGTH} bytes")]
+    InvalidPolicy(String),
+    #[error("Asset name '{0}' is longer than {ASSET_NAME_MAX_LENGTH} bytes")]
+    InvalidAssetName(String),
+    #[error("Asset '{policy}.{name}' appears more than once")]
+    DuplicateAsset { policy: String, name: String },
+    #[error("Asset '{policy}.{name}' has a zero quantity")]
+    ZeroQuantity { policy: String, name: String },
+    #[error("Policy '{0}' has no assets")]
+    EmptyPolicy(String),
+    #[error("Lovelace quantity {0} is not a 64-bit natural number")]
+    InvalidLovelace(Integer),
+    #[error("Quantity {0} does not fit in a CBOR integer")]
+    QuantityTooLarge(Integer),
+    #[error(transparent)]
+    Value(#[from] ValueError),
+}
+
+impl From<minicbor::decode::Error> for ValueCodecError {
+    fn from(error: minicbor::decode::Error) -> Self {
+        ValueCodecError::Cbor(error.to_string())
+    }
+}
+
+type Assets = BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, Integer>>;
+
+impl<'a> LedgerValue<'a> {
+    /// Decode a ledger `value`: either a coin or `[coin, multiasset]`.
+    pub fn from_cbor(
+        arena: &'a Arena,
+        cbor: &[u8],
+    ) -> Result<&'a LedgerValue<'a>, ValueCodecError> {
+        let mut d = Decoder::new(cbor);
+
+        let value = Self::decode_cbor(arena, &mut d)?;
+
+        if d.position() != cbor.len() {
+            return Err(ValueCodecError::Cbor("trailing bytes".into()));
+        }
+
+        Ok(value)
+    }
+
+    /// Decode a ledger `value` from the middle of a larger CBOR item.
+    pub fn decode_cbor(
+        arena: &'a Arena,
+        d: &mut Decoder<'_>,
+    ) -> Result<&'a LedgerValue<'a>, ValueCodecError> {
+        let mut assets = Assets::new();
+
+        let coin = match d.datatype()? {
+            Type::Array => {
+                if d.array()? != Some(2) {
+                    return Err(ValueCodecError::Cbor("expected [coin, multiasset]".into()));
+                }
+
+                let coin = d.u64()?;
+
+                let policies = d
+                    .map()?
+                    .ok_or_else(|| ValueCodecError::Cbor("indefinite multiasset".into()))?;
+
+                for _ in 0..policies {
+                    let policy = d.bytes()?.to_vec();
+
+                    let tokens = d
+                        .map()?
+                        .ok_or_else(|| ValueCodecError::Cbor("indefinite asset map".into()))?;
+
+                    if tokens == 0 {
+                        return Err(ValueCodecError::EmptyPolicy(hex::encode(&policy)));
+                    }
+
+                    for _ in 0..tokens {
+                        let name = d.bytes()?.to_vec();
+                        let quantity: i128 = d.int()?.into();
+
+                        insert(&mut assets, &policy, name, Integer::from(quantity))?;
+                    }
+                }
+
+                coin
+            }
+            _ => d.u64()?,
+        };
+
+        Ok(build(arena, coin, assets))
+    }
+
+    /// Encode as a ledger `value`, a bare coin when there are no other
+    /// assets.
+    pub fn to_cbor(&self) -> Result<Vec<u8>, ValueCodecError> {
+        let mut e = Encoder::new(Vec::new());
+
+        self.encode_cbor(&mut e)?;
+
+        Ok(e.into_writer())
+    }
+
+    pub fn encode_cbor(&self, e: &mut Encoder<Vec<u8>>) -> Result<(), ValueCodecError> {
+        let (coin, assets) = self.split_lovelace()?;
+
+        let infallible =
+            |error: minicbor::encode::Error<_>| ValueCodecError::Cbor(error.to_string());
+
+        if assets.is_empty() {
+            e.u64(coin).map_err(infallible)?;
+
+            return Ok(());
+        }
+
+        e.array(2).map_err(infallible)?;
+        e.u64(coin).map_err(infallible)?;
+        e.map(assets.len() as u64).map_err(infallible)?;
+
+        for entry in assets {
+            e.bytes(entry.currency).map_err(infallible)?;
+            e.map(entry.tokens.len() as u64).map_err(infallible)?;
+
+            for token in entry.tokens {
+                let quantity = token
+                    .quantity
+                    .to_i128()
+                    .and_then(|quantity| minicbor::data::Int::try_from(quantity).ok())
+                    .ok_or_else(|| ValueCodecError::QuantityTooLarge(token.quantity.clone()))?;
+
+                e.bytes(token.name).map_err(infallible)?;
+                e.int(quantity).map_err(infallible)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decode the JSON of `cardano-cli`: lovelace under `"lovelace"` and
+    /// quantities by hex policy id and hex asset name. Quantities beyond 64
+    /// bits may be given as decimal strings.
+    pub fn from_json(
+        arena: &'a Arena,
+        json: &Json,
+    ) -> Result<&'a LedgerValue<'a>, ValueCodecError> {
+        let object = json
+            .as_object()
+            .ok_or_else(|| ValueCodecError::Json("expected an object".into()))?;
+
+        let mut coin = 0;
+        let mut assets = Assets::new();
+
+        for (key, value) in object {
+            if key == LOVELACE {
+                coin = json_quantity(value)?.to_u64().ok_or_else(|| {
+                    ValueCodecError::Json("lovelace is not a 64-bit natural".into())
+                })?;
+
+                continue;
+            }
+
+            let policy = json_hex(key)?;
+
+            let tokens = value.as_object().ok_or_else(|| {
+                ValueCodecError::Json(format!("expected an object of assets for '{key}'"))
+            })?;
+
+            if tokens.is_empty() {
+                return Err(ValueCodecError::EmptyPolicy(key.clone()));
+            }
+
+            for (name, quantity) in tokens {
+                insert(
+                    &mut assets,
+                    &policy,
+                    json_hex(name)?,
+                    json_quantity(quantity)?,
+                )?;
+            }
+        }
+
+        Ok(build(arena, coin, assets))
+    }
+
+    /// The JSON of `cardano-cli`. Quantities beyond 64 bits are written as
+    /// decimal strings.
+    pub fn to_json(&self) -> Result<Json, ValueCodecError> {
+        let (coin, assets) = self.split_lovelace()?;
+
+        let mut object = Map::new();
+
+        object.insert(LOVELACE.into(), Json::from(coin));
+
+        for entry in assets {
+            let tokens = entry
+                .tokens
+                .iter()
+                .map(|token| (hex::encode(token.name), json_number(token.quantity)))
+                .collect();
+
+            object.insert(hex::encode(entry.currency), Json::Object(tokens));
+        }
+
+        Ok(Json::Object(object))
+    }
+
+    /// The lovelace and the remaining, ledger-shaped, assets.
+    fn split_lovelace(&self) -> Result<(u64, &'a [CurrencyEntry<'a>]), ValueCodecError> {
+        let (coin, assets) = match self.entries.split_first() {
+            Some((first, rest)) if first.currency.is_empty() => match first.tokens {
+                [TokenEntry { name: [], quantity }] => {
+                    let coin = quantity
+                        .to_u64()
+                        .ok_or_else(|| ValueCodecError::InvalidLovelace((*quantity).clone()))?;
+
+                    (coin, rest)
+                }
+                _ => return Err(ValueCodecError::InvalidPolicy(String::new())),
+            },
+            _ => (0, self.entries),
+        };
+
+        for entry in assets {
+            if entry.currency.len() != POLICY_ID_LENGTH {
+                return Err(ValueCodecError::InvalidPolicy(hex::encode(entry.currency)));
+            }
+        }
+
+        Ok((coin, assets))
+    }
+}
+
+fn insert(
+    assets: &mut Assets,
+    policy: &[u8],
+    name: Vec<u8>,
+    quantity: Integer,
+) -> Result<(), ValueCodecError> {
+    if policy.len() != POLICY_ID_LENGTH {
+        return Err(ValueCodecError::InvalidPolicy(hex::encode(policy)));
+    }
+
+    if name.len() > ASSET_NAME_MAX_LENGTH {
+        return Err(ValueCodecError::InvalidAssetName(hex::encode(&name)));
+    }
+
+    if quantity.is_zero() {
+        return Err(ValueCodecError::ZeroQuantity {
+            policy: hex::encode(policy),
+            name: hex::encode(&name),
+        });
+    }
+
+    check_quantity_range(&quantity)?;
+
+    let tokens = assets.entry(policy.to_vec()).or_default();
+
+    if tokens.contains_key(&name) {
+        return Err(ValueCodecError::DuplicateAsset {
+            policy: hex::encode(policy),
+            name: hex::encode(&name),
+        });
+    }
+
+    tokens.insert(name, quantity);
+
+    Ok(())
+}
+
+/// Lay out assets the way the builtins expect them: sorted by currency and
+/// token, ada first and no zero quantities.
+fn build<'a>(arena: &'a Arena, coin: u64, assets: Assets) -> &'a LedgerValue<'a> {
+    let bytes = |bytes: &[u8]| -> &'a [u8] {
+        arena.alloc(BumpVec::from_iter_in(
+            bytes.iter().copied(),
+            arena.as_bump(),
+        ))
+    };
+
+    let mut currency_entries = BumpVec::new_in(arena.as_bump());
+
+    if coin > 0 {
+        let tokens: &'a [TokenEntry<'a>] = arena.alloc([TokenEntry {
+            name: &[],
+            quantity: arena.alloc_integer(Integer::from(coin)),
+        }]);
+
+        currency_entries.push(CurrencyEntry {
+            currency: &[],
+            tokens,
+        });
+    }
+
+    for (policy, tokens) in &assets {
+        let tokens = BumpVec::from_iter_in(
+            tokens.iter().map(|(name, quantity)| TokenEntry {
+                name: bytes(name),
+                quantity: arena.alloc_integer(quantity.clone()),
+            }),
+            arena.as_bump(),
+        );
+
+        currency_entries.push(CurrencyEntry {
+            currency: bytes(policy),
+            tokens: arena.alloc(tokens),
+        });
+    }
+
+    let entries: &'a [CurrencyEntry<'a>] = arena.alloc(currency_entries);
+    let (size, negative_count) = count_stats(entries);
+
+    arena.alloc(LedgerValue {
+        entries,
+        size,
+        negative_count,
+    })
+}
+
+fn json_hex(key: &str) -> Result<Vec<u8>, ValueCodecError> {
+    hex::decode(key).map_err(|_| ValueCodecError::Json(format!("'{key}' is not hex")))
+}
+
+fn json_quantity(json: &Json) -> Result<Integer, ValueCodecError> {
+    let quantity = match json {
+        Json::Number(n) => n
+            .as_i64()
+            .map(Integer::from)
+            .or_else(|| n.as_u64().map(Integer::from)),
+        Json::String(s) => s.parse().ok(),
+        _ => None,
+    };
+
+    quantity.ok_or_else(|| ValueCodecError::Json(format!("{json} is not an integer quantity")))
+}
+
+fn json_number(quantity: &Integer) -> Json {
+    if let Some(i) = quantity.to_i64() {
+        Json::from(i)
+    } else if let (false, Some(u)) = (quantity.is_negative(), quantity.to_u64()) {
+        Json::from(u)
+    } else {
+        Json::String(quantity.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use crate::{arena::Arena, constant::Integer, ledger_value::LedgerValue};
+
+    use super::ValueCodecError;
+
+    const POLICY_A: [u8; 28] = [0xaa; 28];
+    const POLICY_B: [u8; 28] = [0x0b; 28];
+
+    fn quantities(value: &LedgerValue) -> Vec<(Vec<u8>, Vec<u8>, Integer)> {
+        value
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                entry.tokens.iter().map(|token| {
+                    (
+                        entry.currency.to_vec(),
+                        token.name.to_vec(),
+                        token.quantity.clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_cbor() {
+        let arena = Arena::new();
+
+        let mut e = minicbor::Encoder::new(Vec::new());
+
+        // Out of order on purpose: the result is sorted.
+        e.array(2).unwrap().u64(5_000_000).unwrap().map(2).unwrap();
+        e.bytes(&POLICY_A).unwrap().map(2).unwrap();
+        e.bytes(b"zz").unwrap().i64(-3).unwrap();
+        e.bytes(b"aa").unwrap().u64(7).unwrap();
+        e.bytes(&POLICY_B).unwrap().map(1).unwrap();
+        e.bytes(b"").unwrap().u64(1).unwrap();
+
+        let value = LedgerValue::from_cbor(&arena, e.writer()).unwrap();
+
+        assert_eq!(
+            quantities(value),
+            vec![
+                (vec![], vec![], Integer::from(5_000_000)),
+                (POLICY_B.to_vec(), vec![], Integer::from(1)),
+                (POLICY_A.to_vec(), b"aa".to_vec(), Integer::from(7)),
+                (POLICY_A.to_vec(), b"zz".to_vec(), Integer::from(-3)),
+            ]
+        );
+        assert_eq!((value.size, value.negative_count), (4, 1));
+
+        let again = LedgerValue::from_cbor(&arena, &value.to_cbor().unwrap()).unwrap();
+
+        assert_eq!(again, value);
+
+        let coin = LedgerValue::from_cbor(&arena, &[0x1a, 0, 0x0f, 0x42, 0x40]).unwrap();
+
+        assert_eq!(coin.to_cbor().unwrap(), vec![0x1a, 0, 0x0f, 0x42, 0x40]);
+        assert_eq!(LedgerValue::from_cbor(&arena, &[0]).unwrap().entries, &[]);
+    }
+
+    #[test]
+    fn rejects_malformed_cbor() {
+        let arena = Arena::new();
+
+        let encode = |name: &[u8], quantity: i64, twice: bool| {
+            let mut e = minicbor::Encoder::new(Vec::new());
+
+            e.array(2).unwrap().u64(0).unwrap().map(1).unwrap();
+            e.bytes(&POLICY_A).unwrap();
+            e.map(if twice { 2 } else { 1 }).unwrap();
+
+            for _ in 0..if twice { 2 } else { 1 } {
+                e.bytes(name).unwrap().i64(quantity).unwrap();
+            }
+
+            e.into_writer()
+        };
+
+        assert!(matches!(
+            LedgerValue::from_cbor(&arena, &encode(b"a", 0, false)),
+            Err(ValueCodecError::ZeroQuantity { .. })
+        ));
+        assert!(matches!(
+            LedgerValue::from_cbor(&arena, &encode(b"a", 1, true)),
+            Err(ValueCodecError::DuplicateAsset { .. })
+        ));
+        assert!(matches!(
+            LedgerValue::from_cbor(&arena, &encode(&[0; 33], 1, false)),
+            Err(ValueCodecError::InvalidAssetName(_))
+        ));
+    }
+
+    #[test]
+    fn round_trips_json() {
+        let arena = Arena::new();
+
+        let big: Integer = (Integer::from(1) << 100) - 1;
+
+        let json = json!({
+            "lovelace": 2_000_000,
+            hex::encode(POLICY_A): { "6161": 7, "7a7a": big.to_string() },
+        });
+
+        let value = LedgerValue::from_json(&arena, &json).unwrap();
+
+        assert_eq!(
+            quantities(value),
+            vec![
+                (vec![], vec![], Integer::from(2_000_000)),
+                (POLICY_A.to_vec(), b"aa".to_vec(), Integer::from(7)),
+                (POLICY_A.to_vec(), b"zz".to_vec(), big.clone()),
+            ]
+        );
+        assert_eq!(value.to_json().unwrap(), json);
+        assert!(matches!(
+            value.to_cbor(),
+            Err(ValueCodecError::QuantityTooLarge(quantity)) if quantity == big
+        ));
+
+        let too_big: Integer = Integer::from(1) << 127;
+
+        let out_of_range = json!({ hex::encode(POLICY_B): { "": too_big.to_string() } });
+
+        assert!(matches!(
+            LedgerValue::from_json(&arena, &out_of_range),
+            Err(ValueCodecError::Value(_))
+        ));
+    }
+}
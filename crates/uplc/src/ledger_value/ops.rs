@@ -0,0 +1,415 @@
+//! Arithmetic and queries on [`LedgerValue`] for off-chain code.
+//!
+//! Every value built here is sorted by currency and token, has no zero
+//! quantities nor empty currencies, and has up-to-date `size` and
+//! `negative_count`, which is what the builtins assume.
+
+use std::cmp::Ordering;
+
+use bumpalo::collections::Vec as BumpVec;
+use num::Zero;
+
+use crate::{arena::Arena, constant::Integer};
+
+use super::{
+    check_quantity_range, count_stats, CurrencyEntry, LedgerValue, TokenEntry, ValueError,
+};
+
+type Asset<'a> = (&'a [u8], &'a [u8], &'a Integer);
+
+impl<'a> LedgerValue<'a> {
+    /// Every quantity with its currency symbol and token name, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Asset<'a>> + 'a {
+        let entries = self.entries;
+
+        entries.iter().flat_map(|entry| {
+            entry
+                .tokens
+                .iter()
+                .map(move |token| (entry.currency, token.name, token.quantity))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Like [`LedgerValue::lookup_coin`], without allocating a zero.
+    pub fn get(&self, ccy: &[u8], tok: &[u8]) -> Option<&'a Integer> {
+        self.policy(ccy)?
+            .iter()
+            .find(|token| token.name == tok)
+            .map(|token| token.quantity)
+    }
+
+    /// The tokens of one currency symbol.
+    pub fn policy(&self, ccy: &[u8]) -> Option<&'a [TokenEntry<'a>]> {
+        self.entries
+            .binary_search_by(|entry| entry.currency.cmp(ccy))
+            .ok()
+            .map(|index| self.entries[index].tokens)
+    }
+
+    /// The quantity of ada, under the empty currency symbol and token name.
+    pub fn lovelace(&self) -> Integer {
+        self.get(&[], &[]).cloned().unwrap_or_default()
+    }
+
+    pub fn negate(
+        arena: &'a Arena,
+        v: &'a LedgerValue<'a>,
+    ) -> Result<&'a LedgerValue<'a>, ValueError> {
+        from_sorted(arena, v.iter().map(|(ccy, tok, qty)| (ccy, tok, -qty)))
+    }
+
+    /// `v1 - v2`, quantity by quantity.
+    pub fn subtract_value(
+        arena: &'a Arena,
+        v1: &'a LedgerValue<'a>,
+        v2: &'a LedgerValue<'a>,
+    ) -> Result<&'a LedgerValue<'a>, ValueError> {
+        let mut assets = Vec::with_capacity(v1.size + v2.size);
+
+        merge(v1, v2, |ccy, tok, q1, q2| {
+            let zero = Integer::zero();
+
+            assets.push((ccy, tok, q1.unwrap_or(&zero) - q2.unwrap_or(&zero)));
+
+            true
+        });
+
+        from_sorted(arena, assets.into_iter())
+    }
+
+    /// The quantities for which `keep` holds.
+    pub fn filter(
+        arena: &'a Arena,
+        v: &'a LedgerValue<'a>,
+        keep: impl Fn(&[u8], &[u8], &Integer) -> bool,
+    ) -> &'a LedgerValue<'a> {
+        let mut currency_entries = BumpVec::new_in(arena.as_bump());
+
+        for entry in v.entries {
+            let tokens = BumpVec::from_iter_in(
+                entry
+                    .tokens
+                    .iter()
+                    .filter(|token| keep(entry.currency, token.name, token.quantity))
+                    .cloned(),
+                arena.as_bump(),
+            );
+
+            if !tokens.is_empty() {
+                currency_entries.push(CurrencyEntry {
+                    currency: entry.currency,
+                    tokens: arena.alloc(tokens),
+                });
+            }
+        }
+
+        alloc_value(arena, currency_entries)
+    }
+
+    /// Restore the invariants of a value built by hand: sort currencies and
+    /// tokens, add up duplicates and drop zero quantities.
+    pub fn normalize(
+        arena: &'a Arena,
+        v: &'a LedgerValue<'a>,
+    ) -> Result<&'a LedgerValue<'a>, ValueError> {
+        let mut assets: Vec<_> = v.iter().collect();
+
+        assets.sort_by(|(c1, t1, _), (c2, t2, _)| (c1, t1).cmp(&(c2, t2)));
+
+        let mut summed: Vec<(&[u8], &[u8], Integer)> = Vec::with_capacity(assets.len());
+
+        for (ccy, tok, qty) in assets {
+            match summed.last_mut() {
+                Some((c, t, total)) if *c == ccy && *t == tok => *total += qty,
+                _ => summed.push((ccy, tok, qty.clone())),
+            }
+        }
+
+        from_sorted(arena, summed.into_iter())
+    }
+}
+
+impl LedgerValue<'_> {
+    /// Compare quantity by quantity, missing ones being zero: `Less` when no
+    /// quantity of `self` exceeds that of `other`. Most pairs of values are
+    /// not comparable.
+    ///
+    /// This is not `PartialOrd`, which would have to agree with the
+    /// structural `PartialEq`.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+
+        let zero = Integer::zero();
+
+        merge(self, other, |_, _, q1, q2| {
+            match (ordering, q1.unwrap_or(&zero).cmp(q2.unwrap_or(&zero))) {
+                (_, Ordering::Equal) => true,
+                (Ordering::Equal, found) => {
+                    ordering = found;
+
+                    true
+                }
+                (current, found) => current == found,
+            }
+        })
+        .then_some(ordering)
+    }
+
+    /// Whether no quantity of `self` exceeds that of `other`.
+    pub fn leq(&self, other: &Self) -> bool {
+        matches!(self.compare(other), Some(Ordering::Less | Ordering::Equal))
+    }
+}
+
+/// Walk two values in lockstep, calling `f` with the quantities of each
+/// asset found in either. Stops early, returning false, when `f` does.
+fn merge<'a>(
+    v1: &LedgerValue<'a>,
+    v2: &LedgerValue<'a>,
+    mut f: impl FnMut(&'a [u8], &'a [u8], Option<&'a Integer>, Option<&'a Integer>) -> bool,
+) -> bool {
+    let mut left = v1.iter().peekable();
+    let mut right = v2.iter().peekable();
+
+    loop {
+        let keep_going = match (left.peek(), right.peek()) {
+            (None, None) => return true,
+            (Some(&(ccy, tok, qty)), None) => {
+                left.next();
+
+                f(ccy, tok, Some(qty), None)
+            }
+            (None, Some(&(ccy, tok, qty))) => {
+                right.next();
+
+                f(ccy, tok, None, Some(qty))
+            }
+            (Some(&(c1, t1, q1)), Some(&(c2, t2, q2))) => match (c1, t1).cmp(&(c2, t2)) {
+                Ordering::Less => {
+                    left.next();
+
+                    f(c1, t1, Some(q1), None)
+                }
+                Ordering::Greater => {
+                    right.next();
+
+                    f(c2, t2, None, Some(q2))
+                }
+                Ordering::Equal => {
+                    left.next();
+                    right.next();
+
+                    f(c1, t1, Some(q1), Some(q2))
+                }
+            },
+        };
+
+        if !keep_going {
+            return false;
+        }
+    }
+}
+
+/// Build a value from assets already sorted by currency and token, without
+/// duplicates. Zero quantities are dropped.
+fn from_sorted<'a>(
+    arena: &'a Arena,
+    assets: impl Iterator<Item = (&'a [u8], &'a [u8], Integer)>,
+) -> Result<&'a LedgerValue<'a>, ValueError> {
+    let mut currency_entries = BumpVec::new_in(arena.as_bump());
+    let mut tokens = BumpVec::new_in(arena.as_bump());
+    let mut current: Option<&'a [u8]> = None;
+
+    for (ccy, tok, qty) in assets {
+        if qty.is_zero() {
+            continue;
+        }
+
+        check_quantity_range(&qty)?;
+
+        if let Some(currency) = current.filter(|currency| *currency != ccy) {
+            currency_entries.push(CurrencyEntry {
+                currency,
+                tokens: arena.alloc(std::mem::replace(
+                    &mut tokens,
+                    BumpVec::new_in(arena.as_bump()),
+                )),
+            });
+        }
+
+        current = Some(ccy);
+
+        tokens.push(TokenEntry {
+            name: tok,
+            quantity: arena.alloc_integer(qty),
+        });
+    }
+
+    if let Some(currency) = current {
+        currency_entries.push(CurrencyEntry {
+            currency,
+            tokens: arena.alloc(tokens),
+        });
+    }
+
+    Ok(alloc_value(arena, currency_entries))
+}
+
+fn alloc_value<'a>(
+    arena: &'a Arena,
+    currency_entries: BumpVec<'a, CurrencyEntry<'a>>,
+) -> &'a LedgerValue<'a> {
+    let entries: &'a [CurrencyEntry<'a>] = arena.alloc(currency_entries);
+    let (size, negative_count) = count_stats(entries);
+
+    arena.alloc(LedgerValue {
+        entries,
+        size,
+        negative_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena,
+        constant::Integer,
+        ledger_value::{CurrencyEntry, LedgerValue, TokenEntry},
+    };
+
+    fn value<'a>(arena: &'a Arena, assets: &[(&'a [u8], &'a [u8], i64)]) -> &'a LedgerValue<'a> {
+        assets
+            .iter()
+            .fold(LedgerValue::empty(arena), |v, (ccy, tok, qty)| {
+                let qty = arena.alloc_integer(Integer::from(*qty));
+
+                LedgerValue::insert_coin(arena, ccy, tok, qty, v)
+            })
+    }
+
+    fn assets<'a>(v: &LedgerValue<'a>) -> Vec<(&'a [u8], &'a [u8], i64)> {
+        v.iter()
+            .map(|(ccy, tok, qty)| (ccy, tok, i64::try_from(qty).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn subtracts_and_negates() {
+        let arena = Arena::new();
+
+        let v1 = value(&arena, &[(b"", b"", 10), (b"a", b"x", 3), (b"b", b"y", 1)]);
+        let v2 = value(&arena, &[(b"", b"", 4), (b"a", b"x", 3), (b"c", b"z", 2)]);
+
+        let difference = LedgerValue::subtract_value(&arena, v1, v2).unwrap();
+
+        assert_eq!(
+            assets(difference),
+            vec![(&b""[..], &b""[..], 6), (b"b", b"y", 1), (b"c", b"z", -2)]
+        );
+        assert_eq!((difference.size, difference.negative_count), (3, 1));
+        assert_eq!(difference.lovelace(), Integer::from(6));
+        assert!(difference.policy(b"a").is_none());
+
+        let negated = LedgerValue::negate(&arena, difference).unwrap();
+
+        assert_eq!(negated.negative_count, 2);
+        assert_eq!(negated.get(b"c", b"z"), Some(&Integer::from(2)));
+        assert!(LedgerValue::subtract_value(&arena, v1, v1)
+            .unwrap()
+            .is_empty());
+
+        let min: Integer = Integer::from(1) << 127;
+        let min = -min;
+        let min = arena.alloc_integer(min);
+        let min = LedgerValue::insert_coin(&arena, b"m", b"", min, LedgerValue::empty(&arena));
+
+        assert!(LedgerValue::negate(&arena, min).is_err());
+    }
+
+    #[test]
+    fn filters_and_normalizes() {
+        let arena = Arena::new();
+
+        let v = value(&arena, &[(b"", b"", 10), (b"a", b"x", 3), (b"a", b"y", -1)]);
+
+        let tokens = LedgerValue::filter(&arena, v, |ccy, _, _| !ccy.is_empty());
+
+        assert_eq!(
+            assets(tokens),
+            vec![(&b"a"[..], &b"x"[..], 3), (b"a", b"y", -1)]
+        );
+
+        let positive = LedgerValue::filter(&arena, v, |_, _, qty| qty > &Integer::from(0));
+
+        assert_eq!(positive.negative_count, 0);
+        assert_eq!(positive.size, 2);
+
+        let by_hand = LedgerValue {
+            entries: arena.alloc([
+                CurrencyEntry {
+                    currency: b"b",
+                    tokens: arena.alloc([TokenEntry {
+                        name: b"",
+                        quantity: arena.alloc_integer(Integer::from(0)),
+                    }]),
+                },
+                CurrencyEntry {
+                    currency: b"a",
+                    tokens: arena.alloc([
+                        TokenEntry {
+                            name: b"y",
+                            quantity: arena.alloc_integer(Integer::from(2)),
+                        },
+                        TokenEntry {
+                            name: b"x",
+                            quantity: arena.alloc_integer(Integer::from(1)),
+                        },
+                    ]),
+                },
+                CurrencyEntry {
+                    currency: b"a",
+                    tokens: arena.alloc([TokenEntry {
+                        name: b"y",
+                        quantity: arena.alloc_integer(Integer::from(-3)),
+                    }]),
+                },
+            ]),
+            size: 0,
+            negative_count: 0,
+        };
+
+        let normalized = LedgerValue::normalize(&arena, arena.alloc(by_hand)).unwrap();
+
+        assert_eq!(
+            normalized,
+            value(&arena, &[(b"a", b"x", 1), (b"a", b"y", -1)])
+        );
+    }
+
+    #[test]
+    fn compares_quantity_by_quantity() {
+        let arena = Arena::new();
+
+        let small = value(&arena, &[(b"", b"", 1), (b"a", b"x", 1)]);
+        let large = value(&arena, &[(b"", b"", 2), (b"a", b"x", 1), (b"b", b"", 1)]);
+        let other = value(&arena, &[(b"", b"", 5)]);
+
+        assert_eq!(small.compare(large), Some(Ordering::Less));
+        assert_eq!(large.compare(small), Some(Ordering::Greater));
+        assert_eq!(small.compare(small), Some(Ordering::Equal));
+        assert_eq!(small.compare(other), None);
+        assert_eq!(other.compare(large), None);
+        assert!(LedgerValue::empty(&arena).leq(small));
+        assert!(small.leq(large));
+        assert!(!large.leq(small));
+        assert!(!small.leq(other));
+    }
+}
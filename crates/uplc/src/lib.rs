@@ -1,15 +1,20 @@
 pub mod arena;
 pub mod binder;
 pub mod bls;
+pub mod blueprint;
 pub mod builtin;
 pub mod constant;
 pub mod data;
 pub mod flat;
+pub mod hash;
 pub mod ledger_value;
 pub mod machine;
 pub mod program;
+pub mod script;
+pub mod script_context;
 pub mod syn;
 pub mod term;
+pub mod tx;
 pub mod typ;
 
 pub use bumpalo;
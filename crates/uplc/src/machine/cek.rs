@@ -477,8 +477,7 @@ impl<'a, B: BuiltinCostModel> Machine<'a, B> {
     where
         V: Eval<'a>,
     {
-        self.ex_budget.mem -= spend_budget.mem;
-        self.ex_budget.cpu -= spend_budget.cpu;
+        self.ex_budget = self.ex_budget - spend_budget;
 
         if self.ex_budget.mem < 0 || self.ex_budget.cpu < 0 {
             Err(MachineError::OutOfExError(self.ex_budget))
@@ -2,14 +2,14 @@ use crate::{
     builtin::DefaultFunction,
     machine::{
         cost_model::{
-            builtin_costs::BuiltinCostModel,
+            builtin_costs::{BuiltinCostModel, BuiltinCosting},
             cost_map::CostMap,
             costing::{
                 Cost, OneArgumentCosting, SixArgumentsCosting, ThreeArgumentsCosting,
                 TwoArgumentsCosting,
             },
         },
-        ExBudget,
+        ExBudget, PlutusVersion,
     },
 };
 
@@ -304,6 +304,8 @@ impl Default for BuiltinCostsV2 {
     }
 }
 impl BuiltinCostModel for BuiltinCostsV2 {
+    const PLUTUS_VERSION: PlutusVersion = PlutusVersion::V2;
+
     fn initialize(cost_map: &CostMap) -> Self {
         Self {
             add_integer: TwoArgumentsCosting::new(
@@ -915,4 +917,213 @@ impl BuiltinCostModel for BuiltinCostsV2 {
             _ => None,
         }
     }
+
+    fn costings(&self) -> Vec<(DefaultFunction, BuiltinCosting<'_>)> {
+        vec![
+            (
+                DefaultFunction::AddInteger,
+                BuiltinCosting::Two(&self.add_integer),
+            ),
+            (
+                DefaultFunction::SubtractInteger,
+                BuiltinCosting::Two(&self.subtract_integer),
+            ),
+            (
+                DefaultFunction::MultiplyInteger,
+                BuiltinCosting::Two(&self.multiply_integer),
+            ),
+            (
+                DefaultFunction::DivideInteger,
+                BuiltinCosting::Two(&self.divide_integer),
+            ),
+            (
+                DefaultFunction::QuotientInteger,
+                BuiltinCosting::Two(&self.quotient_integer),
+            ),
+            (
+                DefaultFunction::RemainderInteger,
+                BuiltinCosting::Two(&self.remainder_integer),
+            ),
+            (
+                DefaultFunction::ModInteger,
+                BuiltinCosting::Two(&self.mod_integer),
+            ),
+            (
+                DefaultFunction::EqualsInteger,
+                BuiltinCosting::Two(&self.equals_integer),
+            ),
+            (
+                DefaultFunction::LessThanInteger,
+                BuiltinCosting::Two(&self.less_than_integer),
+            ),
+            (
+                DefaultFunction::LessThanEqualsInteger,
+                BuiltinCosting::Two(&self.less_than_equals_integer),
+            ),
+            (
+                DefaultFunction::AppendByteString,
+                BuiltinCosting::Two(&self.append_byte_string),
+            ),
+            (
+                DefaultFunction::ConsByteString,
+                BuiltinCosting::Two(&self.cons_byte_string),
+            ),
+            (
+                DefaultFunction::SliceByteString,
+                BuiltinCosting::Three(&self.slice_byte_string),
+            ),
+            (
+                DefaultFunction::LengthOfByteString,
+                BuiltinCosting::One(&self.length_of_byte_string),
+            ),
+            (
+                DefaultFunction::IndexByteString,
+                BuiltinCosting::Two(&self.index_byte_string),
+            ),
+            (
+                DefaultFunction::EqualsByteString,
+                BuiltinCosting::Two(&self.equals_byte_string),
+            ),
+            (
+                DefaultFunction::LessThanByteString,
+                BuiltinCosting::Two(&self.less_than_byte_string),
+            ),
+            (
+                DefaultFunction::LessThanEqualsByteString,
+                BuiltinCosting::Two(&self.less_than_equals_byte_string),
+            ),
+            (
+                DefaultFunction::Sha2_256,
+                BuiltinCosting::One(&self.sha2_256),
+            ),
+            (
+                DefaultFunction::Sha3_256,
+                BuiltinCosting::One(&self.sha3_256),
+            ),
+            (
+                DefaultFunction::Blake2b_256,
+                BuiltinCosting::One(&self.blake2b_256),
+            ),
+            (
+                DefaultFunction::VerifyEd25519Signature,
+                BuiltinCosting::Three(&self.verify_ed25519_signature),
+            ),
+            (
+                DefaultFunction::VerifyEcdsaSecp256k1Signature,
+                BuiltinCosting::Three(&self.verify_ecdsa_secp256k1_signature),
+            ),
+            (
+                DefaultFunction::VerifySchnorrSecp256k1Signature,
+                BuiltinCosting::Three(&self.verify_schnorr_secp256k1_signature),
+            ),
+            (
+                DefaultFunction::AppendString,
+                BuiltinCosting::Two(&self.append_string),
+            ),
+            (
+                DefaultFunction::EqualsString,
+                BuiltinCosting::Two(&self.equals_string),
+            ),
+            (
+                DefaultFunction::EncodeUtf8,
+                BuiltinCosting::One(&self.encode_utf8),
+            ),
+            (
+                DefaultFunction::DecodeUtf8,
+                BuiltinCosting::One(&self.decode_utf8),
+            ),
+            (
+                DefaultFunction::IfThenElse,
+                BuiltinCosting::Three(&self.if_then_else),
+            ),
+            (
+                DefaultFunction::ChooseUnit,
+                BuiltinCosting::Two(&self.choose_unit),
+            ),
+            (DefaultFunction::Trace, BuiltinCosting::Two(&self.trace)),
+            (
+                DefaultFunction::FstPair,
+                BuiltinCosting::One(&self.fst_pair),
+            ),
+            (
+                DefaultFunction::SndPair,
+                BuiltinCosting::One(&self.snd_pair),
+            ),
+            (
+                DefaultFunction::ChooseList,
+                BuiltinCosting::Three(&self.choose_list),
+            ),
+            (DefaultFunction::MkCons, BuiltinCosting::Two(&self.mk_cons)),
+            (
+                DefaultFunction::HeadList,
+                BuiltinCosting::One(&self.head_list),
+            ),
+            (
+                DefaultFunction::TailList,
+                BuiltinCosting::One(&self.tail_list),
+            ),
+            (
+                DefaultFunction::NullList,
+                BuiltinCosting::One(&self.null_list),
+            ),
+            (
+                DefaultFunction::ChooseData,
+                BuiltinCosting::Six(&self.choose_data),
+            ),
+            (
+                DefaultFunction::ConstrData,
+                BuiltinCosting::Two(&self.constr_data),
+            ),
+            (
+                DefaultFunction::MapData,
+                BuiltinCosting::One(&self.map_data),
+            ),
+            (
+                DefaultFunction::ListData,
+                BuiltinCosting::One(&self.list_data),
+            ),
+            (DefaultFunction::IData, BuiltinCosting::One(&self.i_data)),
+            (DefaultFunction::BData, BuiltinCosting::One(&self.b_data)),
+            (
+                DefaultFunction::UnConstrData,
+                BuiltinCosting::One(&self.un_constr_data),
+            ),
+            (
+                DefaultFunction::UnMapData,
+                BuiltinCosting::One(&self.un_map_data),
+            ),
+            (
+                DefaultFunction::UnListData,
+                BuiltinCosting::One(&self.un_list_data),
+            ),
+            (
+                DefaultFunction::UnIData,
+                BuiltinCosting::One(&self.un_i_data),
+            ),
+            (
+                DefaultFunction::UnBData,
+                BuiltinCosting::One(&self.un_b_data),
+            ),
+            (
+                DefaultFunction::EqualsData,
+                BuiltinCosting::Two(&self.equals_data),
+            ),
+            (
+                DefaultFunction::MkPairData,
+                BuiltinCosting::Two(&self.mk_pair_data),
+            ),
+            (
+                DefaultFunction::MkNilData,
+                BuiltinCosting::One(&self.mk_nil_data),
+            ),
+            (
+                DefaultFunction::MkNilPairData,
+                BuiltinCosting::One(&self.mk_nil_pair_data),
+            ),
+            (
+                DefaultFunction::SerialiseData,
+                BuiltinCosting::One(&self.serialise_data),
+            ),
+        ]
+    }
 }
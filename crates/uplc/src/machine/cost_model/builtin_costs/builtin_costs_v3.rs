@@ -2,14 +2,14 @@ use crate::{
     builtin::DefaultFunction,
     machine::{
         cost_model::{
-            builtin_costs::BuiltinCostModel,
+            builtin_costs::{BuiltinCostModel, BuiltinCosting},
             cost_map::CostMap,
             costing::{
                 Cost, OneArgumentCosting, SixArgumentsCosting, ThreeArgumentsCosting,
                 TwoArgumentsCosting,
             },
         },
-        ExBudget,
+        ExBudget, PlutusVersion,
     },
 };
 
@@ -557,6 +557,8 @@ impl Default for BuiltinCostsV3 {
 }
 
 impl BuiltinCostModel for BuiltinCostsV3 {
+    const PLUTUS_VERSION: PlutusVersion = PlutusVersion::V3;
+
     fn initialize(cost_map: &CostMap) -> Self {
         Self {
             add_integer: TwoArgumentsCosting::new(
@@ -1719,4 +1721,401 @@ impl BuiltinCostModel for BuiltinCostsV3 {
             )),
         }
     }
+
+    fn costings(&self) -> Vec<(DefaultFunction, BuiltinCosting<'_>)> {
+        vec![
+            (
+                DefaultFunction::AddInteger,
+                BuiltinCosting::Two(&self.add_integer),
+            ),
+            (
+                DefaultFunction::SubtractInteger,
+                BuiltinCosting::Two(&self.subtract_integer),
+            ),
+            (
+                DefaultFunction::MultiplyInteger,
+                BuiltinCosting::Two(&self.multiply_integer),
+            ),
+            (
+                DefaultFunction::DivideInteger,
+                BuiltinCosting::Two(&self.divide_integer),
+            ),
+            (
+                DefaultFunction::QuotientInteger,
+                BuiltinCosting::Two(&self.quotient_integer),
+            ),
+            (
+                DefaultFunction::RemainderInteger,
+                BuiltinCosting::Two(&self.remainder_integer),
+            ),
+            (
+                DefaultFunction::ModInteger,
+                BuiltinCosting::Two(&self.mod_integer),
+            ),
+            (
+                DefaultFunction::EqualsInteger,
+                BuiltinCosting::Two(&self.equals_integer),
+            ),
+            (
+                DefaultFunction::LessThanInteger,
+                BuiltinCosting::Two(&self.less_than_integer),
+            ),
+            (
+                DefaultFunction::LessThanEqualsInteger,
+                BuiltinCosting::Two(&self.less_than_equals_integer),
+            ),
+            (
+                DefaultFunction::AppendByteString,
+                BuiltinCosting::Two(&self.append_byte_string),
+            ),
+            (
+                DefaultFunction::ConsByteString,
+                BuiltinCosting::Two(&self.cons_byte_string),
+            ),
+            (
+                DefaultFunction::SliceByteString,
+                BuiltinCosting::Three(&self.slice_byte_string),
+            ),
+            (
+                DefaultFunction::LengthOfByteString,
+                BuiltinCosting::One(&self.length_of_byte_string),
+            ),
+            (
+                DefaultFunction::IndexByteString,
+                BuiltinCosting::Two(&self.index_byte_string),
+            ),
+            (
+                DefaultFunction::EqualsByteString,
+                BuiltinCosting::Two(&self.equals_byte_string),
+            ),
+            (
+                DefaultFunction::LessThanByteString,
+                BuiltinCosting::Two(&self.less_than_byte_string),
+            ),
+            (
+                DefaultFunction::LessThanEqualsByteString,
+                BuiltinCosting::Two(&self.less_than_equals_byte_string),
+            ),
+            (
+                DefaultFunction::Sha2_256,
+                BuiltinCosting::One(&self.sha2_256),
+            ),
+            (
+                DefaultFunction::Sha3_256,
+                BuiltinCosting::One(&self.sha3_256),
+            ),
+            (
+                DefaultFunction::Blake2b_224,
+                BuiltinCosting::One(&self.blake2b_224),
+            ),
+            (
+                DefaultFunction::Blake2b_256,
+                BuiltinCosting::One(&self.blake2b_256),
+            ),
+            (
+                DefaultFunction::Keccak_256,
+                BuiltinCosting::One(&self.keccak_256),
+            ),
+            (
+                DefaultFunction::VerifyEd25519Signature,
+                BuiltinCosting::Three(&self.verify_ed25519_signature),
+            ),
+            (
+                DefaultFunction::VerifyEcdsaSecp256k1Signature,
+                BuiltinCosting::Three(&self.verify_ecdsa_secp256k1_signature),
+            ),
+            (
+                DefaultFunction::VerifySchnorrSecp256k1Signature,
+                BuiltinCosting::Three(&self.verify_schnorr_secp256k1_signature),
+            ),
+            (
+                DefaultFunction::AppendString,
+                BuiltinCosting::Two(&self.append_string),
+            ),
+            (
+                DefaultFunction::EqualsString,
+                BuiltinCosting::Two(&self.equals_string),
+            ),
+            (
+                DefaultFunction::EncodeUtf8,
+                BuiltinCosting::One(&self.encode_utf8),
+            ),
+            (
+                DefaultFunction::DecodeUtf8,
+                BuiltinCosting::One(&self.decode_utf8),
+            ),
+            (
+                DefaultFunction::IfThenElse,
+                BuiltinCosting::Three(&self.if_then_else),
+            ),
+            (
+                DefaultFunction::ChooseUnit,
+                BuiltinCosting::Two(&self.choose_unit),
+            ),
+            (DefaultFunction::Trace, BuiltinCosting::Two(&self.trace)),
+            (
+                DefaultFunction::FstPair,
+                BuiltinCosting::One(&self.fst_pair),
+            ),
+            (
+                DefaultFunction::SndPair,
+                BuiltinCosting::One(&self.snd_pair),
+            ),
+            (
+                DefaultFunction::ChooseList,
+                BuiltinCosting::Three(&self.choose_list),
+            ),
+            (DefaultFunction::MkCons, BuiltinCosting::Two(&self.mk_cons)),
+            (
+                DefaultFunction::HeadList,
+                BuiltinCosting::One(&self.head_list),
+            ),
+            (
+                DefaultFunction::TailList,
+                BuiltinCosting::One(&self.tail_list),
+            ),
+            (
+                DefaultFunction::NullList,
+                BuiltinCosting::One(&self.null_list),
+            ),
+            (
+                DefaultFunction::ChooseData,
+                BuiltinCosting::Six(&self.choose_data),
+            ),
+            (
+                DefaultFunction::ConstrData,
+                BuiltinCosting::Two(&self.constr_data),
+            ),
+            (
+                DefaultFunction::MapData,
+                BuiltinCosting::One(&self.map_data),
+            ),
+            (
+                DefaultFunction::ListData,
+                BuiltinCosting::One(&self.list_data),
+            ),
+            (DefaultFunction::IData, BuiltinCosting::One(&self.i_data)),
+            (DefaultFunction::BData, BuiltinCosting::One(&self.b_data)),
+            (
+                DefaultFunction::UnConstrData,
+                BuiltinCosting::One(&self.un_constr_data),
+            ),
+            (
+                DefaultFunction::UnMapData,
+                BuiltinCosting::One(&self.un_map_data),
+            ),
+            (
+                DefaultFunction::UnListData,
+                BuiltinCosting::One(&self.un_list_data),
+            ),
+            (
+                DefaultFunction::UnIData,
+                BuiltinCosting::One(&self.un_i_data),
+            ),
+            (
+                DefaultFunction::UnBData,
+                BuiltinCosting::One(&self.un_b_data),
+            ),
+            (
+                DefaultFunction::EqualsData,
+                BuiltinCosting::Two(&self.equals_data),
+            ),
+            (
+                DefaultFunction::MkPairData,
+                BuiltinCosting::Two(&self.mk_pair_data),
+            ),
+            (
+                DefaultFunction::MkNilData,
+                BuiltinCosting::One(&self.mk_nil_data),
+            ),
+            (
+                DefaultFunction::MkNilPairData,
+                BuiltinCosting::One(&self.mk_nil_pair_data),
+            ),
+            (
+                DefaultFunction::SerialiseData,
+                BuiltinCosting::One(&self.serialise_data),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_Add,
+                BuiltinCosting::Two(&self.bls12_381_g1_add),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_Neg,
+                BuiltinCosting::One(&self.bls12_381_g1_neg),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_ScalarMul,
+                BuiltinCosting::Two(&self.bls12_381_g1_scalar_mul),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_Equal,
+                BuiltinCosting::Two(&self.bls12_381_g1_equal),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_Compress,
+                BuiltinCosting::One(&self.bls12_381_g1_compress),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_Uncompress,
+                BuiltinCosting::One(&self.bls12_381_g1_uncompress),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_HashToGroup,
+                BuiltinCosting::Two(&self.bls12_381_g1_hash_to_group),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_Add,
+                BuiltinCosting::Two(&self.bls12_381_g2_add),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_Neg,
+                BuiltinCosting::One(&self.bls12_381_g2_neg),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_ScalarMul,
+                BuiltinCosting::Two(&self.bls12_381_g2_scalar_mul),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_Equal,
+                BuiltinCosting::Two(&self.bls12_381_g2_equal),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_Compress,
+                BuiltinCosting::One(&self.bls12_381_g2_compress),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_Uncompress,
+                BuiltinCosting::One(&self.bls12_381_g2_uncompress),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_HashToGroup,
+                BuiltinCosting::Two(&self.bls12_381_g2_hash_to_group),
+            ),
+            (
+                DefaultFunction::Bls12_381_MillerLoop,
+                BuiltinCosting::Two(&self.bls12_381_miller_loop),
+            ),
+            (
+                DefaultFunction::Bls12_381_MulMlResult,
+                BuiltinCosting::Two(&self.bls12_381_mul_ml_result),
+            ),
+            (
+                DefaultFunction::Bls12_381_FinalVerify,
+                BuiltinCosting::Two(&self.bls12_381_final_verify),
+            ),
+            (
+                DefaultFunction::IntegerToByteString,
+                BuiltinCosting::Three(&self.integer_to_byte_string),
+            ),
+            (
+                DefaultFunction::ByteStringToInteger,
+                BuiltinCosting::Two(&self.byte_string_to_integer),
+            ),
+            (
+                DefaultFunction::AndByteString,
+                BuiltinCosting::Three(&self.and_byte_string),
+            ),
+            (
+                DefaultFunction::OrByteString,
+                BuiltinCosting::Three(&self.or_byte_string),
+            ),
+            (
+                DefaultFunction::XorByteString,
+                BuiltinCosting::Three(&self.xor_byte_string),
+            ),
+            (
+                DefaultFunction::ComplementByteString,
+                BuiltinCosting::One(&self.complement_byte_string),
+            ),
+            (
+                DefaultFunction::ReadBit,
+                BuiltinCosting::Two(&self.read_bit),
+            ),
+            (
+                DefaultFunction::WriteBits,
+                BuiltinCosting::Three(&self.write_bits),
+            ),
+            (
+                DefaultFunction::ReplicateByte,
+                BuiltinCosting::Two(&self.replicate_byte),
+            ),
+            (
+                DefaultFunction::ShiftByteString,
+                BuiltinCosting::Two(&self.shift_byte_string),
+            ),
+            (
+                DefaultFunction::RotateByteString,
+                BuiltinCosting::Two(&self.rotate_byte_string),
+            ),
+            (
+                DefaultFunction::CountSetBits,
+                BuiltinCosting::One(&self.count_set_bits),
+            ),
+            (
+                DefaultFunction::FindFirstSetBit,
+                BuiltinCosting::One(&self.find_first_set_bit),
+            ),
+            (
+                DefaultFunction::Ripemd_160,
+                BuiltinCosting::One(&self.ripemd_160),
+            ),
+            (
+                DefaultFunction::ExpModInteger,
+                BuiltinCosting::Three(&self.exp_mod_integer),
+            ),
+            (
+                DefaultFunction::DropList,
+                BuiltinCosting::Two(&self.drop_list),
+            ),
+            (
+                DefaultFunction::LengthOfArray,
+                BuiltinCosting::One(&self.length_of_array),
+            ),
+            (
+                DefaultFunction::ListToArray,
+                BuiltinCosting::Two(&self.list_to_array),
+            ),
+            (
+                DefaultFunction::IndexArray,
+                BuiltinCosting::Two(&self.index_array),
+            ),
+            (
+                DefaultFunction::Bls12_381_G1_MultiScalarMul,
+                BuiltinCosting::Two(&self.bls12_381_g1_multi_scalar_mul),
+            ),
+            (
+                DefaultFunction::Bls12_381_G2_MultiScalarMul,
+                BuiltinCosting::Two(&self.bls12_381_g2_multi_scalar_mul),
+            ),
+            (
+                DefaultFunction::InsertCoin,
+                BuiltinCosting::One(&self.insert_coin),
+            ),
+            (
+                DefaultFunction::LookupCoin,
+                BuiltinCosting::Three(&self.lookup_coin),
+            ),
+            (
+                DefaultFunction::UnionValue,
+                BuiltinCosting::Two(&self.union_value),
+            ),
+            (
+                DefaultFunction::ValueContains,
+                BuiltinCosting::Two(&self.value_contains),
+            ),
+            (
+                DefaultFunction::ValueData,
+                BuiltinCosting::One(&self.value_data),
+            ),
+            (
+                DefaultFunction::UnValueData,
+                BuiltinCosting::One(&self.un_value_data),
+            ),
+            (
+                DefaultFunction::ScaleValue,
+                BuiltinCosting::Two(&self.scale_value),
+            ),
+        ]
+    }
 }
@@ -0,0 +1,1187 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    time::{Duration, Instant},
+};
+
+use num::bigint::Sign;
+use serde_json::Value as Json;
+
+use crate::{
+    arena::Arena,
+    binder::DeBruijn,
+    builtin::DefaultFunction,
+    constant::{Constant, Integer},
+    data::PlutusData,
+    machine::{runtime::Runtime, value::Value, BuiltinSemantics, ExBudget, Machine, PlutusVersion},
+    program::Version,
+    typ::Type,
+};
+
+use super::{
+    builtin_costs::{
+        builtin_costs_v1::BuiltinCostsV1, builtin_costs_v2::BuiltinCostsV2,
+        builtin_costs_v3::BuiltinCostsV3, BuiltinCostModel, BuiltinCosting,
+    },
+    cost_map::CostMap,
+    costing::{
+        Cost, OneArgument, OneArgumentCosting as One, SixArguments, SixArgumentsCosting as Six,
+        ThreeArguments, ThreeArgumentsCosting as Three, TwoArguments, TwoArgumentsCosting as Two,
+    },
+    json, CostModel, CostModelError,
+};
+
+/// How to calibrate the builtins of a cost model.
+///
+/// Without an explicit cost model the default one for `plutus_version` is
+/// calibrated. Every sized argument of a builtin is generated at each of
+/// `sizes`, in machine words, and every combination of them becomes one
+/// sample. Each sample is timed `repetitions` times and the median is kept.
+#[derive(Debug, Clone)]
+pub struct CalibrationConfig<'c> {
+    pub plutus_version: PlutusVersion,
+    pub cost_model: Option<&'c [i64]>,
+    pub sizes: Vec<usize>,
+    pub repetitions: usize,
+    pub builtins: Option<Vec<DefaultFunction>>,
+}
+
+impl<'c> CalibrationConfig<'c> {
+    pub fn new(plutus_version: PlutusVersion) -> Self {
+        CalibrationConfig {
+            plutus_version,
+            cost_model: None,
+            sizes: vec![1, 4, 16, 64],
+            repetitions: 10,
+            builtins: None,
+        }
+    }
+
+    pub fn with_cost_model(self, cost_model: &'c [i64]) -> Self {
+        CalibrationConfig {
+            cost_model: Some(cost_model),
+            ..self
+        }
+    }
+
+    pub fn with_sizes(self, sizes: Vec<usize>) -> Self {
+        CalibrationConfig { sizes, ..self }
+    }
+
+    pub fn with_repetitions(self, repetitions: usize) -> Self {
+        CalibrationConfig {
+            repetitions,
+            ..self
+        }
+    }
+
+    /// Only calibrate `builtins` instead of every builtin of the model.
+    pub fn with_builtins(self, builtins: Vec<DefaultFunction>) -> Self {
+        CalibrationConfig {
+            builtins: Some(builtins),
+            ..self
+        }
+    }
+
+    /// Time the builtins of the cost model on generated arguments and fit
+    /// its CPU costing functions to the timings.
+    ///
+    /// Timings are only meaningful in release builds. Fails if the given
+    /// cost model is invalid.
+    pub fn calibrate(&self) -> Result<CalibrationReport, CostModelError> {
+        let report = match self.plutus_version {
+            PlutusVersion::V1 => calibrate(self.cost_model::<BuiltinCostsV1>()?, self),
+            PlutusVersion::V2 => calibrate(self.cost_model::<BuiltinCostsV2>()?, self),
+            PlutusVersion::V3 => calibrate(self.cost_model::<BuiltinCostsV3>()?, self),
+        };
+
+        Ok(report)
+    }
+
+    fn cost_model<B: BuiltinCostModel + Default>(&self) -> Result<CostModel<B>, CostModelError> {
+        match self.cost_model {
+            Some(params) => CostModel::initialize_cost_model(&self.plutus_version, params),
+            None => Ok(CostModel::default()),
+        }
+    }
+}
+
+/// One timed call of a builtin.
+///
+/// `sizes` are the argument sizes the machine costed the call with. Like the
+/// CPU costs of the model, `measured` is in picoseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub sizes: Vec<i64>,
+    pub measured: i64,
+    pub model: i64,
+}
+
+/// The samples of one builtin and the CPU costing function fitted to them.
+///
+/// The candidate has the same shape as the costing function of the model.
+/// There is none when the samples cannot tell its coefficients apart, and
+/// then there are no residuals either.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinCalibration {
+    pub builtin: DefaultFunction,
+    pub samples: Vec<Sample>,
+    pub model: String,
+    pub candidate: Option<String>,
+    /// `measured` minus the candidate's cost, per sample.
+    pub residuals: Vec<i64>,
+}
+
+impl BuiltinCalibration {
+    /// The samples that took longer than the model charges for.
+    pub fn under_costed(&self) -> impl Iterator<Item = &Sample> {
+        self.samples
+            .iter()
+            .filter(|sample| sample.measured > sample.model)
+    }
+
+    /// The largest ratio of measured to charged CPU over all samples.
+    pub fn max_ratio(&self) -> f64 {
+        self.samples
+            .iter()
+            .map(|sample| sample.measured as f64 / sample.model.max(1) as f64)
+            .fold(0.0, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationReport {
+    pub builtins: Vec<BuiltinCalibration>,
+    /// Builtins of the model with no argument generator, or whose generated
+    /// arguments were rejected.
+    pub skipped: Vec<DefaultFunction>,
+    /// Every parameter of the model, with the CPU parameters of calibrated
+    /// builtins replaced by their candidates.
+    pub params: Vec<(&'static str, i64)>,
+}
+
+impl fmt::Display for CalibrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for calibration in &self.builtins {
+            writeln!(f, "{}:", calibration.builtin.name())?;
+            writeln!(f, "  model:     {}", calibration.model)?;
+
+            match &calibration.candidate {
+                Some(candidate) => writeln!(f, "  candidate: {candidate}")?,
+                None => writeln!(f, "  candidate: none")?,
+            }
+
+            writeln!(
+                f,
+                "  under-costed: {}/{} samples, max measured/model ratio {:.2}",
+                calibration.under_costed().count(),
+                calibration.samples.len(),
+                calibration.max_ratio()
+            )?;
+
+            if let Some(worst) = calibration.residuals.iter().max_by_key(|r| r.abs()) {
+                writeln!(f, "  largest residual: {worst:+}")?;
+            }
+        }
+
+        if !self.skipped.is_empty() {
+            let names: Vec<_> = self.skipped.iter().map(|b| b.name()).collect();
+
+            writeln!(f, "skipped: {}", names.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn calibrate<B: BuiltinCostModel>(
+    cost_model: CostModel<B>,
+    config: &CalibrationConfig,
+) -> CalibrationReport {
+    let mut costs = CostModel {
+        machine_startup: cost_model.machine_startup,
+        machine_costs: cost_model.machine_costs,
+        builtin_costs: Recording::new(cost_model.builtin_costs),
+    };
+
+    let builtins: Vec<DefaultFunction> = costs
+        .builtin_costs
+        .inner
+        .costings()
+        .into_iter()
+        .map(|(builtin, _)| builtin)
+        .filter(|builtin| match &config.builtins {
+            Some(wanted) => wanted.contains(builtin),
+            None => true,
+        })
+        .collect();
+
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let mut measured = Vec::new();
+    let mut skipped = Vec::new();
+
+    for builtin in builtins {
+        let Some(args) = arguments(builtin) else {
+            skipped.push(builtin);
+
+            continue;
+        };
+
+        let sized = args
+            .iter()
+            .filter(|arg| matches!(arg, Arg::Sized(_)))
+            .count();
+
+        let mut samples = Vec::new();
+
+        for sizes in grid(&config.sizes, sized) {
+            let (returned, sample) = measure(
+                costs,
+                builtin,
+                &args,
+                &sizes,
+                config.repetitions.max(1),
+                &mut rng,
+            );
+
+            costs = returned;
+
+            match sample {
+                Some(sample) => samples.push(sample),
+                None => {
+                    samples.clear();
+
+                    break;
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            skipped.push(builtin);
+        } else {
+            measured.push((builtin, samples));
+        }
+    }
+
+    let model = &costs.builtin_costs.inner;
+    let costings = model.costings();
+    let mut builtin_costs = json::builtin_costs(model);
+    let mut builtins = Vec::new();
+
+    for (builtin, samples) in measured {
+        let Some((_, costing)) = costings.iter().find(|(b, _)| *b == builtin) else {
+            continue;
+        };
+
+        let (model, candidate) = match costing {
+            BuiltinCosting::One(c) => (c.cpu.to_string(), fit_one(&c.cpu, &samples)),
+            BuiltinCosting::Two(c) => (c.cpu.to_string(), fit_two(&c.cpu, &samples)),
+            BuiltinCosting::Three(c) => (c.cpu.to_string(), fit_three(&c.cpu, &samples)),
+            BuiltinCosting::Six(c) => (c.cpu.to_string(), fit_six(&samples)),
+        };
+
+        let residuals = match &candidate {
+            Some(candidate) => samples
+                .iter()
+                .map(|sample| {
+                    sample
+                        .measured
+                        .saturating_sub(candidate.cost(&sample.sizes))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        if let Some(candidate) = &candidate {
+            builtin_costs[builtin.name()]["cpu"] = candidate.to_json();
+        }
+
+        builtins.push(BuiltinCalibration {
+            builtin,
+            samples,
+            model,
+            candidate: candidate.map(|candidate| candidate.to_string()),
+            residuals,
+        });
+    }
+
+    let params = json::named_params(
+        &B::PLUTUS_VERSION,
+        &builtin_costs,
+        &json::machine_costs(costs.machine_startup, &costs.machine_costs),
+    );
+
+    CalibrationReport {
+        builtins,
+        skipped,
+        params,
+    }
+}
+
+/// Wraps a builtin cost model to remember the sizes the machine last costed
+/// a builtin with, along with the resulting cost. The sizes are copied into
+/// the same buffer on every call so recording does not allocate while a
+/// builtin is being timed.
+struct Recording<B> {
+    inner: B,
+    sizes: RefCell<Vec<i64>>,
+    budget: Cell<Option<ExBudget>>,
+}
+
+impl<B> Recording<B> {
+    fn new(inner: B) -> Self {
+        Recording {
+            inner,
+            sizes: RefCell::new(Vec::with_capacity(6)),
+            budget: Cell::new(None),
+        }
+    }
+}
+
+impl<B: BuiltinCostModel> BuiltinCostModel for Recording<B> {
+    const PLUTUS_VERSION: PlutusVersion = B::PLUTUS_VERSION;
+
+    fn initialize(cost_map: &CostMap) -> Self {
+        Recording::new(B::initialize(cost_map))
+    }
+
+    fn get_cost(&self, builtin: DefaultFunction, args: &[i64]) -> Option<ExBudget> {
+        let budget = self.inner.get_cost(builtin, args)?;
+
+        let mut sizes = self.sizes.borrow_mut();
+
+        sizes.clear();
+        sizes.extend_from_slice(args);
+
+        self.budget.set(Some(budget));
+
+        Some(budget)
+    }
+
+    fn costings(&self) -> Vec<(DefaultFunction, BuiltinCosting<'_>)> {
+        self.inner.costings()
+    }
+}
+
+/// How long to keep calling a builtin for to time it once.
+const BATCH: Duration = Duration::from_micros(20);
+
+/// Call `builtin` on arguments of `sizes` and time it. The cost model moves
+/// into the machine for the call and is handed back afterwards.
+fn measure<B: BuiltinCostModel>(
+    costs: CostModel<Recording<B>>,
+    builtin: DefaultFunction,
+    args: &[Arg],
+    sizes: &[usize],
+    repetitions: usize,
+    rng: &mut Rng,
+) -> (CostModel<Recording<B>>, Option<Sample>) {
+    let arena = Arena::new();
+
+    let mut sizes = sizes.iter();
+
+    let runtime = args.iter().fold(
+        Runtime::<DeBruijn>::new(&arena, arena.alloc(builtin)),
+        |runtime, arg| {
+            let constant = match arg {
+                Arg::Sized(generate) => generate(&arena, *sizes.next().unwrap_or(&1), rng),
+                Arg::Fixed(generate) => generate(&arena),
+            };
+
+            runtime.push(&arena, Value::con(&arena, constant))
+        },
+    );
+
+    let mut machine = Machine::new(
+        &arena,
+        ExBudget::new(i64::MAX, i64::MAX),
+        costs,
+        BuiltinSemantics::from(&B::PLUTUS_VERSION),
+        *Version::plutus_v3(&arena),
+    );
+
+    let start = Instant::now();
+    let ok = machine.call(runtime).is_ok();
+    let first = start.elapsed();
+
+    // Calls close to the resolution of the clock are timed in batches.
+    let batch = (BATCH.as_nanos() / first.as_nanos().max(1)).clamp(1, 1000);
+
+    let mut timings: Vec<i64> = (0..if ok { repetitions } else { 0 })
+        .map(|_| {
+            let start = Instant::now();
+
+            for _ in 0..batch {
+                let _ = machine.call(runtime);
+            }
+
+            (start.elapsed().as_nanos() * 1000 / batch) as i64
+        })
+        .collect();
+
+    let costs = machine.costs;
+    let recorded = costs.builtin_costs.budget.take();
+
+    let sample = match recorded {
+        Some(budget) if ok => {
+            let sizes = costs.builtin_costs.sizes.borrow().clone();
+
+            timings.sort_unstable();
+
+            Some(Sample {
+                sizes,
+                measured: timings[timings.len() / 2],
+                model: budget.cpu,
+            })
+        }
+        _ => None,
+    };
+
+    (costs, sample)
+}
+
+/// Every way to pick one of `sizes` for each of `count` arguments.
+fn grid(sizes: &[usize], count: usize) -> Vec<Vec<usize>> {
+    (0..count).fold(vec![vec![]], |combinations, _| {
+        combinations
+            .iter()
+            .flat_map(|combination| {
+                sizes.iter().map(move |size| {
+                    let mut combination = combination.clone();
+                    combination.push((*size).max(1));
+                    combination
+                })
+            })
+            .collect()
+    })
+}
+
+type Generate = for<'a> fn(&'a Arena, usize, &mut Rng) -> &'a Constant<'a>;
+
+type Fixed = for<'a> fn(&'a Arena) -> &'a Constant<'a>;
+
+enum Arg {
+    /// An argument generated at every calibration size.
+    Sized(Generate),
+    /// An argument that stays the same across samples, like an index that
+    /// has to be in range.
+    Fixed(Fixed),
+}
+
+/// The arguments to call `builtin` with, if it can be calibrated. Builtins
+/// whose arguments have to be valid curve points, keys or ledger values are
+/// left out.
+fn arguments(builtin: DefaultFunction) -> Option<Vec<Arg>> {
+    use Arg::*;
+    use DefaultFunction::*;
+
+    let args = match builtin {
+        AddInteger
+        | SubtractInteger
+        | MultiplyInteger
+        | DivideInteger
+        | QuotientInteger
+        | RemainderInteger
+        | ModInteger
+        | EqualsInteger
+        | LessThanInteger
+        | LessThanEqualsInteger => vec![Sized(integer), Sized(integer)],
+        AppendByteString | EqualsByteString | LessThanByteString | LessThanEqualsByteString => {
+            vec![Sized(byte_string), Sized(byte_string)]
+        }
+        ConsByteString => vec![Fixed(byte), Sized(byte_string)],
+        SliceByteString => vec![Fixed(zero), Fixed(zero), Sized(byte_string)],
+        LengthOfByteString | Sha2_256 | Sha3_256 | Blake2b_256 | Keccak_256 | Blake2b_224
+        | Ripemd_160 | ComplementByteString | CountSetBits | FindFirstSetBit => {
+            vec![Sized(byte_string)]
+        }
+        IndexByteString | ReadBit => vec![Sized(byte_string), Fixed(zero)],
+        AppendString | EqualsString => vec![Sized(string), Sized(string)],
+        EncodeUtf8 => vec![Sized(string)],
+        DecodeUtf8 => vec![Sized(utf8)],
+        IfThenElse => vec![Fixed(true_), Fixed(unit), Fixed(unit)],
+        ChooseUnit => vec![Fixed(unit), Fixed(unit)],
+        Trace => vec![Sized(string), Fixed(unit)],
+        FstPair | SndPair => vec![Fixed(pair)],
+        ChooseList => vec![Sized(list), Fixed(unit), Fixed(unit)],
+        MkCons => vec![Fixed(zero), Sized(list)],
+        HeadList | TailList | NullList => vec![Sized(list)],
+        ChooseData => vec![
+            Sized(data),
+            Fixed(unit),
+            Fixed(unit),
+            Fixed(unit),
+            Fixed(unit),
+            Fixed(unit),
+        ],
+        ConstrData => vec![Fixed(zero), Sized(data_list)],
+        MapData => vec![Sized(data_pairs)],
+        ListData => vec![Sized(data_list)],
+        IData => vec![Sized(integer)],
+        BData => vec![Sized(byte_string)],
+        UnConstrData => vec![Sized(constr_data)],
+        UnMapData => vec![Sized(map_data)],
+        UnListData => vec![Sized(data)],
+        UnIData => vec![Sized(integer_data)],
+        UnBData => vec![Sized(byte_string_data)],
+        EqualsData | MkPairData => vec![Sized(data), Sized(data)],
+        SerialiseData => vec![Sized(data)],
+        MkNilData | MkNilPairData => vec![Fixed(unit)],
+        IntegerToByteString => vec![Fixed(true_), Fixed(zero), Sized(integer)],
+        ByteStringToInteger => vec![Fixed(true_), Sized(byte_string)],
+        AndByteString | OrByteString | XorByteString => {
+            vec![Fixed(false_), Sized(byte_string), Sized(byte_string)]
+        }
+        WriteBits => vec![Sized(byte_string), Fixed(indices), Fixed(false_)],
+        ReplicateByte => vec![Sized(literal), Fixed(zero)],
+        ShiftByteString | RotateByteString => vec![Sized(byte_string), Sized(literal)],
+        ExpModInteger => vec![Sized(integer), Sized(integer), Sized(integer)],
+        DropList => vec![Sized(literal), Sized(list)],
+        LengthOfArray => vec![Sized(array)],
+        ListToArray => vec![Sized(list)],
+        IndexArray => vec![Sized(array), Fixed(zero)],
+        _ => return None,
+    };
+
+    Some(args)
+}
+
+/// A xorshift generator, so that calibration runs see the same arguments.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// `words` machine words of random bytes, the first of which is never
+    /// zero so that integers read from them keep their size.
+    fn bytes(&mut self, words: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = (0..words).flat_map(|_| self.next().to_be_bytes()).collect();
+
+        if let Some(first) = bytes.first_mut() {
+            *first |= 1;
+        }
+
+        bytes
+    }
+}
+
+fn big_integer<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Integer {
+    arena.alloc_integer(Integer::from_bytes_be(Sign::Plus, &rng.bytes(size)))
+}
+
+fn integer<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    Constant::integer(arena, big_integer(arena, size, rng))
+}
+
+/// A small integer whose value, rather than its size, grows with `size`.
+fn literal<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    Constant::integer_from(arena, 8 * size as i128)
+}
+
+fn byte_string<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    let bytes = rng.bytes(size);
+
+    Constant::byte_string(arena, arena.alloc(bytes))
+}
+
+fn ascii(size: usize, rng: &mut Rng) -> String {
+    rng.bytes(size)
+        .into_iter()
+        .map(|byte| char::from(b'a' + byte % 26))
+        .collect()
+}
+
+fn string<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    let string = ascii(size, rng);
+
+    Constant::string(arena, arena.alloc(string))
+}
+
+fn utf8<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    let bytes = ascii(size, rng).into_bytes();
+
+    Constant::byte_string(arena, arena.alloc(bytes))
+}
+
+fn list<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    let items: Vec<_> = (0..size)
+        .map(|i| Constant::integer_from(arena, i as i128))
+        .collect();
+
+    Constant::proto_list(arena, Type::integer(arena), arena.alloc(items))
+}
+
+fn array<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    let items: Vec<_> = (0..size)
+        .map(|i| Constant::integer_from(arena, i as i128))
+        .collect();
+
+    Constant::proto_array(arena, Type::integer(arena), arena.alloc(items))
+}
+
+fn data_items(arena: &Arena, size: usize) -> &[&PlutusData<'_>] {
+    let items: Vec<_> = (0..size)
+        .map(|i| PlutusData::integer_from(arena, i as i128))
+        .collect();
+
+    arena.alloc(items)
+}
+
+fn data<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    Constant::data(arena, PlutusData::list(arena, data_items(arena, size)))
+}
+
+fn constr_data<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    Constant::data(arena, PlutusData::constr(arena, 0, data_items(arena, size)))
+}
+
+fn map_data<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    let items = data_items(arena, size);
+    let pairs: Vec<_> = items.iter().map(|item| (*item, *item)).collect();
+
+    Constant::data(arena, PlutusData::map(arena, arena.alloc(pairs)))
+}
+
+fn integer_data<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    let integer = big_integer(arena, size, rng);
+
+    Constant::data(arena, PlutusData::integer(arena, integer))
+}
+
+fn byte_string_data<'a>(arena: &'a Arena, size: usize, rng: &mut Rng) -> &'a Constant<'a> {
+    let bytes = rng.bytes(size);
+
+    Constant::data(arena, PlutusData::byte_string(arena, arena.alloc(bytes)))
+}
+
+fn data_list<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    let items: Vec<_> = data_items(arena, size)
+        .iter()
+        .map(|item| Constant::data(arena, item))
+        .collect();
+
+    Constant::proto_list(arena, Type::data(arena), arena.alloc(items))
+}
+
+fn data_pairs<'a>(arena: &'a Arena, size: usize, _: &mut Rng) -> &'a Constant<'a> {
+    let data = Type::data(arena);
+
+    let items: Vec<_> = data_items(arena, size)
+        .iter()
+        .map(|item| {
+            let item = Constant::data(arena, item);
+
+            Constant::proto_pair(arena, data, data, item, item)
+        })
+        .collect();
+
+    Constant::proto_list(arena, Type::pair(arena, data, data), arena.alloc(items))
+}
+
+fn zero(arena: &Arena) -> &Constant<'_> {
+    Constant::integer_from(arena, 0)
+}
+
+fn byte(arena: &Arena) -> &Constant<'_> {
+    Constant::integer_from(arena, 42)
+}
+
+fn true_(arena: &Arena) -> &Constant<'_> {
+    Constant::bool(arena, true)
+}
+
+fn false_(arena: &Arena) -> &Constant<'_> {
+    Constant::bool(arena, false)
+}
+
+fn unit(arena: &Arena) -> &Constant<'_> {
+    Constant::unit(arena)
+}
+
+fn pair(arena: &Arena) -> &Constant<'_> {
+    let integer = Type::integer(arena);
+
+    Constant::proto_pair(arena, integer, integer, zero(arena), zero(arena))
+}
+
+fn indices(arena: &Arena) -> &Constant<'_> {
+    Constant::proto_list(arena, Type::integer(arena), arena.alloc([zero(arena)]))
+}
+
+/// A costing function fitted to samples, of any arity.
+enum Fitted {
+    One(OneArgument),
+    Two(TwoArguments),
+    Three(ThreeArguments),
+    Six(SixArguments),
+}
+
+impl Fitted {
+    fn cost(&self, sizes: &[i64]) -> i64 {
+        let size = |i: usize| sizes.get(i).copied().unwrap_or_default();
+
+        match self {
+            Fitted::One(c) => c.cost([size(0)]),
+            Fitted::Two(c) => c.cost([size(0), size(1)]),
+            Fitted::Three(c) => c.cost([size(0), size(1), size(2)]),
+            Fitted::Six(c) => c.cost(std::array::from_fn(size)),
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        match self {
+            Fitted::One(c) => json::one_argument(c),
+            Fitted::Two(c) => json::two_arguments(c),
+            Fitted::Three(c) => json::three_arguments(c),
+            Fitted::Six(c) => json::six_arguments(c),
+        }
+    }
+}
+
+impl fmt::Display for Fitted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fitted::One(c) => c.fmt(f),
+            Fitted::Two(c) => c.fmt(f),
+            Fitted::Three(c) => c.fmt(f),
+            Fitted::Six(c) => c.fmt(f),
+        }
+    }
+}
+
+fn fit_one(shape: &OneArgument, samples: &[Sample]) -> Option<Fitted> {
+    let all = || samples.iter();
+
+    let fitted = match shape {
+        OneArgument::ConstantCost(_) => One::constant_cost(mean(all())?),
+        OneArgument::LinearCost(_) => {
+            let [intercept, slope] = regress(all(), |[x, ..]| [1.0, x])?;
+
+            One::linear_cost(intercept, slope)
+        }
+        OneArgument::Quadratic(_) => {
+            let [c0, c1, c2] = regress_signed(all(), |[x, ..]| [1.0, x, x * x])?;
+
+            One::quadratic_cost(c0, c1, c2)
+        }
+    };
+
+    Some(Fitted::One(fitted))
+}
+
+fn fit_two(shape: &TwoArguments, samples: &[Sample]) -> Option<Fitted> {
+    let all = || samples.iter();
+    let diagonal = || samples.iter().filter(|s| s.sizes[0] == s.sizes[1]);
+    let off_diagonal = || samples.iter().filter(|s| s.sizes[0] != s.sizes[1]);
+    let below = || samples.iter().filter(|s| s.sizes[0] < s.sizes[1]);
+    let above = || samples.iter().filter(|s| s.sizes[0] >= s.sizes[1]);
+
+    let linear = |basis: fn([f64; 3]) -> f64| regress(all(), move |xs| [1.0, basis(xs)]);
+
+    let fitted = match shape {
+        TwoArguments::ConstantCost(_) => Two::constant_cost(mean(all())?),
+        TwoArguments::LinearInX(_) => {
+            let [intercept, slope] = linear(|[x, _, _]| x)?;
+
+            Two::linear_in_x(intercept, slope)
+        }
+        TwoArguments::LinearInY(_) => {
+            let [intercept, slope] = linear(|[_, y, _]| y)?;
+
+            Two::linear_in_y(intercept, slope)
+        }
+        TwoArguments::AddedSizes(_) => {
+            let [intercept, slope] = linear(|[x, y, _]| x + y)?;
+
+            Two::added_sizes(intercept, slope)
+        }
+        TwoArguments::SubtractedSizes(s) => {
+            let minimum = s.minimum as f64;
+            let [intercept, slope] = regress(all(), |[x, y, _]| [1.0, minimum.max(x - y)])?;
+
+            Two::subtracted_sizes(intercept, slope, s.minimum)
+        }
+        TwoArguments::MultipliedSizes(_) => {
+            let [intercept, slope] = linear(|[x, y, _]| x * y)?;
+
+            Two::multiplied_sizes(intercept, slope)
+        }
+        TwoArguments::MinSize(_) => {
+            let [intercept, slope] = linear(|[x, y, _]| x.min(y))?;
+
+            Two::min_size(intercept, slope)
+        }
+        TwoArguments::MaxSize(_) => {
+            let [intercept, slope] = linear(|[x, y, _]| x.max(y))?;
+
+            Two::max_size(intercept, slope)
+        }
+        TwoArguments::LinearOnDiagonal(_) => {
+            let [intercept, slope] = regress(diagonal(), |[x, ..]| [1.0, x])?;
+
+            Two::linear_on_diagonal(mean(off_diagonal())?, intercept, slope)
+        }
+        TwoArguments::QuadraticInY(_) => {
+            let [c0, c1, c2] = regress_signed(all(), |[_, y, _]| [1.0, y, y * y])?;
+
+            Two::quadratic_in_y(c0, c1, c2)
+        }
+        TwoArguments::ConstAboveDiagonalIntoQuadraticXAndY(_, q) => {
+            let [c00, c10, c01, c20, c11, c02] =
+                regress_signed(above(), |[x, y, _]| [1.0, x, y, x * x, x * y, y * y])?;
+
+            Two::const_above_diagonal_into_quadratic_x_and_y(
+                mean(below())?,
+                q.minimum,
+                c00,
+                c01,
+                c02,
+                c10,
+                c11,
+                c20,
+            )
+        }
+        TwoArguments::ConstAboveDiagonalIntoMultipliedSizes(..) => {
+            let [intercept, slope] = regress(above(), |[x, y, _]| [1.0, x * y])?;
+
+            Two::const_above_diagonal_into_multiplied_sizes(mean(below())?, intercept, slope)
+        }
+        TwoArguments::WithInteraction(_) => {
+            let [c00, c10, c01, c11] = regress_signed(all(), |[x, y, _]| [1.0, x, y, x * y])?;
+
+            Two::with_interaction(c00, c10, c01, c11)
+        }
+    };
+
+    Some(Fitted::Two(fitted))
+}
+
+fn fit_three(shape: &ThreeArguments, samples: &[Sample]) -> Option<Fitted> {
+    let all = || samples.iter();
+
+    let linear = |basis: fn([f64; 3]) -> f64| regress(all(), move |xs| [1.0, basis(xs)]);
+
+    let fitted = match shape {
+        ThreeArguments::ConstantCost(_) => Three::constant_cost(mean(all())?),
+        ThreeArguments::LinearInX(_) => {
+            let [intercept, slope] = linear(|[x, _, _]| x)?;
+
+            Three::linear_in_x(intercept, slope)
+        }
+        ThreeArguments::LinearInY(_) => {
+            let [intercept, slope] = linear(|[_, y, _]| y)?;
+
+            Three::linear_in_y(intercept, slope)
+        }
+        ThreeArguments::LinearInZ(_) => {
+            let [intercept, slope] = linear(|[_, _, z]| z)?;
+
+            Three::linear_in_z(intercept, slope)
+        }
+        ThreeArguments::QuadraticInZ(_) => {
+            let [c0, c1, c2] = regress_signed(all(), |[_, _, z]| [1.0, z, z * z])?;
+
+            Three::quadratic_in_z(c0, c1, c2)
+        }
+        // When `y` is not zero it is the cost itself, so only the other
+        // samples say anything about the coefficients.
+        ThreeArguments::LiteralInYorLinearInZ(_) => {
+            let [intercept, slope] =
+                regress(samples.iter().filter(|s| s.sizes[1] == 0), |[_, _, z]| {
+                    [1.0, z]
+                })?;
+
+            Three::literal_in_y_or_linear_in_z(intercept, slope)
+        }
+        ThreeArguments::LinearInYAndZ(_) => {
+            let [intercept, slope1, slope2] = regress(all(), |[_, y, z]| [1.0, y, z])?;
+
+            Three::linear_in_y_and_z(intercept, slope1, slope2)
+        }
+        ThreeArguments::LinearInMaxYZ(_) => {
+            let [intercept, slope] = linear(|[_, y, z]| y.max(z))?;
+
+            Three::linear_in_max_y_z(intercept, slope)
+        }
+        // Bases larger than the modulus are charged half as much again, so
+        // scale those samples back before fitting.
+        ThreeArguments::ExpModCost(_) => {
+            let scaled: Vec<Sample> = samples
+                .iter()
+                .map(|s| Sample {
+                    measured: if s.sizes[0] <= s.sizes[2] {
+                        s.measured
+                    } else {
+                        s.measured * 2 / 3
+                    },
+                    ..s.clone()
+                })
+                .collect();
+
+            let [c00, c11, c12] = regress(scaled.iter(), |[_, y, z]| [1.0, y * z, y * z * z])?;
+
+            Three::exp_mod_cost(c00, c11, c12)
+        }
+    };
+
+    Some(Fitted::Three(fitted))
+}
+
+fn fit_six(samples: &[Sample]) -> Option<Fitted> {
+    Some(Fitted::Six(Six::constant_cost(mean(samples.iter())?)))
+}
+
+fn mean<'s>(samples: impl Iterator<Item = &'s Sample>) -> Option<i64> {
+    let [mean] = regress(samples, |_| [1.0])?;
+
+    Some(mean)
+}
+
+/// Least squares fit of the measurements to `basis`, which maps the sizes
+/// of a sample to the terms the coefficients multiply. Fails when the
+/// samples do not determine every coefficient.
+///
+/// Cost models only take negative values for the `c*` coefficients of
+/// polynomials, so every other coefficient that comes out negative is
+/// pinned to zero and the rest fitted again, like Plutus' own fits do.
+fn regress<'s, const N: usize>(
+    samples: impl Iterator<Item = &'s Sample>,
+    basis: impl Fn([f64; 3]) -> [f64; N],
+) -> Option<[i64; N]> {
+    let samples: Vec<_> = samples.collect();
+    let mut free = [true; N];
+
+    loop {
+        let solution = solve(&samples, &basis, free)?;
+
+        let negative = (0..N)
+            .filter(|i| solution[*i] < 0.0)
+            .min_by(|a, b| solution[*a].total_cmp(&solution[*b]));
+
+        match negative {
+            Some(i) => free[i] = false,
+            None => return Some(solution.map(|coefficient| coefficient.round() as i64)),
+        }
+    }
+}
+
+/// Like [`regress`], for coefficients that may be negative.
+fn regress_signed<'s, const N: usize>(
+    samples: impl Iterator<Item = &'s Sample>,
+    basis: impl Fn([f64; 3]) -> [f64; N],
+) -> Option<[i64; N]> {
+    let samples: Vec<_> = samples.collect();
+
+    let solution = solve(&samples, &basis, [true; N])?;
+
+    Some(solution.map(|coefficient| coefficient.round() as i64))
+}
+
+/// Solve the normal equations for the `free` coefficients, holding the
+/// others at zero.
+fn solve<const N: usize>(
+    samples: &[&Sample],
+    basis: impl Fn([f64; 3]) -> [f64; N],
+    free: [bool; N],
+) -> Option<[f64; N]> {
+    // Every row pairs the left-hand side with the right-hand side.
+    let mut system = [[0.0f64; N]; N].map(|row| (row, 0.0f64));
+
+    for sample in samples {
+        let size = |i: usize| sample.sizes.get(i).copied().unwrap_or_default() as f64;
+        let terms = basis([size(0), size(1), size(2)]);
+        let terms: [f64; N] = std::array::from_fn(|i| if free[i] { terms[i] } else { 0.0 });
+
+        for (i, (row, rhs)) in system.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell += terms[i] * terms[j];
+            }
+
+            *rhs += terms[i] * sample.measured as f64;
+        }
+    }
+
+    for (i, (row, _)) in system.iter_mut().enumerate() {
+        if !free[i] {
+            row[i] = 1.0;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for column in 0..N {
+        let pivot = (column..N).max_by(|a, b| {
+            system[*a].0[column]
+                .abs()
+                .total_cmp(&system[*b].0[column].abs())
+        })?;
+
+        if system[pivot].0[column].abs() < 1e-9 {
+            return None;
+        }
+
+        system.swap(column, pivot);
+
+        for row in column + 1..N {
+            let factor = system[row].0[column] / system[column].0[column];
+
+            for j in column..N {
+                system[row].0[j] -= factor * system[column].0[j];
+            }
+
+            system[row].1 -= factor * system[column].1;
+        }
+    }
+
+    let mut solution = [0.0f64; N];
+
+    for row in (0..N).rev() {
+        let known: f64 = (row + 1..N).map(|j| system[row].0[j] * solution[j]).sum();
+
+        solution[row] = (system[row].1 - known) / system[row].0[row];
+    }
+
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builtin::DefaultFunction,
+        machine::{
+            cost_model::builtin_costs::builtin_costs_v3::BuiltinCostsV3, CostModel, PlutusVersion,
+        },
+    };
+
+    use super::{fit_three, fit_two, CalibrationConfig, Sample, Three, Two};
+
+    fn samples(sizes: &[[i64; 3]], cost: impl Fn(i64, i64, i64) -> i64) -> Vec<Sample> {
+        sizes
+            .iter()
+            .map(|&[x, y, z]| Sample {
+                sizes: vec![x, y, z],
+                measured: cost(x, y, z),
+                model: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_exact_shapes() {
+        let grid: Vec<[i64; 3]> = [1, 2, 4, 8]
+            .iter()
+            .flat_map(|x| [1, 3, 9].map(|y| [*x, y, x + y]))
+            .collect();
+
+        let added = samples(&grid, |x, y, _| 1000 + 7 * (x + y));
+        let fitted = fit_two(&Two::added_sizes(0, 0), &added).unwrap();
+
+        assert_eq!(fitted.to_string(), "1000 + 7 * (x + y)");
+
+        let interaction = samples(&grid, |x, y, _| 5 - 2 * x + 3 * y + x * y);
+        let fitted = fit_two(&Two::with_interaction(0, 0, 0, 0), &interaction).unwrap();
+
+        assert_eq!(fitted.to_string(), "5 - 2 * x + 3 * y + x * y");
+
+        let diagonal = samples(&[[1, 1, 0], [4, 4, 0], [1, 4, 0], [4, 1, 0]], |x, y, _| {
+            if x == y {
+                10 + 2 * x
+            } else {
+                99
+            }
+        });
+        let fitted = fit_two(&Two::linear_on_diagonal(0, 0, 0), &diagonal).unwrap();
+
+        assert_eq!(fitted.to_string(), "if x == y then 10 + 2 * x else 99");
+
+        let linear = samples(&grid, |_, y, z| 40 + 3 * y + 5 * z);
+        let fitted = fit_three(&Three::linear_in_y_and_z(0, 0, 0), &linear).unwrap();
+
+        assert_eq!(fitted.to_string(), "40 + 3 * y + 5 * z");
+        assert_eq!(fitted.cost(&[0, 2, 3]), 40 + 6 + 15);
+    }
+
+    #[test]
+    fn pins_negative_slopes_to_zero() {
+        let shrinking = samples(&[[1, 0, 0], [2, 0, 0], [3, 0, 0]], |x, _, _| 100 - x);
+        let fitted = fit_two(&Two::linear_in_x(0, 0), &shrinking).unwrap();
+
+        assert_eq!(fitted.to_string(), "98");
+
+        let fitted = fit_two(&Two::with_interaction(0, 0, 0, 0), &shrinking);
+
+        assert!(fitted.is_none());
+
+        // Polynomial coefficients may stay negative.
+        let shrinking = samples(&[[0, 0, 1], [0, 0, 2], [0, 0, 3]], |_, _, z| 100 - z * z);
+        let fitted = fit_three(&Three::quadratic_in_z(0, 0, 0), &shrinking).unwrap();
+
+        assert_eq!(fitted.to_string(), "100 - z^2");
+    }
+
+    #[test]
+    fn needs_enough_distinct_sizes() {
+        let flat = samples(&[[4, 4, 0], [4, 4, 0]], |_, _, _| 12);
+
+        assert!(fit_two(&Two::max_size(0, 0), &flat).is_none());
+        assert!(fit_two(&Two::constant_cost(0), &flat).is_some());
+    }
+
+    /// Runs the builtins for real, so the fitted parameters depend on the
+    /// machine. Run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore = "measures wall-clock timings"]
+    fn calibrates_builtins() {
+        let config = CalibrationConfig::new(PlutusVersion::V3)
+            .with_sizes(vec![1, 2, 4])
+            .with_repetitions(1)
+            .with_builtins(vec![
+                DefaultFunction::AddInteger,
+                DefaultFunction::Sha2_256,
+                DefaultFunction::VerifyEd25519Signature,
+            ]);
+
+        let report = config.calibrate().unwrap();
+
+        assert_eq!(
+            report.skipped,
+            vec![DefaultFunction::VerifyEd25519Signature]
+        );
+        assert_eq!(report.builtins.len(), 2);
+
+        let add = &report.builtins[0];
+
+        assert_eq!(add.builtin, DefaultFunction::AddInteger);
+        assert_eq!(add.samples.len(), 9);
+        assert_eq!(add.samples[5].sizes, vec![2, 4]);
+        assert!(add.candidate.is_some());
+        assert_eq!(add.residuals.len(), 9);
+
+        let sha = &report.builtins[1];
+
+        assert_eq!(sha.samples.len(), 3);
+        assert_eq!(sha.samples[2].sizes, vec![4]);
+
+        // Builtins left alone keep their parameters.
+        let default = CostModel::<BuiltinCostsV3>::default().to_named_params();
+
+        assert_eq!(report.params.len(), default.len());
+
+        let param = |params: &[(&str, i64)], name: &str| {
+            params.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+        };
+
+        for name in [
+            "blake2b_256-cpu-arguments-slope",
+            "add_integer-mem-arguments-slope",
+        ] {
+            assert_eq!(param(&report.params, name), param(&default, name));
+        }
+
+        CostModel::<BuiltinCostsV3>::from_named_params(&PlutusVersion::V3, report.params).unwrap();
+    }
+}
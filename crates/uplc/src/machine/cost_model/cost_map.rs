@@ -3,14 +3,365 @@ use std::ops::Index;
 
 use crate::machine::PlutusVersion;
 
+use super::CostModelError;
+
+/// The value of every parameter that a cost model from an older protocol
+/// version does not provide yet. It exceeds the per-transaction execution
+/// budget, so the builtins that depend on it stay unusable until the ledger
+/// sets real costs for them.
+pub const MISSING_PARAMETER_COST: i64 = 30_000_000_000;
+
 pub struct CostMap {
     map: HashMap<String, i64>,
 }
 
 impl CostMap {
-    pub fn new(version: &PlutusVersion, values: &[i64]) -> Self {
-        let keys = match version {
-            PlutusVersion::V1 => vec![
+    /// Build a cost map from the positional parameters of `version`.
+    ///
+    /// Besides the full array, only the shorter arrays that mainnet used
+    /// before later builtins were costed are accepted: 175 entries for V2
+    /// and 251 for V3 (both before Plomin). The missing parameters are set to
+    /// [`MISSING_PARAMETER_COST`].
+    pub fn new(version: &PlutusVersion, values: &[i64]) -> Result<Self, CostModelError> {
+        let names = parameter_names(version, usize::MAX);
+        let required = parameter_names(version, 0).len();
+
+        if values.len() > names.len() {
+            return Err(CostModelError::ExtraParameters {
+                version: *version,
+                expected: names.len(),
+                given: values.len(),
+            });
+        }
+
+        if values.len() != required && values.len() != names.len() {
+            let end = if values.len() < required {
+                required
+            } else {
+                names.len()
+            };
+
+            return Err(CostModelError::MissingParameters {
+                version: *version,
+                names: names[values.len()..end]
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            });
+        }
+
+        let map = cost_map_from_keys_and_values(&names, values);
+
+        Self::complete(version, map)
+    }
+
+    /// Build a cost map from named parameters, as found in the ledger's
+    /// protocol parameters.
+    ///
+    /// Names are matched regardless of spelling convention, so both
+    /// `addInteger-memory-arguments-slope` and our own
+    /// `add_integer-mem-arguments-slope` are accepted. Every parameter of
+    /// `version` must be given, except that a block of builtins costed in a
+    /// later protocol version may be left out as a whole.
+    pub fn from_named<'k>(
+        version: &PlutusVersion,
+        params: impl IntoIterator<Item = (&'k str, i64)>,
+    ) -> Result<Self, CostModelError> {
+        let names = parameter_names(version, usize::MAX);
+        let required = parameter_names(version, 0).len();
+
+        let known: HashMap<String, &str> =
+            names.iter().map(|name| (normalise(name), *name)).collect();
+
+        let mut map = HashMap::new();
+
+        for (name, value) in params {
+            let Some(key) = known.get(&normalise(name)) else {
+                return Err(CostModelError::UnknownParameter {
+                    version: *version,
+                    name: name.to_string(),
+                });
+            };
+
+            if map.insert(key.to_string(), value).is_some() {
+                return Err(CostModelError::DuplicateParameter(name.to_string()));
+            }
+        }
+
+        let extended = names[required..].iter().any(|name| map.contains_key(*name));
+
+        let expected = if extended {
+            &names[..]
+        } else {
+            &names[..required]
+        };
+
+        let missing: Vec<String> = expected
+            .iter()
+            .filter(|name| !map.contains_key(**name))
+            .map(|name| name.to_string())
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(CostModelError::MissingParameters {
+                version: *version,
+                names: missing,
+            });
+        }
+
+        Self::complete(version, map)
+    }
+
+    /// Check the range of every given parameter, then fill in the ones that
+    /// `version` does not take (yet).
+    fn complete(
+        version: &PlutusVersion,
+        mut map: HashMap<String, i64>,
+    ) -> Result<Self, CostModelError> {
+        for (name, value) in &map {
+            if *value < 0 && !is_coefficient(name) {
+                return Err(CostModelError::OutOfRange {
+                    name: name.clone(),
+                    value: *value,
+                });
+            }
+        }
+
+        let implied = match version {
+            // Terms using constr and case do not deserialise before V3.
+            PlutusVersion::V1 | PlutusVersion::V2 => &[
+                "cek_constr_cost-exBudgetCPU",
+                "cek_constr_cost-exBudgetmem",
+                "cek_case_cost-exBudgetCPU",
+                "cek_case_cost-exBudgetmem",
+            ][..],
+            PlutusVersion::V3 => &[],
+        };
+
+        for name in parameter_names(version, usize::MAX)
+            .into_iter()
+            .chain(implied.iter().copied())
+        {
+            map.entry(name.to_string())
+                .or_insert(MISSING_PARAMETER_COST);
+        }
+
+        Ok(Self { map })
+    }
+}
+
+/// Polynomial coefficients such as `c01` are the only parameters that may be
+/// negative.
+fn is_coefficient(name: &str) -> bool {
+    name.rsplit('-').next().is_some_and(|last| {
+        last.len() > 1 && last.starts_with('c') && last[1..].bytes().all(|b| b.is_ascii_digit())
+    })
+}
+
+/// Whether any parameter of `version` belongs to `entry`, a builtin or
+/// machine step name in either spelling convention.
+pub(crate) fn has_entry(version: &PlutusVersion, entry: &str) -> bool {
+    let prefix = format!("{}-", normalise(entry));
+
+    parameter_names(version, usize::MAX)
+        .iter()
+        .any(|name| normalise(name).starts_with(&prefix))
+}
+
+/// Reduce a parameter name to a form shared by the ledger's camelCase names,
+/// the Plutus JSON paths and the snake_case names used here.
+pub(crate) fn normalise(name: &str) -> String {
+    let name = name
+        .to_ascii_lowercase()
+        .replace('_', "")
+        .replace("memory", "mem")
+        .replace("-model-arguments", "");
+
+    match name.as_str() {
+        // The constant memory cost of blake2b_224 is named like a slope.
+        "blake2b224-mem-arguments-slope" => "blake2b224-mem-arguments".to_string(),
+        // The original name of verifyEd25519Signature in Alonzo.
+        _ => match name.strip_prefix("verifysignature-") {
+            Some(rest) => format!("verifyed25519signature-{rest}"),
+            None => name,
+        },
+    }
+}
+
+/// The positional parameter names of `version` for a cost model array of
+/// `len` entries.
+pub(crate) fn parameter_names(version: &PlutusVersion, len: usize) -> Vec<&'static str> {
+    match version {
+        PlutusVersion::V1 => vec![
+            "add_integer-cpu-arguments-intercept",
+            "add_integer-cpu-arguments-slope",
+            "add_integer-mem-arguments-intercept",
+            "add_integer-mem-arguments-slope",
+            "append_byte_string-cpu-arguments-intercept",
+            "append_byte_string-cpu-arguments-slope",
+            "append_byte_string-mem-arguments-intercept",
+            "append_byte_string-mem-arguments-slope",
+            "append_string-cpu-arguments-intercept",
+            "append_string-cpu-arguments-slope",
+            "append_string-mem-arguments-intercept",
+            "append_string-mem-arguments-slope",
+            "b_data-cpu-arguments",
+            "b_data-mem-arguments",
+            "blake2b_256-cpu-arguments-intercept",
+            "blake2b_256-cpu-arguments-slope",
+            "blake2b_256-mem-arguments",
+            "cek_apply_cost-exBudgetCPU",
+            "cek_apply_cost-exBudgetmem",
+            "cek_builtin_cost-exBudgetCPU",
+            "cek_builtin_cost-exBudgetmem",
+            "cek_const_cost-exBudgetCPU",
+            "cek_const_cost-exBudgetmem",
+            "cek_delay_cost-exBudgetCPU",
+            "cek_delay_cost-exBudgetmem",
+            "cek_force_cost-exBudgetCPU",
+            "cek_force_cost-exBudgetmem",
+            "cek_lam_cost-exBudgetCPU",
+            "cek_lam_cost-exBudgetmem",
+            "cek_startup_cost-exBudgetCPU",
+            "cek_startup_cost-exBudgetmem",
+            "cek_var_cost-exBudgetCPU",
+            "cek_var_cost-exBudgetmem",
+            "choose_data-cpu-arguments",
+            "choose_data-mem-arguments",
+            "choose_list-cpu-arguments",
+            "choose_list-mem-arguments",
+            "choose_unit-cpu-arguments",
+            "choose_unit-mem-arguments",
+            "cons_byte_string-cpu-arguments-intercept",
+            "cons_byte_string-cpu-arguments-slope",
+            "cons_byte_string-mem-arguments-intercept",
+            "cons_byte_string-mem-arguments-slope",
+            "constr_data-cpu-arguments",
+            "constr_data-mem-arguments",
+            "decode_utf8-cpu-arguments-intercept",
+            "decode_utf8-cpu-arguments-slope",
+            "decode_utf8-mem-arguments-intercept",
+            "decode_utf8-mem-arguments-slope",
+            "divide_integer-cpu-arguments-constant",
+            "divide_integer-cpu-arguments-model-arguments-intercept",
+            "divide_integer-cpu-arguments-model-arguments-slope",
+            "divide_integer-mem-arguments-intercept",
+            "divide_integer-mem-arguments-minimum",
+            "divide_integer-mem-arguments-slope",
+            "encode_utf8-cpu-arguments-intercept",
+            "encode_utf8-cpu-arguments-slope",
+            "encode_utf8-mem-arguments-intercept",
+            "encode_utf8-mem-arguments-slope",
+            "equals_byte_string-cpu-arguments-constant",
+            "equals_byte_string-cpu-arguments-intercept",
+            "equals_byte_string-cpu-arguments-slope",
+            "equals_byte_string-mem-arguments",
+            "equals_data-cpu-arguments-intercept",
+            "equals_data-cpu-arguments-slope",
+            "equals_data-mem-arguments",
+            "equals_integer-cpu-arguments-intercept",
+            "equals_integer-cpu-arguments-slope",
+            "equals_integer-mem-arguments",
+            "equals_string-cpu-arguments-constant",
+            "equals_string-cpu-arguments-intercept",
+            "equals_string-cpu-arguments-slope",
+            "equals_string-mem-arguments",
+            "fst_pair-cpu-arguments",
+            "fst_pair-mem-arguments",
+            "head_list-cpu-arguments",
+            "head_list-mem-arguments",
+            "i_data-cpu-arguments",
+            "i_data-mem-arguments",
+            "if_then_else-cpu-arguments",
+            "if_then_else-mem-arguments",
+            "index_byte_string-cpu-arguments",
+            "index_byte_string-mem-arguments",
+            "length_of_byte_string-cpu-arguments",
+            "length_of_byte_string-mem-arguments",
+            "less_than_byte_string-cpu-arguments-intercept",
+            "less_than_byte_string-cpu-arguments-slope",
+            "less_than_byte_string-mem-arguments",
+            "less_than_equals_byte_string-cpu-arguments-intercept",
+            "less_than_equals_byte_string-cpu-arguments-slope",
+            "less_than_equals_byte_string-mem-arguments",
+            "less_than_equals_integer-cpu-arguments-intercept",
+            "less_than_equals_integer-cpu-arguments-slope",
+            "less_than_equals_integer-mem-arguments",
+            "less_than_integer-cpu-arguments-intercept",
+            "less_than_integer-cpu-arguments-slope",
+            "less_than_integer-mem-arguments",
+            "list_data-cpu-arguments",
+            "list_data-mem-arguments",
+            "map_data-cpu-arguments",
+            "map_data-mem-arguments",
+            "mk_cons-cpu-arguments",
+            "mk_cons-mem-arguments",
+            "mk_nil_data-cpu-arguments",
+            "mk_nil_data-mem-arguments",
+            "mk_nil_pair_data-cpu-arguments",
+            "mk_nil_pair_data-mem-arguments",
+            "mk_pair_data-cpu-arguments",
+            "mk_pair_data-mem-arguments",
+            "mod_integer-cpu-arguments-constant",
+            "mod_integer-cpu-arguments-model-arguments-intercept",
+            "mod_integer-cpu-arguments-model-arguments-slope",
+            "mod_integer-mem-arguments-intercept",
+            "mod_integer-mem-arguments-minimum",
+            "mod_integer-mem-arguments-slope",
+            "multiply_integer-cpu-arguments-intercept",
+            "multiply_integer-cpu-arguments-slope",
+            "multiply_integer-mem-arguments-intercept",
+            "multiply_integer-mem-arguments-slope",
+            "null_list-cpu-arguments",
+            "null_list-mem-arguments",
+            "quotient_integer-cpu-arguments-constant",
+            "quotient_integer-cpu-arguments-model-arguments-intercept",
+            "quotient_integer-cpu-arguments-model-arguments-slope",
+            "quotient_integer-mem-arguments-intercept",
+            "quotient_integer-mem-arguments-minimum",
+            "quotient_integer-mem-arguments-slope",
+            "remainder_integer-cpu-arguments-constant",
+            "remainder_integer-cpu-arguments-model-arguments-intercept",
+            "remainder_integer-cpu-arguments-model-arguments-slope",
+            "remainder_integer-mem-arguments-intercept",
+            "remainder_integer-mem-arguments-minimum",
+            "remainder_integer-mem-arguments-slope",
+            "sha2_256-cpu-arguments-intercept",
+            "sha2_256-cpu-arguments-slope",
+            "sha2_256-mem-arguments",
+            "sha3_256-cpu-arguments-intercept",
+            "sha3_256-cpu-arguments-slope",
+            "sha3_256-mem-arguments",
+            "slice_byte_string-cpu-arguments-intercept",
+            "slice_byte_string-cpu-arguments-slope",
+            "slice_byte_string-mem-arguments-intercept",
+            "slice_byte_string-mem-arguments-slope",
+            "snd_pair-cpu-arguments",
+            "snd_pair-mem-arguments",
+            "subtract_integer-cpu-arguments-intercept",
+            "subtract_integer-cpu-arguments-slope",
+            "subtract_integer-mem-arguments-intercept",
+            "subtract_integer-mem-arguments-slope",
+            "tail_list-cpu-arguments",
+            "tail_list-mem-arguments",
+            "trace-cpu-arguments",
+            "trace-mem-arguments",
+            "un_b_data-cpu-arguments",
+            "un_b_data-mem-arguments",
+            "un_constr_data-cpu-arguments",
+            "un_constr_data-mem-arguments",
+            "un_i_data-cpu-arguments",
+            "un_i_data-mem-arguments",
+            "un_list_data-cpu-arguments",
+            "un_list_data-mem-arguments",
+            "un_map_data-cpu-arguments",
+            "un_map_data-mem-arguments",
+            "verify_ed25519_signature-cpu-arguments-intercept",
+            "verify_ed25519_signature-cpu-arguments-slope",
+            "verify_ed25519_signature-mem-arguments",
+        ],
+        PlutusVersion::V2 => {
+            let mut base_keys = vec![
                 "add_integer-cpu-arguments-intercept",
                 "add_integer-cpu-arguments-slope",
                 "add_integer-mem-arguments-intercept",
@@ -144,6 +495,10 @@ impl CostMap {
                 "remainder_integer-mem-arguments-intercept",
                 "remainder_integer-mem-arguments-minimum",
                 "remainder_integer-mem-arguments-slope",
+                "serialise_data-cpu-arguments-intercept",
+                "serialise_data-cpu-arguments-slope",
+                "serialise_data-mem-arguments-intercept",
+                "serialise_data-mem-arguments-slope",
                 "sha2_256-cpu-arguments-intercept",
                 "sha2_256-cpu-arguments-slope",
                 "sha2_256-mem-arguments",
@@ -174,11 +529,35 @@ impl CostMap {
                 "un_list_data-mem-arguments",
                 "un_map_data-cpu-arguments",
                 "un_map_data-mem-arguments",
+                "verify_ecdsa_secp256k1_signature-cpu-arguments",
+                "verify_ecdsa_secp256k1_signature-mem-arguments",
                 "verify_ed25519_signature-cpu-arguments-intercept",
                 "verify_ed25519_signature-cpu-arguments-slope",
                 "verify_ed25519_signature-mem-arguments",
-            ],
-            PlutusVersion::V2 => vec![
+                "verify_schnorr_secp256k1_signature-cpu-arguments-intercept",
+                "verify_schnorr_secp256k1_signature-cpu-arguments-slope",
+                "verify_schnorr_secp256k1_signature-mem-arguments",
+            ];
+
+            // Plomin (PV 10) appended the integer/bytestring conversions.
+            if len >= 185 {
+                base_keys.extend([
+                    "integerToByteString-cpu-arguments-c0",
+                    "integerToByteString-cpu-arguments-c1",
+                    "integerToByteString-cpu-arguments-c2",
+                    "integerToByteString-mem-arguments-intercept",
+                    "integerToByteString-mem-arguments-slope",
+                    "byteStringToInteger-cpu-arguments-c0",
+                    "byteStringToInteger-cpu-arguments-c1",
+                    "byteStringToInteger-cpu-arguments-c2",
+                    "byteStringToInteger-mem-arguments-intercept",
+                    "byteStringToInteger-mem-arguments-slope",
+                ]);
+            }
+            base_keys
+        }
+        PlutusVersion::V3 => {
+            let mut base_keys = vec![
                 "add_integer-cpu-arguments-intercept",
                 "add_integer-cpu-arguments-slope",
                 "add_integer-mem-arguments-intercept",
@@ -229,8 +608,13 @@ impl CostMap {
                 "decode_utf8-mem-arguments-intercept",
                 "decode_utf8-mem-arguments-slope",
                 "divide_integer-cpu-arguments-constant",
-                "divide_integer-cpu-arguments-model-arguments-intercept",
-                "divide_integer-cpu-arguments-model-arguments-slope",
+                "divide_integer-cpu-arguments-c00",
+                "divide_integer-cpu-arguments-c01",
+                "divide_integer-cpu-arguments-c02",
+                "divide_integer-cpu-arguments-c10",
+                "divide_integer-cpu-arguments-c11",
+                "divide_integer-cpu-arguments-c20",
+                "divide_integer-cpu-arguments-minimum",
                 "divide_integer-mem-arguments-intercept",
                 "divide_integer-mem-arguments-minimum",
                 "divide_integer-mem-arguments-slope",
@@ -289,10 +673,14 @@ impl CostMap {
                 "mk_pair_data-cpu-arguments",
                 "mk_pair_data-mem-arguments",
                 "mod_integer-cpu-arguments-constant",
-                "mod_integer-cpu-arguments-model-arguments-intercept",
-                "mod_integer-cpu-arguments-model-arguments-slope",
+                "mod_integer-cpu-arguments-c00",
+                "mod_integer-cpu-arguments-c01",
+                "mod_integer-cpu-arguments-c02",
+                "mod_integer-cpu-arguments-c10",
+                "mod_integer-cpu-arguments-c11",
+                "mod_integer-cpu-arguments-c20",
+                "mod_integer-cpu-arguments-minimum",
                 "mod_integer-mem-arguments-intercept",
-                "mod_integer-mem-arguments-minimum",
                 "mod_integer-mem-arguments-slope",
                 "multiply_integer-cpu-arguments-intercept",
                 "multiply_integer-cpu-arguments-slope",
@@ -301,16 +689,25 @@ impl CostMap {
                 "null_list-cpu-arguments",
                 "null_list-mem-arguments",
                 "quotient_integer-cpu-arguments-constant",
-                "quotient_integer-cpu-arguments-model-arguments-intercept",
-                "quotient_integer-cpu-arguments-model-arguments-slope",
+                "quotient_integer-cpu-arguments-c00",
+                "quotient_integer-cpu-arguments-c01",
+                "quotient_integer-cpu-arguments-c02",
+                "quotient_integer-cpu-arguments-c10",
+                "quotient_integer-cpu-arguments-c11",
+                "quotient_integer-cpu-arguments-c20",
+                "quotient_integer-cpu-arguments-minimum",
                 "quotient_integer-mem-arguments-intercept",
                 "quotient_integer-mem-arguments-minimum",
                 "quotient_integer-mem-arguments-slope",
                 "remainder_integer-cpu-arguments-constant",
-                "remainder_integer-cpu-arguments-model-arguments-intercept",
-                "remainder_integer-cpu-arguments-model-arguments-slope",
+                "remainder_integer-cpu-arguments-c00",
+                "remainder_integer-cpu-arguments-c01",
+                "remainder_integer-cpu-arguments-c02",
+                "remainder_integer-cpu-arguments-c10",
+                "remainder_integer-cpu-arguments-c11",
+                "remainder_integer-cpu-arguments-c20",
+                "remainder_integer-cpu-arguments-minimum",
                 "remainder_integer-mem-arguments-intercept",
-                "remainder_integer-mem-arguments-minimum",
                 "remainder_integer-mem-arguments-slope",
                 "serialise_data-cpu-arguments-intercept",
                 "serialise_data-cpu-arguments-slope",
@@ -354,322 +751,119 @@ impl CostMap {
                 "verify_schnorr_secp256k1_signature-cpu-arguments-intercept",
                 "verify_schnorr_secp256k1_signature-cpu-arguments-slope",
                 "verify_schnorr_secp256k1_signature-mem-arguments",
-            ],
-            PlutusVersion::V3 => {
-                let mut base_keys = vec![
-                    "add_integer-cpu-arguments-intercept",
-                    "add_integer-cpu-arguments-slope",
-                    "add_integer-mem-arguments-intercept",
-                    "add_integer-mem-arguments-slope",
-                    "append_byte_string-cpu-arguments-intercept",
-                    "append_byte_string-cpu-arguments-slope",
-                    "append_byte_string-mem-arguments-intercept",
-                    "append_byte_string-mem-arguments-slope",
-                    "append_string-cpu-arguments-intercept",
-                    "append_string-cpu-arguments-slope",
-                    "append_string-mem-arguments-intercept",
-                    "append_string-mem-arguments-slope",
-                    "b_data-cpu-arguments",
-                    "b_data-mem-arguments",
-                    "blake2b_256-cpu-arguments-intercept",
-                    "blake2b_256-cpu-arguments-slope",
-                    "blake2b_256-mem-arguments",
-                    "cek_apply_cost-exBudgetCPU",
-                    "cek_apply_cost-exBudgetmem",
-                    "cek_builtin_cost-exBudgetCPU",
-                    "cek_builtin_cost-exBudgetmem",
-                    "cek_const_cost-exBudgetCPU",
-                    "cek_const_cost-exBudgetmem",
-                    "cek_delay_cost-exBudgetCPU",
-                    "cek_delay_cost-exBudgetmem",
-                    "cek_force_cost-exBudgetCPU",
-                    "cek_force_cost-exBudgetmem",
-                    "cek_lam_cost-exBudgetCPU",
-                    "cek_lam_cost-exBudgetmem",
-                    "cek_startup_cost-exBudgetCPU",
-                    "cek_startup_cost-exBudgetmem",
-                    "cek_var_cost-exBudgetCPU",
-                    "cek_var_cost-exBudgetmem",
-                    "choose_data-cpu-arguments",
-                    "choose_data-mem-arguments",
-                    "choose_list-cpu-arguments",
-                    "choose_list-mem-arguments",
-                    "choose_unit-cpu-arguments",
-                    "choose_unit-mem-arguments",
-                    "cons_byte_string-cpu-arguments-intercept",
-                    "cons_byte_string-cpu-arguments-slope",
-                    "cons_byte_string-mem-arguments-intercept",
-                    "cons_byte_string-mem-arguments-slope",
-                    "constr_data-cpu-arguments",
-                    "constr_data-mem-arguments",
-                    "decode_utf8-cpu-arguments-intercept",
-                    "decode_utf8-cpu-arguments-slope",
-                    "decode_utf8-mem-arguments-intercept",
-                    "decode_utf8-mem-arguments-slope",
-                    "divide_integer-cpu-arguments-constant",
-                    "divide_integer-cpu-arguments-c00",
-                    "divide_integer-cpu-arguments-c01",
-                    "divide_integer-cpu-arguments-c02",
-                    "divide_integer-cpu-arguments-c10",
-                    "divide_integer-cpu-arguments-c11",
-                    "divide_integer-cpu-arguments-c20",
-                    "divide_integer-cpu-arguments-minimum",
-                    "divide_integer-mem-arguments-intercept",
-                    "divide_integer-mem-arguments-minimum",
-                    "divide_integer-mem-arguments-slope",
-                    "encode_utf8-cpu-arguments-intercept",
-                    "encode_utf8-cpu-arguments-slope",
-                    "encode_utf8-mem-arguments-intercept",
-                    "encode_utf8-mem-arguments-slope",
-                    "equals_byte_string-cpu-arguments-constant",
-                    "equals_byte_string-cpu-arguments-intercept",
-                    "equals_byte_string-cpu-arguments-slope",
-                    "equals_byte_string-mem-arguments",
-                    "equals_data-cpu-arguments-intercept",
-                    "equals_data-cpu-arguments-slope",
-                    "equals_data-mem-arguments",
-                    "equals_integer-cpu-arguments-intercept",
-                    "equals_integer-cpu-arguments-slope",
-                    "equals_integer-mem-arguments",
-                    "equals_string-cpu-arguments-constant",
-                    "equals_string-cpu-arguments-intercept",
-                    "equals_string-cpu-arguments-slope",
-                    "equals_string-mem-arguments",
-                    "fst_pair-cpu-arguments",
-                    "fst_pair-mem-arguments",
-                    "head_list-cpu-arguments",
-                    "head_list-mem-arguments",
-                    "i_data-cpu-arguments",
-                    "i_data-mem-arguments",
-                    "if_then_else-cpu-arguments",
-                    "if_then_else-mem-arguments",
-                    "index_byte_string-cpu-arguments",
-                    "index_byte_string-mem-arguments",
-                    "length_of_byte_string-cpu-arguments",
-                    "length_of_byte_string-mem-arguments",
-                    "less_than_byte_string-cpu-arguments-intercept",
-                    "less_than_byte_string-cpu-arguments-slope",
-                    "less_than_byte_string-mem-arguments",
-                    "less_than_equals_byte_string-cpu-arguments-intercept",
-                    "less_than_equals_byte_string-cpu-arguments-slope",
-                    "less_than_equals_byte_string-mem-arguments",
-                    "less_than_equals_integer-cpu-arguments-intercept",
-                    "less_than_equals_integer-cpu-arguments-slope",
-                    "less_than_equals_integer-mem-arguments",
-                    "less_than_integer-cpu-arguments-intercept",
-                    "less_than_integer-cpu-arguments-slope",
-                    "less_than_integer-mem-arguments",
-                    "list_data-cpu-arguments",
-                    "list_data-mem-arguments",
-                    "map_data-cpu-arguments",
-                    "map_data-mem-arguments",
-                    "mk_cons-cpu-arguments",
-                    "mk_cons-mem-arguments",
-                    "mk_nil_data-cpu-arguments",
-                    "mk_nil_data-mem-arguments",
-                    "mk_nil_pair_data-cpu-arguments",
-                    "mk_nil_pair_data-mem-arguments",
-                    "mk_pair_data-cpu-arguments",
-                    "mk_pair_data-mem-arguments",
-                    "mod_integer-cpu-arguments-constant",
-                    "mod_integer-cpu-arguments-c00",
-                    "mod_integer-cpu-arguments-c01",
-                    "mod_integer-cpu-arguments-c02",
-                    "mod_integer-cpu-arguments-c10",
-                    "mod_integer-cpu-arguments-c11",
-                    "mod_integer-cpu-arguments-c20",
-                    "mod_integer-cpu-arguments-minimum",
-                    "mod_integer-mem-arguments-intercept",
-                    "mod_integer-mem-arguments-slope",
-                    "multiply_integer-cpu-arguments-intercept",
-                    "multiply_integer-cpu-arguments-slope",
-                    "multiply_integer-mem-arguments-intercept",
-                    "multiply_integer-mem-arguments-slope",
-                    "null_list-cpu-arguments",
-                    "null_list-mem-arguments",
-                    "quotient_integer-cpu-arguments-constant",
-                    "quotient_integer-cpu-arguments-c00",
-                    "quotient_integer-cpu-arguments-c01",
-                    "quotient_integer-cpu-arguments-c02",
-                    "quotient_integer-cpu-arguments-c10",
-                    "quotient_integer-cpu-arguments-c11",
-                    "quotient_integer-cpu-arguments-c20",
-                    "quotient_integer-cpu-arguments-minimum",
-                    "quotient_integer-mem-arguments-intercept",
-                    "quotient_integer-mem-arguments-minimum",
-                    "quotient_integer-mem-arguments-slope",
-                    "remainder_integer-cpu-arguments-constant",
-                    "remainder_integer-cpu-arguments-c00",
-                    "remainder_integer-cpu-arguments-c01",
-                    "remainder_integer-cpu-arguments-c02",
-                    "remainder_integer-cpu-arguments-c10",
-                    "remainder_integer-cpu-arguments-c11",
-                    "remainder_integer-cpu-arguments-c20",
-                    "remainder_integer-cpu-arguments-minimum",
-                    "remainder_integer-mem-arguments-intercept",
-                    "remainder_integer-mem-arguments-slope",
-                    "serialise_data-cpu-arguments-intercept",
-                    "serialise_data-cpu-arguments-slope",
-                    "serialise_data-mem-arguments-intercept",
-                    "serialise_data-mem-arguments-slope",
-                    "sha2_256-cpu-arguments-intercept",
-                    "sha2_256-cpu-arguments-slope",
-                    "sha2_256-mem-arguments",
-                    "sha3_256-cpu-arguments-intercept",
-                    "sha3_256-cpu-arguments-slope",
-                    "sha3_256-mem-arguments",
-                    "slice_byte_string-cpu-arguments-intercept",
-                    "slice_byte_string-cpu-arguments-slope",
-                    "slice_byte_string-mem-arguments-intercept",
-                    "slice_byte_string-mem-arguments-slope",
-                    "snd_pair-cpu-arguments",
-                    "snd_pair-mem-arguments",
-                    "subtract_integer-cpu-arguments-intercept",
-                    "subtract_integer-cpu-arguments-slope",
-                    "subtract_integer-mem-arguments-intercept",
-                    "subtract_integer-mem-arguments-slope",
-                    "tail_list-cpu-arguments",
-                    "tail_list-mem-arguments",
-                    "trace-cpu-arguments",
-                    "trace-mem-arguments",
-                    "un_b_data-cpu-arguments",
-                    "un_b_data-mem-arguments",
-                    "un_constr_data-cpu-arguments",
-                    "un_constr_data-mem-arguments",
-                    "un_i_data-cpu-arguments",
-                    "un_i_data-mem-arguments",
-                    "un_list_data-cpu-arguments",
-                    "un_list_data-mem-arguments",
-                    "un_map_data-cpu-arguments",
-                    "un_map_data-mem-arguments",
-                    "verify_ecdsa_secp256k1_signature-cpu-arguments",
-                    "verify_ecdsa_secp256k1_signature-mem-arguments",
-                    "verify_ed25519_signature-cpu-arguments-intercept",
-                    "verify_ed25519_signature-cpu-arguments-slope",
-                    "verify_ed25519_signature-mem-arguments",
-                    "verify_schnorr_secp256k1_signature-cpu-arguments-intercept",
-                    "verify_schnorr_secp256k1_signature-cpu-arguments-slope",
-                    "verify_schnorr_secp256k1_signature-mem-arguments",
-                    "cek_constr_cost-exBudgetCPU",
-                    "cek_constr_cost-exBudgetmem",
-                    "cek_case_cost-exBudgetCPU",
-                    "cek_case_cost-exBudgetmem",
-                    "bls12_381_G1_add-cpu-arguments",
-                    "bls12_381_G1_add-mem-arguments",
-                    "bls12_381_G1_compress-cpu-arguments",
-                    "bls12_381_G1_compress-mem-arguments",
-                    "bls12_381_G1_equal-cpu-arguments",
-                    "bls12_381_G1_equal-mem-arguments",
-                    "bls12_381_G1_hashToGroup-cpu-arguments-intercept",
-                    "bls12_381_G1_hashToGroup-cpu-arguments-slope",
-                    "bls12_381_G1_hashToGroup-mem-arguments",
-                    "bls12_381_G1_neg-cpu-arguments",
-                    "bls12_381_G1_neg-mem-arguments",
-                    "bls12_381_G1_scalarMul-cpu-arguments-intercept",
-                    "bls12_381_G1_scalarMul-cpu-arguments-slope",
-                    "bls12_381_G1_scalarMul-mem-arguments",
-                    "bls12_381_G1_uncompress-cpu-arguments",
-                    "bls12_381_G1_uncompress-mem-arguments",
-                    "bls12_381_G2_add-cpu-arguments",
-                    "bls12_381_G2_add-mem-arguments",
-                    "bls12_381_G2_compress-cpu-arguments",
-                    "bls12_381_G2_compress-mem-arguments",
-                    "bls12_381_G2_equal-cpu-arguments",
-                    "bls12_381_G2_equal-mem-arguments",
-                    "bls12_381_G2_hashToGroup-cpu-arguments-intercept",
-                    "bls12_381_G2_hashToGroup-cpu-arguments-slope",
-                    "bls12_381_G2_hashToGroup-mem-arguments",
-                    "bls12_381_G2_neg-cpu-arguments",
-                    "bls12_381_G2_neg-mem-arguments",
-                    "bls12_381_G2_scalarMul-cpu-arguments-intercept",
-                    "bls12_381_G2_scalarMul-cpu-arguments-slope",
-                    "bls12_381_G2_scalarMul-mem-arguments",
-                    "bls12_381_G2_uncompress-cpu-arguments",
-                    "bls12_381_G2_uncompress-mem-arguments",
-                    "bls12_381_finalVerify-cpu-arguments",
-                    "bls12_381_finalVerify-mem-arguments",
-                    "bls12_381_millerLoop-cpu-arguments",
-                    "bls12_381_millerLoop-mem-arguments",
-                    "bls12_381_mulMlResult-cpu-arguments",
-                    "bls12_381_mulMlResult-mem-arguments",
-                    "keccak_256-cpu-arguments-intercept",
-                    "keccak_256-cpu-arguments-slope",
-                    "keccak_256-mem-arguments",
-                    "blake2b_224-cpu-arguments-intercept",
-                    "blake2b_224-cpu-arguments-slope",
-                    "blake2b_224-mem-arguments-slope",
-                    "integerToByteString-cpu-arguments-c0",
-                    "integerToByteString-cpu-arguments-c1",
-                    "integerToByteString-cpu-arguments-c2",
-                    "integerToByteString-mem-arguments-intercept",
-                    "integerToByteString-mem-arguments-slope",
-                    "byteStringToInteger-cpu-arguments-c0",
-                    "byteStringToInteger-cpu-arguments-c1",
-                    "byteStringToInteger-cpu-arguments-c2",
-                    "byteStringToInteger-mem-arguments-intercept",
-                    "byteStringToInteger-mem-arguments-slope",
-                ];
-
-                // Newer parameter vectors may append extra entries after the known
-                // bitwise/ripemd V3 costs. As long as we have at least that prefix,
-                // keep mapping the known keys instead of silently dropping them.
-                if values.len() >= 297 {
-                    base_keys.extend([
-                        "andByteString-cpu-arguments-intercept",
-                        "andByteString-cpu-arguments-slope1",
-                        "andByteString-cpu-arguments-slope2",
-                        "andByteString-memory-arguments-intercept",
-                        "andByteString-memory-arguments-slope",
-                        "orByteString-cpu-arguments-intercept",
-                        "orByteString-cpu-arguments-slope1",
-                        "orByteString-cpu-arguments-slope2",
-                        "orByteString-memory-arguments-intercept",
-                        "orByteString-memory-arguments-slope",
-                        "xorByteString-cpu-arguments-intercept",
-                        "xorByteString-cpu-arguments-slope1",
-                        "xorByteString-cpu-arguments-slope2",
-                        "xorByteString-memory-arguments-intercept",
-                        "xorByteString-memory-arguments-slope",
-                        "complementByteString-cpu-arguments-intercept",
-                        "complementByteString-cpu-arguments-slope",
-                        "complementByteString-memory-arguments-intercept",
-                        "complementByteString-memory-arguments-slope",
-                        "readBit-cpu-arguments",
-                        "readBit-memory-arguments",
-                        "writeBits-cpu-arguments-intercept",
-                        "writeBits-cpu-arguments-slope",
-                        "writeBits-memory-arguments-intercept",
-                        "writeBits-memory-arguments-slope",
-                        "replicateByte-cpu-arguments-intercept",
-                        "replicateByte-cpu-arguments-slope",
-                        "replicateByte-memory-arguments-intercept",
-                        "replicateByte-memory-arguments-slope",
-                        "shiftByteString-cpu-arguments-intercept",
-                        "shiftByteString-cpu-arguments-slope",
-                        "shiftByteString-memory-arguments-intercept",
-                        "shiftByteString-memory-arguments-slope",
-                        "rotateByteString-cpu-arguments-intercept",
-                        "rotateByteString-cpu-arguments-slope",
-                        "rotateByteString-memory-arguments-intercept",
-                        "rotateByteString-memory-arguments-slope",
-                        "countSetBits-cpu-arguments-intercept",
-                        "countSetBits-cpu-arguments-slope",
-                        "countSetBits-memory-arguments",
-                        "findFirstSetBit-cpu-arguments-intercept",
-                        "findFirstSetBit-cpu-arguments-slope",
-                        "findFirstSetBit-memory-arguments",
-                        "ripemd_160-cpu-arguments-intercept",
-                        "ripemd_160-cpu-arguments-slope",
-                        "ripemd_160-memory-arguments",
-                    ]);
-                }
-                base_keys
-            }
-        };
-
-        let map = cost_map_from_keys_and_values(&keys, values);
+                "cek_constr_cost-exBudgetCPU",
+                "cek_constr_cost-exBudgetmem",
+                "cek_case_cost-exBudgetCPU",
+                "cek_case_cost-exBudgetmem",
+                "bls12_381_G1_add-cpu-arguments",
+                "bls12_381_G1_add-mem-arguments",
+                "bls12_381_G1_compress-cpu-arguments",
+                "bls12_381_G1_compress-mem-arguments",
+                "bls12_381_G1_equal-cpu-arguments",
+                "bls12_381_G1_equal-mem-arguments",
+                "bls12_381_G1_hashToGroup-cpu-arguments-intercept",
+                "bls12_381_G1_hashToGroup-cpu-arguments-slope",
+                "bls12_381_G1_hashToGroup-mem-arguments",
+                "bls12_381_G1_neg-cpu-arguments",
+                "bls12_381_G1_neg-mem-arguments",
+                "bls12_381_G1_scalarMul-cpu-arguments-intercept",
+                "bls12_381_G1_scalarMul-cpu-arguments-slope",
+                "bls12_381_G1_scalarMul-mem-arguments",
+                "bls12_381_G1_uncompress-cpu-arguments",
+                "bls12_381_G1_uncompress-mem-arguments",
+                "bls12_381_G2_add-cpu-arguments",
+                "bls12_381_G2_add-mem-arguments",
+                "bls12_381_G2_compress-cpu-arguments",
+                "bls12_381_G2_compress-mem-arguments",
+                "bls12_381_G2_equal-cpu-arguments",
+                "bls12_381_G2_equal-mem-arguments",
+                "bls12_381_G2_hashToGroup-cpu-arguments-intercept",
+                "bls12_381_G2_hashToGroup-cpu-arguments-slope",
+                "bls12_381_G2_hashToGroup-mem-arguments",
+                "bls12_381_G2_neg-cpu-arguments",
+                "bls12_381_G2_neg-mem-arguments",
+                "bls12_381_G2_scalarMul-cpu-arguments-intercept",
+                "bls12_381_G2_scalarMul-cpu-arguments-slope",
+                "bls12_381_G2_scalarMul-mem-arguments",
+                "bls12_381_G2_uncompress-cpu-arguments",
+                "bls12_381_G2_uncompress-mem-arguments",
+                "bls12_381_finalVerify-cpu-arguments",
+                "bls12_381_finalVerify-mem-arguments",
+                "bls12_381_millerLoop-cpu-arguments",
+                "bls12_381_millerLoop-mem-arguments",
+                "bls12_381_mulMlResult-cpu-arguments",
+                "bls12_381_mulMlResult-mem-arguments",
+                "keccak_256-cpu-arguments-intercept",
+                "keccak_256-cpu-arguments-slope",
+                "keccak_256-mem-arguments",
+                "blake2b_224-cpu-arguments-intercept",
+                "blake2b_224-cpu-arguments-slope",
+                "blake2b_224-mem-arguments-slope",
+                "integerToByteString-cpu-arguments-c0",
+                "integerToByteString-cpu-arguments-c1",
+                "integerToByteString-cpu-arguments-c2",
+                "integerToByteString-mem-arguments-intercept",
+                "integerToByteString-mem-arguments-slope",
+                "byteStringToInteger-cpu-arguments-c0",
+                "byteStringToInteger-cpu-arguments-c1",
+                "byteStringToInteger-cpu-arguments-c2",
+                "byteStringToInteger-mem-arguments-intercept",
+                "byteStringToInteger-mem-arguments-slope",
+            ];
 
-        Self { map }
+            // Plomin (PV 10) appended the bitwise builtins and ripemd_160.
+            if len >= 297 {
+                base_keys.extend([
+                    "andByteString-cpu-arguments-intercept",
+                    "andByteString-cpu-arguments-slope1",
+                    "andByteString-cpu-arguments-slope2",
+                    "andByteString-memory-arguments-intercept",
+                    "andByteString-memory-arguments-slope",
+                    "orByteString-cpu-arguments-intercept",
+                    "orByteString-cpu-arguments-slope1",
+                    "orByteString-cpu-arguments-slope2",
+                    "orByteString-memory-arguments-intercept",
+                    "orByteString-memory-arguments-slope",
+                    "xorByteString-cpu-arguments-intercept",
+                    "xorByteString-cpu-arguments-slope1",
+                    "xorByteString-cpu-arguments-slope2",
+                    "xorByteString-memory-arguments-intercept",
+                    "xorByteString-memory-arguments-slope",
+                    "complementByteString-cpu-arguments-intercept",
+                    "complementByteString-cpu-arguments-slope",
+                    "complementByteString-memory-arguments-intercept",
+                    "complementByteString-memory-arguments-slope",
+                    "readBit-cpu-arguments",
+                    "readBit-memory-arguments",
+                    "writeBits-cpu-arguments-intercept",
+                    "writeBits-cpu-arguments-slope",
+                    "writeBits-memory-arguments-intercept",
+                    "writeBits-memory-arguments-slope",
+                    "replicateByte-cpu-arguments-intercept",
+                    "replicateByte-cpu-arguments-slope",
+                    "replicateByte-memory-arguments-intercept",
+                    "replicateByte-memory-arguments-slope",
+                    "shiftByteString-cpu-arguments-intercept",
+                    "shiftByteString-cpu-arguments-slope",
+                    "shiftByteString-memory-arguments-intercept",
+                    "shiftByteString-memory-arguments-slope",
+                    "rotateByteString-cpu-arguments-intercept",
+                    "rotateByteString-cpu-arguments-slope",
+                    "rotateByteString-memory-arguments-intercept",
+                    "rotateByteString-memory-arguments-slope",
+                    "countSetBits-cpu-arguments-intercept",
+                    "countSetBits-cpu-arguments-slope",
+                    "countSetBits-memory-arguments",
+                    "findFirstSetBit-cpu-arguments-intercept",
+                    "findFirstSetBit-cpu-arguments-slope",
+                    "findFirstSetBit-memory-arguments",
+                    "ripemd_160-cpu-arguments-intercept",
+                    "ripemd_160-cpu-arguments-slope",
+                    "ripemd_160-memory-arguments",
+                ]);
+            }
+            base_keys
+        }
     }
 }
 
@@ -684,6 +878,96 @@ fn cost_map_from_keys_and_values(keys: &[&str], values: &[i64]) -> HashMap<Strin
 impl Index<&str> for CostMap {
     type Output = i64;
     fn index(&self, key: &str) -> &Self::Output {
-        self.map.get(key).unwrap_or(&30000000000)
+        self.map
+            .get(key)
+            .unwrap_or_else(|| panic!("no cost model parameter named {key}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine::{CostModelError, PlutusVersion};
+
+    use super::{parameter_names, CostMap, MISSING_PARAMETER_COST};
+
+    #[test]
+    fn accepts_historical_lengths() {
+        let version = PlutusVersion::V3;
+
+        let cost_map = CostMap::new(&version, &[1; 251]).unwrap();
+
+        assert_eq!(cost_map["byteStringToInteger-mem-arguments-slope"], 1);
+        assert_eq!(
+            cost_map["ripemd_160-memory-arguments"],
+            MISSING_PARAMETER_COST
+        );
+
+        let cost_map = CostMap::new(&version, &[1; 297]).unwrap();
+
+        assert_eq!(cost_map["ripemd_160-memory-arguments"], 1);
+
+        let cost_map = CostMap::new(&PlutusVersion::V1, &[1; 166]).unwrap();
+
+        assert_eq!(
+            cost_map["cek_case_cost-exBudgetCPU"],
+            MISSING_PARAMETER_COST
+        );
+    }
+
+    #[test]
+    fn rejects_missing_and_extra_parameters() {
+        let version = PlutusVersion::V2;
+
+        let names = parameter_names(&version, usize::MAX);
+
+        assert_eq!(
+            CostMap::new(&version, &[1; 174]).err(),
+            Some(CostModelError::MissingParameters {
+                version,
+                names: vec![names[174].to_string()],
+            })
+        );
+
+        assert!(matches!(
+            CostMap::new(&version, &[1; 180]),
+            Err(CostModelError::MissingParameters { names, .. }) if names.len() == 5
+        ));
+
+        assert_eq!(
+            CostMap::new(&version, &[1; 186]).err(),
+            Some(CostModelError::ExtraParameters {
+                version,
+                expected: 185,
+                given: 186,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_negative_parameters() {
+        let version = PlutusVersion::V3;
+
+        let names = parameter_names(&version, 0);
+
+        let mut values = vec![1; names.len()];
+
+        let coefficient = names
+            .iter()
+            .position(|name| *name == "divide_integer-cpu-arguments-c02")
+            .unwrap();
+
+        values[coefficient] = -900;
+
+        assert!(CostMap::new(&version, &values).is_ok());
+
+        values[0] = -1;
+
+        assert_eq!(
+            CostMap::new(&version, &values).err(),
+            Some(CostModelError::OutOfRange {
+                name: names[0].to_string(),
+                value: -1,
+            })
+        );
     }
 }
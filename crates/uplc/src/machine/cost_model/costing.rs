@@ -1,5 +1,12 @@
+use std::{fmt, num::Saturating};
+
 pub trait Cost<const N: usize> {
     fn cost(&self, args: [i64; N]) -> i64;
+
+    /// The smallest and largest cost over every combination of sizes in
+    /// `ranges`, inclusive `(min, max)` pairs of non-negative sizes, or
+    /// `None` when the costing function cannot be bounded soundly over them.
+    fn bounds(&self, ranges: [(i64, i64); N]) -> Option<(i64, i64)>;
 }
 
 // Struct using the trait
@@ -27,12 +34,25 @@ pub enum OneArgument {
 
 impl Cost<1> for OneArgument {
     fn cost(&self, args: [i64; 1]) -> i64 {
-        let x = args[0];
+        let x = Saturating(args[0]);
+
+        let cost = match self {
+            OneArgument::ConstantCost(c) => Saturating(*c),
+            OneArgument::LinearCost(m) => linear(m, x),
+            OneArgument::Quadratic(q) => quadratic(q, x),
+        };
+
+        cost.0
+    }
 
+    fn bounds(&self, [x]: [(i64, i64); 1]) -> Option<(i64, i64)> {
         match self {
-            OneArgument::ConstantCost(c) => *c,
-            OneArgument::LinearCost(m) => m.slope * x + m.intercept,
-            OneArgument::Quadratic(q) => q.coeff_0 + (q.coeff_1 * x) + (q.coeff_2 * x * x),
+            OneArgument::ConstantCost(_) | OneArgument::LinearCost(_) => {
+                extremes(self, corners([x]))
+            }
+            OneArgument::Quadratic(q) => {
+                extremes(self, quadratic_sizes(x, q)?.into_iter().map(|x| [x]))
+            }
         }
     }
 }
@@ -172,53 +192,266 @@ impl TwoArgumentsCosting {
 
 impl Cost<2> for TwoArguments {
     fn cost(&self, args: [i64; 2]) -> i64 {
-        let x = args[0];
-        let y = args[1];
+        let x = Saturating(args[0]);
+        let y = Saturating(args[1]);
 
-        match self {
-            TwoArguments::ConstantCost(c) => *c,
-            TwoArguments::LinearInX(l) => l.slope * x + l.intercept,
-            TwoArguments::LinearInY(l) => l.slope * y + l.intercept,
-            TwoArguments::AddedSizes(s) => s.slope * (x + y) + s.intercept,
-            TwoArguments::SubtractedSizes(s) => s.slope * s.minimum.max(x - y) + s.intercept,
-            TwoArguments::MultipliedSizes(s) => s.slope * (x * y) + s.intercept,
-            TwoArguments::MinSize(s) => s.slope * x.min(y) + s.intercept,
-            TwoArguments::MaxSize(s) => s.slope * x.max(y) + s.intercept,
+        let cost = match self {
+            TwoArguments::ConstantCost(c) => Saturating(*c),
+            TwoArguments::LinearInX(l) => linear(l, x),
+            TwoArguments::LinearInY(l) => linear(l, y),
+            TwoArguments::AddedSizes(s) => Saturating(s.slope) * (x + y) + Saturating(s.intercept),
+            TwoArguments::SubtractedSizes(s) => {
+                Saturating(s.slope) * Saturating(s.minimum).max(x - y) + Saturating(s.intercept)
+            }
+            TwoArguments::MultipliedSizes(s) => {
+                Saturating(s.slope) * (x * y) + Saturating(s.intercept)
+            }
+            TwoArguments::MinSize(s) => Saturating(s.slope) * x.min(y) + Saturating(s.intercept),
+            TwoArguments::MaxSize(s) => Saturating(s.slope) * x.max(y) + Saturating(s.intercept),
             TwoArguments::LinearOnDiagonal(l) => {
                 if x == y {
-                    x * l.slope + l.intercept
+                    x * Saturating(l.slope) + Saturating(l.intercept)
                 } else {
-                    l.constant
+                    Saturating(l.constant)
                 }
             }
-            TwoArguments::QuadraticInY(q) => q.coeff_0 + (q.coeff_1 * y) + (q.coeff_2 * y * y),
+            TwoArguments::QuadraticInY(q) => quadratic(q, y),
             TwoArguments::ConstAboveDiagonalIntoQuadraticXAndY(constant, q) => {
                 if x < y {
-                    *constant
+                    Saturating(*constant)
                 } else {
                     std::cmp::max(
-                        q.minimum,
-                        q.coeff_00
-                            + q.coeff_10 * x
-                            + q.coeff_01 * y
-                            + q.coeff_20 * x * x
-                            + q.coeff_11 * x * y
-                            + q.coeff_02 * y * y,
+                        Saturating(q.minimum),
+                        Saturating(q.coeff_00)
+                            + Saturating(q.coeff_10) * x
+                            + Saturating(q.coeff_01) * y
+                            + Saturating(q.coeff_20) * x * x
+                            + Saturating(q.coeff_11) * x * y
+                            + Saturating(q.coeff_02) * y * y,
                     )
                 }
             }
             TwoArguments::ConstAboveDiagonalIntoMultipliedSizes(constant, s) => {
                 if x < y {
-                    *constant
+                    Saturating(*constant)
                 } else {
-                    s.slope * (x * y) + s.intercept
+                    Saturating(s.slope) * (x * y) + Saturating(s.intercept)
+                }
+            }
+            TwoArguments::WithInteraction(w) => {
+                Saturating(w.c00)
+                    + Saturating(w.c10) * x
+                    + Saturating(w.c01) * y
+                    + Saturating(w.c11) * x * y
+            }
+        };
+
+        cost.0
+    }
+
+    fn bounds(&self, [x, y]: [(i64, i64); 2]) -> Option<(i64, i64)> {
+        let mut points = corners([x, y]);
+
+        // The smallest and largest sizes on or below the diagonal, if any.
+        let below = (x.1 >= y.0).then(|| ([x.0.max(y.0), y.0], [x.1, y.1.min(x.1)]));
+
+        match self {
+            TwoArguments::ConstantCost(_)
+            | TwoArguments::LinearInX(_)
+            | TwoArguments::LinearInY(_)
+            | TwoArguments::AddedSizes(_)
+            | TwoArguments::SubtractedSizes(_)
+            | TwoArguments::MultipliedSizes(_)
+            | TwoArguments::MinSize(_)
+            | TwoArguments::MaxSize(_) => {}
+            // Off the diagonal the cost is constant, and some corner is off
+            // it whenever any sizes are.
+            TwoArguments::LinearOnDiagonal(_) => {
+                let (lo, hi) = (x.0.max(y.0), x.1.min(y.1));
+
+                if lo <= hi {
+                    points.extend([[lo, lo], [hi, hi]]);
+                }
+            }
+            TwoArguments::QuadraticInY(q) => {
+                points = grid([vec![x.0], quadratic_sizes(y, q)?]);
+            }
+            // Below the diagonal the cost grows, or shrinks, with both sizes.
+            TwoArguments::ConstAboveDiagonalIntoMultipliedSizes(..) => {
+                points.extend(below.into_iter().flat_map(|(lo, hi)| [lo, hi]));
+            }
+            TwoArguments::ConstAboveDiagonalIntoQuadraticXAndY(_, q) => {
+                return quadratic_below_diagonal(self, q, x, y, points, below.is_some());
+            }
+            // Bilinear, so extreme at the corners unless it saturates.
+            TwoArguments::WithInteraction(w) => {
+                let coefficients = [w.c10, w.c01, w.c11];
+
+                let monotone =
+                    coefficients.iter().all(|c| *c >= 0) || coefficients.iter().all(|c| *c <= 0);
+
+                let fits = fits(&[
+                    magnitude(w.c00, &[]),
+                    magnitude(w.c10, &[x.1]),
+                    magnitude(w.c01, &[y.1]),
+                    magnitude(w.c11, &[x.1, y.1]),
+                ]);
+
+                if !monotone && !fits {
+                    return None;
                 }
             }
-            TwoArguments::WithInteraction(w) => w.c00 + w.c10 * x + w.c01 * y + w.c11 * x * y,
         }
+
+        extremes(self, points)
     }
 }
 
+/// Bound `max(minimum, Q(x, y))` on or below the diagonal, and the constant
+/// above it, by the real extremes of `Q` over that region: at its corners,
+/// at the vertex of `Q` along each edge, or at the critical point inside.
+fn quadratic_below_diagonal(
+    costing: &TwoArguments,
+    q: &TwoArgumentsQuadraticFunction,
+    x: (i64, i64),
+    y: (i64, i64),
+    mut points: Vec<[i64; 2]>,
+    below: bool,
+) -> Option<(i64, i64)> {
+    if !below {
+        return extremes(costing, points);
+    }
+
+    let fits = fits(&[
+        magnitude(q.coeff_00, &[]),
+        magnitude(q.coeff_10, &[x.1]),
+        magnitude(q.coeff_01, &[y.1]),
+        magnitude(q.coeff_20, &[x.1, x.1]),
+        magnitude(q.coeff_11, &[x.1, y.1]),
+        magnitude(q.coeff_02, &[y.1, y.1]),
+    ]);
+
+    if !fits {
+        return None;
+    }
+
+    let [c00, c10, c01, c20, c11, c02] = [
+        q.coeff_00, q.coeff_10, q.coeff_01, q.coeff_20, q.coeff_11, q.coeff_02,
+    ]
+    .map(i128::from);
+
+    // Every edge as the range of the size `s` along it, and `Q` along it as
+    // e0 + e1 s + e2 s^2.
+    let mut edges = Vec::new();
+
+    for a in [x.0, x.1] {
+        let range = (y.0, y.1.min(a));
+
+        if range.0 <= range.1 {
+            points.extend([[a, range.0], [a, range.1]]);
+        }
+
+        let a = i128::from(a);
+
+        edges.push((range, [c00 + c10 * a + c20 * a * a, c01 + c11 * a, c02]));
+    }
+
+    for b in [y.0, y.1] {
+        let range = (x.0.max(b), x.1);
+
+        if range.0 <= range.1 {
+            points.extend([[range.0, b], [range.1, b]]);
+        }
+
+        let b = i128::from(b);
+
+        edges.push((range, [c00 + c01 * b + c02 * b * b, c10 + c11 * b, c20]));
+    }
+
+    edges.push((
+        (x.0.max(y.0), x.1.min(y.1)),
+        [c00, c10 + c01, c20 + c11 + c02],
+    ));
+
+    // The corners were evaluated exactly, only the extremes between them
+    // are left.
+    let (mut lower, mut upper) = extremes(costing, points)?;
+
+    let mut bound = |numerator: i128, denominator: i128, concave: bool| -> Option<()> {
+        if concave {
+            let peak = i64::try_from(ceil(numerator, denominator)?).ok()?;
+
+            upper = upper.max(q.minimum.max(peak));
+        } else {
+            let bottom = i64::try_from(numerator.div_euclid(denominator)).ok()?;
+
+            lower = lower.min(q.minimum.max(bottom));
+        }
+
+        Some(())
+    };
+
+    for ((min, max), [e0, e1, e2]) in edges {
+        if min > max || e2 == 0 {
+            continue;
+        }
+
+        // The vertex is at -e1 / (2 e2), where Q is e0 - e1^2 / (4 e2).
+        let (n, d) = if e2 > 0 { (-e1, 2 * e2) } else { (e1, -2 * e2) };
+
+        if i128::from(min).checked_mul(d)? <= n && n <= i128::from(max).checked_mul(d)? {
+            let numerator = e0.checked_mul(4 * e2)?.checked_sub(e1.checked_mul(e1)?)?;
+
+            if e2 > 0 {
+                bound(numerator, 4 * e2, false)?;
+            } else {
+                bound(-numerator, -4 * e2, true)?;
+            }
+        }
+    }
+
+    // The critical point solves 2 c20 x + c11 y = -c10 and
+    // c11 x + 2 c02 y = -c01. It is an extremum when `det` is positive, and
+    // Q there is c00 + (c10 x + c01 y) / 2.
+    let det = (4 * c20)
+        .checked_mul(c02)?
+        .checked_sub(c11.checked_mul(c11)?)?;
+
+    if det > 0 {
+        let nx = c11
+            .checked_mul(c01)?
+            .checked_sub((2 * c02).checked_mul(c10)?)?;
+        let ny = c11
+            .checked_mul(c10)?
+            .checked_sub((2 * c20).checked_mul(c01)?)?;
+
+        let scaled = |size: i64| i128::from(size).checked_mul(det);
+
+        let inside = scaled(x.0)? <= nx
+            && nx <= scaled(x.1)?
+            && scaled(y.0)? <= ny
+            && ny <= scaled(y.1)?
+            && ny <= nx;
+
+        if inside {
+            let denominator = det.checked_mul(2)?;
+            let numerator = c00
+                .checked_mul(denominator)?
+                .checked_add(c10.checked_mul(nx)?)?
+                .checked_add(c01.checked_mul(ny)?)?;
+
+            bound(numerator, denominator, c20 < 0)?;
+        }
+    }
+
+    Some((lower, upper))
+}
+
+/// `numerator / denominator` rounded up, for a positive `denominator`.
+fn ceil(numerator: i128, denominator: i128) -> Option<i128> {
+    Some(-numerator.checked_neg()?.div_euclid(denominator))
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ThreeArguments {
     ConstantCost(i64),
@@ -287,33 +520,81 @@ impl ThreeArgumentsCosting {
 
 impl Cost<3> for ThreeArguments {
     fn cost(&self, args: [i64; 3]) -> i64 {
-        let x = args[0];
-        let y = args[1];
-        let z = args[2];
+        let x = Saturating(args[0]);
+        let y = Saturating(args[1]);
+        let z = Saturating(args[2]);
 
-        match self {
-            ThreeArguments::ConstantCost(c) => *c,
+        let cost = match self {
+            ThreeArguments::ConstantCost(c) => Saturating(*c),
             // ThreeArguments::AddedSizes(s) => (x + y + z) * s.slope + s.intercept,
-            ThreeArguments::LinearInX(l) => x * l.slope + l.intercept,
-            ThreeArguments::LinearInY(l) => y * l.slope + l.intercept,
-            ThreeArguments::LinearInZ(l) => z * l.slope + l.intercept,
-            ThreeArguments::QuadraticInZ(q) => q.coeff_0 + (q.coeff_1 * z) + (q.coeff_2 * z * z),
+            ThreeArguments::LinearInX(l) => linear(l, x),
+            ThreeArguments::LinearInY(l) => linear(l, y),
+            ThreeArguments::LinearInZ(l) => linear(l, z),
+            ThreeArguments::QuadraticInZ(q) => quadratic(q, z),
             ThreeArguments::LiteralInYorLinearInZ(l) => {
-                if y == 0 {
-                    l.slope * z + l.intercept
+                if y.0 == 0 {
+                    linear(l, z)
                 } else {
                     y
                 }
             }
-            ThreeArguments::LinearInYAndZ(l) => y * l.slope1 + z * l.slope2 + l.intercept,
-            ThreeArguments::LinearInMaxYZ(l) => y.max(z) * l.slope + l.intercept,
+            ThreeArguments::LinearInYAndZ(l) => {
+                y * Saturating(l.slope1) + z * Saturating(l.slope2) + Saturating(l.intercept)
+            }
+            ThreeArguments::LinearInMaxYZ(l) => linear(l, y.max(z)),
             ThreeArguments::ExpModCost(c) => {
-                let cost = c.coeff_00 + c.coeff_11 * y * z + c.coeff_12 * y * z * z;
+                let cost = Saturating(c.coeff_00)
+                    + Saturating(c.coeff_11) * y * z
+                    + Saturating(c.coeff_12) * y * z * z;
+
                 if x <= z {
                     cost
                 } else {
-                    cost + (cost / 2)
+                    cost + (cost / Saturating(2))
+                }
+            }
+        };
+
+        cost.0
+    }
+
+    fn bounds(&self, [x, y, z]: [(i64, i64); 3]) -> Option<(i64, i64)> {
+        match self {
+            ThreeArguments::ConstantCost(_)
+            | ThreeArguments::LinearInX(_)
+            | ThreeArguments::LinearInY(_)
+            | ThreeArguments::LinearInZ(_)
+            | ThreeArguments::LinearInYAndZ(_)
+            | ThreeArguments::LinearInMaxYZ(_) => extremes(self, corners([x, y, z])),
+            ThreeArguments::QuadraticInZ(q) => {
+                extremes(self, grid([vec![x.0], vec![y.0], quadratic_sizes(z, q)?]))
+            }
+            // A literal y is the cost unless it is zero, so the smallest
+            // non-zero y counts too.
+            ThreeArguments::LiteralInYorLinearInZ(_) => {
+                let mut ys = vec![y.0, y.1];
+
+                if y.0 == 0 && y.1 > 0 {
+                    ys.push(1);
                 }
+
+                extremes(self, grid([vec![x.0], ys, vec![z.0, z.1]]))
+            }
+            // With non-negative coefficients the cost grows with y and z,
+            // and half as much again once x is above z.
+            ThreeArguments::ExpModCost(c) => {
+                if [c.coeff_00, c.coeff_11, c.coeff_12].iter().any(|c| *c < 0) {
+                    return None;
+                }
+
+                let zs = vec![
+                    z.0,
+                    z.1,
+                    x.0.clamp(z.0, z.1),
+                    x.1.saturating_sub(1).clamp(z.0, z.1),
+                ];
+
+                extremes(self, grid([vec![x.0, x.1], vec![y.0, y.1], zs]))
             }
         }
     }
@@ -338,6 +619,123 @@ impl Cost<6> for SixArguments {
             SixArguments::ConstantCost(c) => *c,
         }
     }
+
+    fn bounds(&self, _ranges: [(i64, i64); 6]) -> Option<(i64, i64)> {
+        match self {
+            SixArguments::ConstantCost(c) => Some((*c, *c)),
+        }
+    }
+}
+
+// Costs are computed like the Haskell `CostingInteger`: every operation
+// saturates at the bounds of `i64` instead of wrapping around.
+
+fn linear(l: &LinearSize, x: Saturating<i64>) -> Saturating<i64> {
+    Saturating(l.slope) * x + Saturating(l.intercept)
+}
+
+fn quadratic(q: &QuadraticFunction, x: Saturating<i64>) -> Saturating<i64> {
+    Saturating(q.coeff_0) + Saturating(q.coeff_1) * x + Saturating(q.coeff_2) * x * x
+}
+
+// Bounds are found by evaluating the cost at a few sizes that are known to
+// include the cheapest and the most expensive ones.
+
+fn extremes<const N: usize>(
+    costing: &impl Cost<N>,
+    points: impl IntoIterator<Item = [i64; N]>,
+) -> Option<(i64, i64)> {
+    points
+        .into_iter()
+        .map(|point| costing.cost(point))
+        .fold(None, |bounds, cost| {
+            let (lower, upper) = bounds.unwrap_or((cost, cost));
+
+            Some((lower.min(cost), upper.max(cost)))
+        })
+}
+
+/// Every combination of the given values of each size.
+fn grid<const N: usize>(axes: [Vec<i64>; N]) -> Vec<[i64; N]> {
+    let mut points = vec![[0; N]];
+
+    for (i, axis) in axes.iter().enumerate() {
+        points = points
+            .into_iter()
+            .flat_map(|point| {
+                axis.iter().map(move |size| {
+                    let mut point = point;
+
+                    point[i] = *size;
+
+                    point
+                })
+            })
+            .collect();
+    }
+
+    points
+}
+
+fn corners<const N: usize>(ranges: [(i64, i64); N]) -> Vec<[i64; N]> {
+    grid(ranges.map(|(min, max)| vec![min, max]))
+}
+
+/// The sizes where a quadratic in one size can be cheapest or most
+/// expensive: the ends of `range`, and the integers either side of its
+/// vertex. A quadratic that may saturate is only bounded when it is
+/// monotone.
+fn quadratic_sizes(range: (i64, i64), q: &QuadraticFunction) -> Option<Vec<i64>> {
+    let monotone = q.coeff_1 >= 0 && q.coeff_2 >= 0 || q.coeff_1 <= 0 && q.coeff_2 <= 0;
+
+    if monotone {
+        return Some(vec![range.0, range.1]);
+    }
+
+    let fits = fits(&[
+        magnitude(q.coeff_0, &[]),
+        magnitude(q.coeff_1, &[range.1]),
+        magnitude(q.coeff_2, &[range.1, range.1]),
+    ]);
+
+    fits.then(|| quadratic_points(range, q.coeff_1.into(), q.coeff_2.into()))
+}
+
+/// The ends of `range` and the integers either side of the vertex of
+/// `c1 * s + c2 * s^2` within it. Empty when `range` is.
+fn quadratic_points((min, max): (i64, i64), c1: i128, c2: i128) -> Vec<i64> {
+    if min > max {
+        return vec![];
+    }
+
+    let mut points = vec![min, max];
+
+    if c2 != 0 {
+        let (numerator, denominator) = if c2 > 0 { (-c1, 2 * c2) } else { (c1, -2 * c2) };
+
+        let vertex = numerator.div_euclid(denominator);
+
+        for s in [vertex, vertex + 1] {
+            points.push(s.clamp(min.into(), max.into()) as i64);
+        }
+    }
+
+    points
+}
+
+/// The largest magnitude of `coefficient` times the given sizes.
+fn magnitude(coefficient: i64, sizes: &[i64]) -> i128 {
+    sizes.iter().fold(i128::from(coefficient).abs(), |m, s| {
+        m.saturating_mul((*s).into())
+    })
+}
+
+/// Whether terms of these magnitudes add up without saturating.
+fn fits(magnitudes: &[i128]) -> bool {
+    magnitudes
+        .iter()
+        .try_fold(0i128, |sum, m| sum.checked_add(*m))
+        .is_some_and(|sum| sum <= i64::MAX.into())
 }
 
 #[derive(Debug, PartialEq)]
@@ -393,20 +791,20 @@ pub struct ConstantOrLinear {
 
 #[derive(Debug, PartialEq)]
 pub struct QuadraticFunction {
-    coeff_0: i64,
-    coeff_1: i64,
-    coeff_2: i64,
+    pub coeff_0: i64,
+    pub coeff_1: i64,
+    pub coeff_2: i64,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct TwoArgumentsQuadraticFunction {
-    minimum: i64,
-    coeff_00: i64,
-    coeff_01: i64,
-    coeff_02: i64,
-    coeff_10: i64,
-    coeff_11: i64,
-    coeff_20: i64,
+    pub minimum: i64,
+    pub coeff_00: i64,
+    pub coeff_01: i64,
+    pub coeff_02: i64,
+    pub coeff_10: i64,
+    pub coeff_11: i64,
+    pub coeff_20: i64,
 }
 
 #[derive(Debug, PartialEq)]
@@ -419,7 +817,376 @@ pub struct WithInteraction {
 
 #[derive(Debug, PartialEq)]
 pub struct ExpModCost {
-    coeff_00: i64,
-    coeff_11: i64,
-    coeff_12: i64,
+    pub coeff_00: i64,
+    pub coeff_11: i64,
+    pub coeff_12: i64,
+}
+
+// Formulas over the argument sizes x, y and z, as in the Plutus cost model
+// specification.
+
+impl fmt::Display for OneArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OneArgument::ConstantCost(c) => write!(f, "{c}"),
+            OneArgument::LinearCost(l) => write_linear(f, l, "x"),
+            OneArgument::Quadratic(q) => write_quadratic(f, q, "x"),
+        }
+    }
+}
+
+impl fmt::Display for TwoArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TwoArguments::ConstantCost(c) => write!(f, "{c}"),
+            TwoArguments::LinearInX(l) => write_linear(f, l, "x"),
+            TwoArguments::LinearInY(l) => write_linear(f, l, "y"),
+            TwoArguments::AddedSizes(s) => {
+                write_polynomial(f, &[(s.intercept, ""), (s.slope, "(x + y)")])
+            }
+            TwoArguments::SubtractedSizes(s) => write_polynomial(
+                f,
+                &[
+                    (s.intercept, ""),
+                    (s.slope, &format!("max({}, x - y)", s.minimum)),
+                ],
+            ),
+            TwoArguments::MultipliedSizes(s) => write_multiplied(f, s),
+            TwoArguments::MinSize(s) => {
+                write_polynomial(f, &[(s.intercept, ""), (s.slope, "min(x, y)")])
+            }
+            TwoArguments::MaxSize(s) => {
+                write_polynomial(f, &[(s.intercept, ""), (s.slope, "max(x, y)")])
+            }
+            TwoArguments::LinearOnDiagonal(l) => {
+                write!(f, "if x == y then ")?;
+                write_polynomial(f, &[(l.intercept, ""), (l.slope, "x")])?;
+                write!(f, " else {}", l.constant)
+            }
+            TwoArguments::QuadraticInY(q) => write_quadratic(f, q, "y"),
+            TwoArguments::ConstAboveDiagonalIntoQuadraticXAndY(constant, q) => {
+                write!(f, "if x < y then {constant} else max({}, ", q.minimum)?;
+                write_polynomial(
+                    f,
+                    &[
+                        (q.coeff_00, ""),
+                        (q.coeff_10, "x"),
+                        (q.coeff_01, "y"),
+                        (q.coeff_20, "x^2"),
+                        (q.coeff_11, "x * y"),
+                        (q.coeff_02, "y^2"),
+                    ],
+                )?;
+                write!(f, ")")
+            }
+            TwoArguments::ConstAboveDiagonalIntoMultipliedSizes(constant, s) => {
+                write!(f, "if x < y then {constant} else ")?;
+                write_multiplied(f, s)
+            }
+            TwoArguments::WithInteraction(w) => write_polynomial(
+                f,
+                &[(w.c00, ""), (w.c10, "x"), (w.c01, "y"), (w.c11, "x * y")],
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ThreeArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThreeArguments::ConstantCost(c) => write!(f, "{c}"),
+            ThreeArguments::LinearInX(l) => write_linear(f, l, "x"),
+            ThreeArguments::LinearInY(l) => write_linear(f, l, "y"),
+            ThreeArguments::LinearInZ(l) => write_linear(f, l, "z"),
+            ThreeArguments::QuadraticInZ(q) => write_quadratic(f, q, "z"),
+            ThreeArguments::LiteralInYorLinearInZ(l) => {
+                write!(f, "if y == 0 then ")?;
+                write_linear(f, l, "z")?;
+                write!(f, " else y")
+            }
+            ThreeArguments::LinearInYAndZ(l) => {
+                write_polynomial(f, &[(l.intercept, ""), (l.slope1, "y"), (l.slope2, "z")])
+            }
+            ThreeArguments::LinearInMaxYZ(l) => write_linear(f, l, "max(y, z)"),
+            ThreeArguments::ExpModCost(c) => {
+                write!(f, "let c = ")?;
+                write_polynomial(
+                    f,
+                    &[
+                        (c.coeff_00, ""),
+                        (c.coeff_11, "y * z"),
+                        (c.coeff_12, "y * z^2"),
+                    ],
+                )?;
+                write!(f, " in if x <= z then c else c + c / 2")
+            }
+        }
+    }
+}
+
+impl fmt::Display for SixArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SixArguments::ConstantCost(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+fn write_linear(f: &mut fmt::Formatter<'_>, l: &LinearSize, variable: &str) -> fmt::Result {
+    write_polynomial(f, &[(l.intercept, ""), (l.slope, variable)])
+}
+
+fn write_multiplied(f: &mut fmt::Formatter<'_>, s: &MultipliedSizes) -> fmt::Result {
+    write_polynomial(f, &[(s.intercept, ""), (s.slope, "x * y")])
+}
+
+fn write_quadratic(
+    f: &mut fmt::Formatter<'_>,
+    q: &QuadraticFunction,
+    variable: &str,
+) -> fmt::Result {
+    write_polynomial(
+        f,
+        &[
+            (q.coeff_0, ""),
+            (q.coeff_1, variable),
+            (q.coeff_2, &format!("{variable}^2")),
+        ],
+    )
+}
+
+/// Write `c0 + c1 * m1 + ...`, leaving out zero terms and folding signs.
+fn write_polynomial(f: &mut fmt::Formatter<'_>, terms: &[(i64, &str)]) -> fmt::Result {
+    let mut first = true;
+
+    for (coefficient, monomial) in terms {
+        if *coefficient == 0 {
+            continue;
+        }
+
+        let magnitude = coefficient.unsigned_abs();
+
+        match (first, *coefficient < 0) {
+            (true, false) => {}
+            (true, true) => write!(f, "-")?,
+            (false, false) => write!(f, " + ")?,
+            (false, true) => write!(f, " - ")?,
+        }
+
+        if monomial.is_empty() {
+            write!(f, "{magnitude}")?;
+        } else if magnitude == 1 {
+            write!(f, "{monomial}")?;
+        } else {
+            write!(f, "{magnitude} * {monomial}")?;
+        }
+
+        first = false;
+    }
+
+    if first {
+        write!(f, "0")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Cost, OneArgumentCosting as One, SixArgumentsCosting as Six,
+        ThreeArgumentsCosting as Three, TwoArgumentsCosting as Two,
+    };
+
+    const MAX: i64 = i64::MAX;
+    const MIN: i64 = i64::MIN;
+
+    #[test]
+    fn one_argument_saturates() {
+        assert_eq!(One::constant_cost(MAX).cost([MAX]), MAX);
+        assert_eq!(One::linear_cost(1, 2).cost([MAX / 2]), MAX);
+        assert_eq!(One::linear_cost(0, 2).cost([MAX / 2]), MAX - 1);
+        assert_eq!(One::linear_cost(-1, -2).cost([MAX]), MIN);
+        assert_eq!(One::quadratic_cost(0, 0, 1).cost([1 << 32]), MAX);
+        assert_eq!(
+            One::quadratic_cost(0, 0, 1).cost([(1 << 31) + 1]),
+            (1 << 62) + (1 << 32) + 1
+        );
+        assert_eq!(One::quadratic_cost(0, 0, -1).cost([1 << 32]), MIN);
+    }
+
+    #[test]
+    fn two_arguments_saturate() {
+        assert_eq!(Two::constant_cost(MAX).cost([MAX, MAX]), MAX);
+        assert_eq!(Two::linear_in_x(1, 1).cost([MAX, 0]), MAX);
+        assert_eq!(Two::linear_in_y(1, 1).cost([0, MAX]), MAX);
+        assert_eq!(Two::added_sizes(0, 1).cost([MAX, 1]), MAX);
+        assert_eq!(Two::added_sizes(0, 1).cost([MAX - 1, 1]), MAX);
+        assert_eq!(Two::subtracted_sizes(0, 1, 1).cost([MAX, MIN]), MAX);
+        assert_eq!(Two::subtracted_sizes(0, 1, 1).cost([MIN, MAX]), 1);
+        assert_eq!(Two::multiplied_sizes(0, 1).cost([1 << 32, 1 << 31]), MAX);
+        assert_eq!(
+            Two::multiplied_sizes(1, 1).cost([1 << 31, 1 << 31]),
+            (1 << 62) + 1
+        );
+        assert_eq!(Two::min_size(1, MAX).cost([2, 3]), MAX);
+        assert_eq!(Two::max_size(1, MAX).cost([2, 3]), MAX);
+        assert_eq!(Two::linear_on_diagonal(7, 1, 2).cost([MAX, MAX]), MAX);
+        assert_eq!(Two::linear_on_diagonal(7, 1, 2).cost([MAX, 0]), 7);
+        assert_eq!(Two::quadratic_in_y(0, 0, 1).cost([0, 1 << 32]), MAX);
+        assert_eq!(Two::quadratic_in_y(0, MAX, 1).cost([0, 2]), MAX);
+
+        let quadratic = Two::const_above_diagonal_into_quadratic_x_and_y(
+            85848, 85848, 123203, 7305, -900, 1716, 960, 57,
+        );
+
+        assert_eq!(quadratic.cost([1, 2]), 85848);
+        // Every step saturates on its own, so later terms still count.
+        assert_eq!(quadratic.cost([MAX, 1]), MAX - 900);
+        assert_eq!(quadratic.cost([1 << 40, 1 << 40]), 85848);
+
+        let negative = Two::const_above_diagonal_into_quadratic_x_and_y(0, 42, 0, 0, -1, 0, 0, 0);
+
+        assert_eq!(negative.cost([MAX, MAX]), 42);
+
+        let multiplied = Two::const_above_diagonal_into_multiplied_sizes(3, 0, 1);
+
+        assert_eq!(multiplied.cost([MAX, MAX]), MAX);
+        assert_eq!(multiplied.cost([0, MAX]), 3);
+
+        assert_eq!(Two::with_interaction(0, 0, 0, 1).cost([MAX, 2]), MAX);
+        assert_eq!(Two::with_interaction(0, 0, 0, -1).cost([MAX, 2]), MIN);
+        assert_eq!(Two::with_interaction(MAX, 1, 1, 0).cost([1, 1]), MAX);
+    }
+
+    #[test]
+    fn two_argument_bounds_match_every_cost() {
+        // Exact shapes are bounded by costs they take, the quadratic in x
+        // and y by its real extremes.
+        let shapes = [
+            (Two::constant_cost(7), true),
+            (Two::linear_in_x(5, -3), true),
+            (Two::linear_in_y(5, 3), true),
+            (Two::added_sizes(1, 2), true),
+            (Two::subtracted_sizes(0, 3, 2), true),
+            (Two::multiplied_sizes(4, -1), true),
+            (Two::min_size(1, 2), true),
+            (Two::max_size(1, 2), true),
+            (Two::linear_on_diagonal(500, 1, 20), true),
+            (Two::quadratic_in_y(1000, -90, 1), true),
+            (Two::quadratic_in_y(0, 90, -1), true),
+            (
+                Two::const_above_diagonal_into_multiplied_sizes(3, 10, 2),
+                true,
+            ),
+            (
+                Two::const_above_diagonal_into_multiplied_sizes(3, 1000, -2),
+                true,
+            ),
+            (Two::with_interaction(10, 5, -3, 1), true),
+            (
+                Two::const_above_diagonal_into_quadratic_x_and_y(
+                    85848, 85848, 123203, 7305, -900, 1716, 960, 57,
+                ),
+                false,
+            ),
+            (
+                Two::const_above_diagonal_into_quadratic_x_and_y(
+                    0, -1_000_000, 5000, 100, -5, 600, 2, -4,
+                ),
+                false,
+            ),
+            (
+                Two::const_above_diagonal_into_quadratic_x_and_y(
+                    0, -1_000_000, 5000, -100, 5, -600, 2, 4,
+                ),
+                false,
+            ),
+        ];
+
+        let ends = [0, 3, 40, 100];
+
+        let ranges: Vec<(i64, i64)> = ends
+            .iter()
+            .flat_map(|min| {
+                ends.iter()
+                    .filter(move |max| min <= *max)
+                    .map(move |max| (*min, *max))
+            })
+            .collect();
+
+        for (shape, exact) in &shapes {
+            for x in &ranges {
+                for y in &ranges {
+                    let costs: Vec<i64> = (x.0..=x.1)
+                        .flat_map(|a| (y.0..=y.1).map(move |b| shape.cost([a, b])))
+                        .collect();
+
+                    let cheapest = *costs.iter().min().unwrap();
+                    let dearest = *costs.iter().max().unwrap();
+
+                    let (lower, upper) = shape.bounds([*x, *y]).unwrap();
+
+                    assert!(
+                        lower <= cheapest && dearest <= upper,
+                        "{shape} over {x:?} and {y:?}: {lower}..{upper} misses {cheapest}..{dearest}"
+                    );
+
+                    if *exact {
+                        assert_eq!((lower, upper), (cheapest, dearest), "{shape}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_give_up_when_costs_may_saturate() {
+        let divide = Two::const_above_diagonal_into_quadratic_x_and_y(
+            85848, 85848, 123203, 7305, -900, 1716, 960, 57,
+        );
+
+        assert_eq!(divide.bounds([(0, MAX), (0, MAX)]), None);
+        assert_eq!(divide.bounds([(0, 10), (11, MAX)]), Some((85848, 85848)));
+        assert_eq!(
+            Two::multiplied_sizes(0, 1).bounds([(0, MAX), (0, MAX)]),
+            Some((0, MAX))
+        );
+        assert_eq!(One::quadratic_cost(0, -1, 1).bounds([(0, MAX)]), None);
+    }
+
+    #[test]
+    fn three_arguments_saturate() {
+        assert_eq!(Three::constant_cost(MAX).cost([MAX, MAX, MAX]), MAX);
+        assert_eq!(Three::linear_in_x(1, 2).cost([MAX, 0, 0]), MAX);
+        assert_eq!(Three::linear_in_y(1, 2).cost([0, MAX, 0]), MAX);
+        assert_eq!(Three::linear_in_z(1, 2).cost([0, 0, MAX]), MAX);
+        assert_eq!(Three::quadratic_in_z(0, 0, 1).cost([0, 0, 1 << 32]), MAX);
+        assert_eq!(
+            Three::literal_in_y_or_linear_in_z(1, 2).cost([0, 0, MAX]),
+            MAX
+        );
+        assert_eq!(
+            Three::literal_in_y_or_linear_in_z(1, 2).cost([0, MAX, MAX]),
+            MAX
+        );
+        assert_eq!(Three::linear_in_y_and_z(0, 1, 1).cost([0, MAX, 1]), MAX);
+        assert_eq!(Three::linear_in_y_and_z(0, -1, -1).cost([0, MAX, 2]), MIN);
+        assert_eq!(Three::linear_in_max_y_z(1, 1).cost([0, 1, MAX]), MAX);
+
+        let exp_mod = Three::exp_mod_cost(607153, 231697, 53144);
+
+        assert_eq!(exp_mod.cost([1, 1 << 20, 1 << 20]), MAX);
+        assert_eq!(exp_mod.cost([MAX, 1 << 20, 1 << 20]), MAX);
+        assert_eq!(exp_mod.cost([1, 1, 1]), 607153 + 231697 + 53144);
+        assert_eq!(
+            exp_mod.cost([3, 1, 1]),
+            (607153 + 231697 + 53144) + (607153 + 231697 + 53144) / 2
+        );
+    }
+
+    #[test]
+    fn six_arguments_saturate() {
+        assert_eq!(Six::constant_cost(MAX).cost([MAX; 6]), MAX);
+    }
 }
@@ -0,0 +1,52 @@
+use crate::machine::PlutusVersion;
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum CostModelError {
+    #[error("Invalid cost model JSON: {0}")]
+    Json(String),
+    #[error("Cost model parameter '{0}' is not a 64-bit integer")]
+    InvalidValue(String),
+    #[error("Unknown cost model parameter '{name}' for {version:?}")]
+    UnknownParameter {
+        version: PlutusVersion,
+        name: String,
+    },
+    #[error("Cost model parameter '{0}' is given more than once")]
+    DuplicateParameter(String),
+    #[error("Missing cost model parameters for {version:?}: {}", names.join(", "))]
+    MissingParameters {
+        version: PlutusVersion,
+        names: Vec<String>,
+    },
+    #[error(
+        "Too many cost model parameters for {version:?}: expected at most {expected}, got {given}"
+    )]
+    ExtraParameters {
+        version: PlutusVersion,
+        expected: usize,
+        given: usize,
+    },
+    #[error("Cost model parameter '{name}' is out of range: {value}")]
+    OutOfRange { name: String, value: i64 },
+    #[error("{version:?} is not available at protocol version {protocol_version}")]
+    UnavailableVersion {
+        version: PlutusVersion,
+        protocol_version: u32,
+    },
+    #[error(
+        "No mainnet parameters of {version:?} at protocol version {protocol_version} are shipped"
+    )]
+    UnshippedParameters {
+        version: PlutusVersion,
+        protocol_version: u32,
+    },
+    #[error("Costing function '{path}' of {builtin} is declared {declared}, expected {expected}")]
+    ShapeMismatch {
+        builtin: String,
+        path: String,
+        declared: String,
+        expected: String,
+    },
+    #[error("Unknown entry '{0}' is neither a builtin nor a machine step")]
+    UnknownEntry(String),
+}
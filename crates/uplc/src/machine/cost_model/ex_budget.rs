@@ -19,9 +19,11 @@ impl ExBudget {
         Self::machine_max()
     }
 
+    /// Scale the budget to `n` occurrences, saturating like the Haskell
+    /// `CostingInteger`.
     pub fn occurrences(&mut self, n: i64) {
-        self.mem *= n;
-        self.cpu *= n;
+        self.mem = self.mem.saturating_mul(n);
+        self.cpu = self.cpu.saturating_mul(n);
     }
 
     pub fn machine() -> Self {
@@ -111,8 +113,33 @@ impl std::ops::Sub for ExBudget {
 
     fn sub(self, rhs: Self) -> Self::Output {
         ExBudget {
-            mem: self.mem - rhs.mem,
-            cpu: self.cpu - rhs.cpu,
+            mem: self.mem.saturating_sub(rhs.mem),
+            cpu: self.cpu.saturating_sub(rhs.cpu),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ExBudget;
+
+    #[test]
+    fn occurrences_saturate() {
+        let mut budget = ExBudget::new(100, 16000);
+
+        budget.occurrences(i64::MAX / 100 + 1);
+
+        assert_eq!(budget, ExBudget::new(i64::MAX, i64::MAX));
+
+        let mut budget = ExBudget::new(100, 16000);
+
+        budget.occurrences(3);
+
+        assert_eq!(budget, ExBudget::new(300, 48000));
+
+        assert_eq!(
+            ExBudget::new(i64::MIN, 0) - ExBudget::new(1, i64::MIN),
+            ExBudget::new(i64::MIN, i64::MAX)
+        );
+    }
+}
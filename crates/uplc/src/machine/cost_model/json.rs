@@ -0,0 +1,698 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::{
+    arena::Arena,
+    machine::{ExBudget, PlutusVersion},
+    syn::builtin_from_str,
+};
+
+use super::{
+    builtin_costs::{BuiltinCostModel, BuiltinCosting},
+    cost_map::{has_entry, normalise, parameter_names, CostMap},
+    costing::{OneArgument, SixArguments, ThreeArguments, TwoArguments},
+    machine_costs::MachineCosts,
+    CostModelError, StepKind,
+};
+
+const MACHINE_STEPS: [(&str, Option<StepKind>); 10] = [
+    ("cekStartupCost", None),
+    ("cekVarCost", Some(StepKind::Var)),
+    ("cekConstCost", Some(StepKind::Constant)),
+    ("cekLamCost", Some(StepKind::Lambda)),
+    ("cekDelayCost", Some(StepKind::Delay)),
+    ("cekForceCost", Some(StepKind::Force)),
+    ("cekApplyCost", Some(StepKind::Apply)),
+    ("cekBuiltinCost", Some(StepKind::Builtin)),
+    ("cekConstrCost", Some(StepKind::Constr)),
+    ("cekCaseCost", Some(StepKind::Case)),
+];
+
+/// Load a flat `{ "name": value }` object of named parameters.
+pub(crate) fn from_named(version: &PlutusVersion, json: &str) -> Result<CostMap, CostModelError> {
+    let object = parse_object(json)?;
+
+    let params = object
+        .iter()
+        .map(|(name, value)| {
+            value
+                .as_i64()
+                .map(|value| (name.as_str(), value))
+                .ok_or_else(|| CostModelError::InvalidValue(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    CostMap::from_named(version, params)
+}
+
+/// Load the `builtinCostModel*.json` and `cekMachineCosts*.json` files from
+/// the Plutus repository.
+///
+/// Both files describe every builtin and machine step known to Plutus, so
+/// entries that `version` does not take parameters for are skipped. Entries
+/// that are not builtins or machine steps at all are rejected.
+///
+/// The declared costing function shapes are returned by path, to be checked
+/// with [`check_shapes`] once the model is built.
+pub(crate) fn from_plutus(
+    version: &PlutusVersion,
+    builtin_costs: &str,
+    machine_costs: &str,
+) -> Result<(CostMap, Vec<(String, String)>), CostModelError> {
+    let arena = Arena::new();
+
+    let mut params = Vec::new();
+    let mut declared = Vec::new();
+
+    for json in [builtin_costs, machine_costs] {
+        for (entry, value) in parse_object(json)? {
+            if has_entry(version, &entry) {
+                shapes(entry.clone(), &value, &mut declared);
+                flatten(entry, &value, &mut params)?;
+            } else if builtin_from_str(&arena, &entry).is_none()
+                && !MACHINE_STEPS.iter().any(|(step, _)| *step == entry)
+            {
+                return Err(CostModelError::UnknownEntry(entry));
+            }
+        }
+    }
+
+    let cost_map = CostMap::from_named(
+        version,
+        params.iter().map(|(name, value)| (name.as_str(), *value)),
+    )?;
+
+    Ok((cost_map, declared))
+}
+
+/// Check the costing function shapes declared in a Plutus JSON file against
+/// those of the model built from it, laid out by [`builtin_costs`]. Shapes
+/// for builtins the model does not cost are not checked.
+pub(crate) fn check_shapes(
+    declared: &[(String, String)],
+    builtin_costs: &Value,
+) -> Result<(), CostModelError> {
+    let mut expected = Vec::new();
+
+    for (entry, value) in builtin_costs.as_object().into_iter().flatten() {
+        shapes(entry.clone(), value, &mut expected);
+    }
+
+    let expected: HashMap<String, String> = expected
+        .into_iter()
+        .map(|(path, shape)| (normalise(&path), shape))
+        .collect();
+
+    for (path, shape) in declared {
+        match expected.get(&normalise(path)) {
+            Some(expected) if expected != shape => {
+                return Err(CostModelError::ShapeMismatch {
+                    builtin: path.split('-').next().unwrap_or_default().to_string(),
+                    path: path.clone(),
+                    declared: shape.clone(),
+                    expected: expected.clone(),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Lay out builtin costs like Plutus' `builtinCostModel*.json`.
+pub(crate) fn builtin_costs<B: BuiltinCostModel>(builtin_costs: &B) -> Value {
+    let object = builtin_costs
+        .costings()
+        .into_iter()
+        .map(|(builtin, costing)| {
+            let (mem, cpu) = match costing {
+                BuiltinCosting::One(c) => (one_argument(&c.mem), one_argument(&c.cpu)),
+                BuiltinCosting::Two(c) => (two_arguments(&c.mem), two_arguments(&c.cpu)),
+                BuiltinCosting::Three(c) => (three_arguments(&c.mem), three_arguments(&c.cpu)),
+                BuiltinCosting::Six(c) => (six_arguments(&c.mem), six_arguments(&c.cpu)),
+            };
+
+            (
+                builtin.name().to_string(),
+                json!({ "cpu": cpu, "memory": mem }),
+            )
+        })
+        .collect();
+
+    Value::Object(object)
+}
+
+/// Lay out machine costs like Plutus' `cekMachineCosts*.json`.
+pub(crate) fn machine_costs(startup: ExBudget, machine_costs: &MachineCosts) -> Value {
+    let object = MACHINE_STEPS
+        .into_iter()
+        .map(|(step, kind)| {
+            let budget = match kind {
+                Some(kind) => machine_costs.get(kind as usize),
+                None => startup,
+            };
+
+            (
+                step.to_string(),
+                json!({ "exBudgetCPU": budget.cpu, "exBudgetMemory": budget.mem }),
+            )
+        })
+        .collect();
+
+    Value::Object(object)
+}
+
+/// The positional parameters of `version`, named, read back out of the
+/// Plutus JSON layout. Parameters that `version` accepts without costing
+/// anything with them, like the V2 integer/bytestring conversions, are left
+/// out.
+pub(crate) fn named_params(
+    version: &PlutusVersion,
+    builtin_costs: &Value,
+    machine_costs: &Value,
+) -> Vec<(&'static str, i64)> {
+    let mut params = Vec::new();
+
+    for object in [builtin_costs, machine_costs] {
+        for (entry, value) in object.as_object().into_iter().flatten() {
+            flatten(entry.clone(), value, &mut params)
+                .expect("exported cost models only hold integers");
+        }
+    }
+
+    let values: HashMap<String, i64> = params
+        .into_iter()
+        .map(|(name, value)| (normalise(&name), value))
+        .collect();
+
+    parameter_names(version, usize::MAX)
+        .into_iter()
+        .map_while(|name| Some((name, *values.get(&normalise(name))?)))
+        .collect()
+}
+
+pub(super) fn one_argument(costing: &OneArgument) -> Value {
+    match costing {
+        OneArgument::ConstantCost(c) => shape("constant_cost", json!(c)),
+        OneArgument::LinearCost(l) => shape(
+            "linear_in_x",
+            json!({ "intercept": l.intercept, "slope": l.slope }),
+        ),
+        OneArgument::Quadratic(q) => shape(
+            "quadratic_in_x",
+            json!({ "c0": q.coeff_0, "c1": q.coeff_1, "c2": q.coeff_2 }),
+        ),
+    }
+}
+
+pub(super) fn two_arguments(costing: &TwoArguments) -> Value {
+    let linear =
+        |kind, intercept, slope| shape(kind, json!({ "intercept": intercept, "slope": slope }));
+
+    match costing {
+        TwoArguments::ConstantCost(c) => shape("constant_cost", json!(c)),
+        TwoArguments::LinearInX(l) => linear("linear_in_x", l.intercept, l.slope),
+        TwoArguments::LinearInY(l) => linear("linear_in_y", l.intercept, l.slope),
+        TwoArguments::AddedSizes(s) => linear("added_sizes", s.intercept, s.slope),
+        TwoArguments::SubtractedSizes(s) => shape(
+            "subtracted_sizes",
+            json!({ "intercept": s.intercept, "slope": s.slope, "minimum": s.minimum }),
+        ),
+        TwoArguments::MultipliedSizes(s) => linear("multiplied_sizes", s.intercept, s.slope),
+        TwoArguments::MinSize(s) => linear("min_size", s.intercept, s.slope),
+        TwoArguments::MaxSize(s) => linear("max_size", s.intercept, s.slope),
+        TwoArguments::LinearOnDiagonal(l) => shape(
+            "linear_on_diagonal",
+            json!({ "constant": l.constant, "intercept": l.intercept, "slope": l.slope }),
+        ),
+        TwoArguments::QuadraticInY(q) => shape(
+            "quadratic_in_y",
+            json!({ "c0": q.coeff_0, "c1": q.coeff_1, "c2": q.coeff_2 }),
+        ),
+        TwoArguments::ConstAboveDiagonalIntoQuadraticXAndY(constant, q) => shape(
+            "const_above_diagonal",
+            json!({
+                "constant": constant,
+                "model": shape(
+                    "quadratic_in_x_and_y",
+                    json!({
+                        "minimum": q.minimum,
+                        "c00": q.coeff_00,
+                        "c10": q.coeff_10,
+                        "c01": q.coeff_01,
+                        "c20": q.coeff_20,
+                        "c11": q.coeff_11,
+                        "c02": q.coeff_02,
+                    }),
+                ),
+            }),
+        ),
+        TwoArguments::ConstAboveDiagonalIntoMultipliedSizes(constant, s) => shape(
+            "const_above_diagonal",
+            json!({
+                "constant": constant,
+                "model": linear("multiplied_sizes", s.intercept, s.slope),
+            }),
+        ),
+        TwoArguments::WithInteraction(w) => shape(
+            "with_interaction",
+            json!({ "c00": w.c00, "c10": w.c10, "c01": w.c01, "c11": w.c11 }),
+        ),
+    }
+}
+
+pub(super) fn three_arguments(costing: &ThreeArguments) -> Value {
+    let linear =
+        |kind, intercept, slope| shape(kind, json!({ "intercept": intercept, "slope": slope }));
+
+    match costing {
+        ThreeArguments::ConstantCost(c) => shape("constant_cost", json!(c)),
+        ThreeArguments::LinearInX(l) => linear("linear_in_x", l.intercept, l.slope),
+        ThreeArguments::LinearInY(l) => linear("linear_in_y", l.intercept, l.slope),
+        ThreeArguments::LinearInZ(l) => linear("linear_in_z", l.intercept, l.slope),
+        ThreeArguments::QuadraticInZ(q) => shape(
+            "quadratic_in_z",
+            json!({ "c0": q.coeff_0, "c1": q.coeff_1, "c2": q.coeff_2 }),
+        ),
+        ThreeArguments::LiteralInYorLinearInZ(l) => {
+            linear("literal_in_y_or_linear_in_z", l.intercept, l.slope)
+        }
+        ThreeArguments::LinearInYAndZ(l) => shape(
+            "linear_in_y_and_z",
+            json!({ "intercept": l.intercept, "slope1": l.slope1, "slope2": l.slope2 }),
+        ),
+        ThreeArguments::LinearInMaxYZ(l) => linear("linear_in_max_yz", l.intercept, l.slope),
+        ThreeArguments::ExpModCost(c) => shape(
+            "exp_mod_cost",
+            json!({
+                "coefficient00": c.coeff_00,
+                "coefficient11": c.coeff_11,
+                "coefficient12": c.coeff_12,
+            }),
+        ),
+    }
+}
+
+pub(super) fn six_arguments(costing: &SixArguments) -> Value {
+    match costing {
+        SixArguments::ConstantCost(c) => shape("constant_cost", json!(c)),
+    }
+}
+
+fn shape(kind: &str, arguments: Value) -> Value {
+    json!({ "arguments": arguments, "type": kind })
+}
+
+fn parse_object(json: &str) -> Result<Map<String, Value>, CostModelError> {
+    match serde_json::from_str(json) {
+        Ok(Value::Object(object)) => Ok(object),
+        Ok(_) => Err(CostModelError::Json("expected an object".to_string())),
+        Err(e) => Err(CostModelError::Json(e.to_string())),
+    }
+}
+
+/// The `type` of every costing function in `value`, by its path.
+fn shapes(path: String, value: &Value, shapes_by_path: &mut Vec<(String, String)>) {
+    for (key, value) in value.as_object().into_iter().flatten() {
+        match value {
+            Value::String(shape) if key == "type" => {
+                shapes_by_path.push((path.clone(), shape.clone()))
+            }
+            _ => shapes(format!("{path}-{key}"), value, shapes_by_path),
+        }
+    }
+}
+
+/// Join the path to every number with `-`, which is how Plutus derives
+/// parameter names. The `type` of each costing function is checked
+/// separately, see [`check_shapes`].
+fn flatten(
+    path: String,
+    value: &Value,
+    params: &mut Vec<(String, i64)>,
+) -> Result<(), CostModelError> {
+    match value {
+        Value::Number(number) => {
+            let number = number
+                .as_i64()
+                .ok_or_else(|| CostModelError::InvalidValue(path.clone()))?;
+
+            params.push((path, number));
+        }
+        Value::Object(object) => {
+            for (key, value) in object {
+                if key == "type" && value.is_string() {
+                    continue;
+                }
+
+                flatten(format!("{path}-{key}"), value, params)?;
+            }
+        }
+        _ => return Err(CostModelError::InvalidValue(path)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use crate::{
+        builtin::DefaultFunction,
+        machine::{
+            cost_model::{
+                builtin_costs::BuiltinCostModel,
+                builtin_costs::{
+                    builtin_costs_v1::BuiltinCostsV1, builtin_costs_v2::BuiltinCostsV2,
+                    builtin_costs_v3::BuiltinCostsV3,
+                },
+                cost_map::{normalise, parameter_names},
+            },
+            CostModel, CostModelError, PlutusVersion,
+        },
+    };
+
+    fn camel_case(name: &str) -> String {
+        let mut parts = name.split('_');
+
+        let mut camel = parts.next().unwrap_or_default().to_string();
+
+        for part in parts {
+            let mut chars = part.chars();
+
+            camel.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+            camel.push_str(chars.as_str());
+        }
+
+        camel
+    }
+
+    /// Lay out positional parameters the way the Plutus JSON files do, with
+    /// the costing function shapes of V1.
+    fn plutus_json(version: &PlutusVersion, values: &[i64]) -> (Value, Value) {
+        let shapes = CostModel::<BuiltinCostsV1>::default().builtin_costs_json();
+
+        let shape = |entry: &str, budget: &str| {
+            shapes
+                .as_object()
+                .unwrap()
+                .iter()
+                .find(|(name, _)| normalise(name) == normalise(entry))
+                .map(|(_, costs)| costs[budget]["type"].clone())
+                .unwrap()
+        };
+
+        let mut builtins = Map::new();
+        let mut machine = Map::new();
+
+        for (name, value) in parameter_names(version, values.len()).iter().zip(values) {
+            let mut segments = name.split('-');
+
+            let entry = camel_case(segments.next().unwrap());
+
+            let target = if entry.starts_with("cek") {
+                &mut machine
+            } else {
+                &mut builtins
+            };
+
+            let mut node = target.entry(entry.clone()).or_insert_with(|| json!({}));
+
+            let segments: Vec<_> = segments
+                .map(|segment| match segment {
+                    "mem" => "memory",
+                    "exBudgetmem" => "exBudgetMemory",
+                    segment => segment,
+                })
+                .collect();
+
+            for (i, segment) in segments.iter().enumerate() {
+                if i == 1 {
+                    node["type"] = shape(&entry, segments[0]);
+                }
+
+                if i + 1 == segments.len() {
+                    node[*segment] = json!(value);
+                } else {
+                    node = node
+                        .as_object_mut()
+                        .unwrap()
+                        .entry(*segment)
+                        .or_insert_with(|| json!({}));
+                }
+            }
+        }
+
+        (Value::Object(builtins), Value::Object(machine))
+    }
+
+    #[test]
+    fn named_params_match_positional() {
+        let version = PlutusVersion::V2;
+
+        let names = parameter_names(&version, 0);
+
+        let values: Vec<i64> = (0..names.len() as i64).collect();
+
+        let json: Map<String, Value> = names
+            .iter()
+            .zip(&values)
+            .map(|(name, value)| {
+                let (entry, rest) = name.split_once('-').unwrap();
+
+                (format!("{}-{rest}", camel_case(entry)), json!(value))
+            })
+            .collect();
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV2>::from_named_json(
+                &version,
+                &Value::Object(json).to_string()
+            ),
+            CostModel::initialize_cost_model(&version, &values)
+        );
+    }
+
+    #[test]
+    fn named_params_errors() {
+        let version = PlutusVersion::V1;
+
+        let names = parameter_names(&version, 0);
+
+        let mut params: Vec<(&str, i64)> = names.iter().map(|name| (*name, 1)).collect();
+
+        params.pop();
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_named_params(&version, params.clone()),
+            Err(CostModelError::MissingParameters {
+                version,
+                names: vec![names[names.len() - 1].to_string()],
+            })
+        );
+
+        params.push(("addInteger-cpu-arguments-intercept", 1));
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_named_params(&version, params.clone()),
+            Err(CostModelError::DuplicateParameter(
+                "addInteger-cpu-arguments-intercept".to_string()
+            ))
+        );
+
+        params.push(("serialiseData-cpu-arguments-intercept", 1));
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_named_params(&version, params[1..].to_vec()),
+            Err(CostModelError::UnknownParameter {
+                version,
+                name: "serialiseData-cpu-arguments-intercept".to_string(),
+            })
+        );
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_named_json(
+                &version,
+                r#"{"addInteger-cpu-arguments-intercept": 1.5}"#
+            ),
+            Err(CostModelError::InvalidValue(
+                "addInteger-cpu-arguments-intercept".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn v3_extension_is_all_or_nothing() {
+        let version = PlutusVersion::V3;
+
+        let names = parameter_names(&version, 0);
+
+        let mut params: Vec<(&str, i64)> = names.iter().map(|name| (*name, 1)).collect();
+
+        assert!(CostModel::<BuiltinCostsV3>::from_named_params(&version, params.clone()).is_ok());
+
+        params.push(("readBit-cpu-arguments", 1));
+
+        assert!(matches!(
+            CostModel::<BuiltinCostsV3>::from_named_params(&version, params),
+            Err(CostModelError::MissingParameters { names, .. }) if names.len() == 45
+        ));
+    }
+
+    #[test]
+    fn exports_round_trip() {
+        fn round_trip<B: BuiltinCostModel + PartialEq + std::fmt::Debug>() {
+            let version = B::PLUTUS_VERSION;
+
+            let names = match version {
+                PlutusVersion::V2 => parameter_names(&version, 0),
+                _ => parameter_names(&version, usize::MAX),
+            };
+
+            let values: Vec<i64> = (0..names.len() as i64).collect();
+
+            let model = CostModel::<B>::initialize_cost_model(&version, &values).unwrap();
+
+            let named = model.to_named_params();
+
+            assert_eq!(named, names.into_iter().zip(values).collect::<Vec<_>>());
+
+            assert_eq!(
+                CostModel::<B>::from_named_params(&version, named).as_ref(),
+                Ok(&model)
+            );
+
+            assert_eq!(
+                CostModel::<B>::from_plutus_json(
+                    &version,
+                    &model.builtin_costs_json().to_string(),
+                    &model.machine_costs_json().to_string(),
+                )
+                .as_ref(),
+                Ok(&model)
+            );
+        }
+
+        round_trip::<BuiltinCostsV1>();
+        round_trip::<BuiltinCostsV2>();
+        round_trip::<BuiltinCostsV3>();
+    }
+
+    #[test]
+    fn formulas() {
+        let model = CostModel::<BuiltinCostsV3>::default();
+
+        let formula = |builtin| {
+            model
+                .formulas()
+                .into_iter()
+                .find(|formula| formula.builtin == builtin)
+                .unwrap()
+        };
+
+        let add_integer = formula(DefaultFunction::AddInteger);
+
+        assert_eq!(add_integer.cpu, "100788 + 420 * max(x, y)");
+        assert_eq!(add_integer.mem, "1 + max(x, y)");
+
+        assert_eq!(
+            formula(DefaultFunction::DivideInteger).cpu,
+            "if x < y then 85848 else max(85848, 123203 + 1716 * x + 7305 * y + 57 * x^2 + 960 * x * y - 900 * y^2)"
+        );
+
+        assert_eq!(
+            formula(DefaultFunction::IntegerToByteString).mem,
+            "if y == 0 then z else y"
+        );
+    }
+
+    #[test]
+    fn plutus_json_matches_positional() {
+        let version = PlutusVersion::V1;
+
+        let values: Vec<i64> = (0..parameter_names(&version, 0).len() as i64).collect();
+
+        let (mut builtins, mut machine) = plutus_json(&version, &values);
+
+        // Known to Plutus but not costed in V1.
+        builtins["serialiseData"] = json!({ "cpu": { "arguments": 1, "type": "constant_cost" } });
+        machine["cekConstrCost"] = json!({ "exBudgetCPU": 1, "exBudgetMemory": 1 });
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_plutus_json(
+                &version,
+                &builtins.to_string(),
+                &machine.to_string()
+            ),
+            CostModel::initialize_cost_model(&version, &values)
+        );
+
+        builtins["fooBar"] = json!({});
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_plutus_json(
+                &version,
+                &builtins.to_string(),
+                &machine.to_string()
+            ),
+            Err(CostModelError::UnknownEntry("fooBar".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_shapes() {
+        let version = PlutusVersion::V1;
+
+        let values: Vec<i64> = (0..parameter_names(&version, 0).len() as i64).collect();
+
+        let (mut builtins, machine) = plutus_json(&version, &values);
+
+        assert_eq!(builtins["addInteger"]["cpu"]["type"], json!("max_size"));
+
+        builtins["addInteger"]["cpu"]["type"] = json!("added_sizes");
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV1>::from_plutus_json(
+                &version,
+                &builtins.to_string(),
+                &machine.to_string()
+            ),
+            Err(CostModelError::ShapeMismatch {
+                builtin: "addInteger".to_string(),
+                path: "addInteger-cpu".to_string(),
+                declared: "added_sizes".to_string(),
+                expected: "max_size".to_string(),
+            })
+        );
+
+        builtins["addInteger"]["cpu"]["type"] = json!("max_size");
+        builtins["divideInteger"]["cpu"]["arguments"]["model"]["type"] = json!("added_sizes");
+
+        assert!(matches!(
+            CostModel::<BuiltinCostsV1>::from_plutus_json(
+                &version,
+                &builtins.to_string(),
+                &machine.to_string()
+            ),
+            Err(CostModelError::ShapeMismatch { builtin, path, .. })
+                if builtin == "divideInteger" && path == "divideInteger-cpu-arguments-model"
+        ));
+    }
+
+    #[test]
+    fn loads_plutus_v3_files() {
+        let version = PlutusVersion::V3;
+
+        assert_eq!(
+            CostModel::<BuiltinCostsV3>::from_plutus_json(
+                &version,
+                include_str!("../../../tests/cost_models/builtinCostModelC.json"),
+                include_str!("../../../tests/cost_models/cekMachineCostsC.json"),
+            ),
+            Ok(CostModel::<BuiltinCostsV3>::default())
+        );
+    }
+}
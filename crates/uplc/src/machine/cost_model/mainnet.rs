@@ -0,0 +1,258 @@
+use crate::machine::PlutusVersion;
+
+use super::CostModelError;
+
+/// The mainnet parameters of `version` at `protocol_version`.
+///
+/// V1 and V2 have had the same parameters since Vasil (PV 7), V2 being
+/// extended with the integer/bytestring conversions at Plomin (PV 10). V3
+/// came with the Chang parameters (PV 9) and was extended at Plomin. PV 11
+/// keeps the Plomin parameters: the builtins it adds have fixed costs that
+/// no parameter sets yet.
+///
+/// A version that did not exist yet at `protocol_version` is
+/// [`CostModelError::UnavailableVersion`]; older or later parameter sets that
+/// are not shipped, such as the Alonzo V1 ones, are
+/// [`CostModelError::UnshippedParameters`].
+pub fn mainnet_params(
+    version: &PlutusVersion,
+    protocol_version: u32,
+) -> Result<&'static [i64], CostModelError> {
+    let params: &[i64] = match (version, protocol_version) {
+        (PlutusVersion::V1, 7..=11) => &PLUTUS_V1,
+        (PlutusVersion::V2, 7..=9) => &PLUTUS_V2,
+        (PlutusVersion::V2, 10..=11) => &PLUTUS_V2_PLOMIN,
+        (PlutusVersion::V3, 9) => &PLUTUS_V3_CHANG,
+        (PlutusVersion::V3, 10..=11) => &PLUTUS_V3,
+        (PlutusVersion::V1, ..5) | (PlutusVersion::V2, ..7) | (PlutusVersion::V3, ..9) => {
+            return Err(CostModelError::UnavailableVersion {
+                version: *version,
+                protocol_version,
+            })
+        }
+        _ => {
+            return Err(CostModelError::UnshippedParameters {
+                version: *version,
+                protocol_version,
+            })
+        }
+    };
+
+    Ok(params)
+}
+
+pub(crate) const PLUTUS_V1: [i64; 166] = [
+    100788, 420, 1, 1, 1000, 173, 0, 1, 1000, 59957, 4, 1, 11183, 32, 201305, 8356, 4, 16000, 100,
+    16000, 100, 16000, 100, 16000, 100, 16000, 100, 16000, 100, 100, 100, 16000, 100, 94375, 32,
+    132994, 32, 61462, 4, 72010, 178, 0, 1, 22151, 32, 91189, 769, 4, 2, 85848, 228465, 122, 0, 1,
+    1, 1000, 42921, 4, 2, 24548, 29498, 38, 1, 898148, 27279, 1, 51775, 558, 1, 39184, 1000, 60594,
+    1, 141895, 32, 83150, 32, 15299, 32, 76049, 1, 13169, 4, 22100, 10, 28999, 74, 1, 28999, 74, 1,
+    43285, 552, 1, 44749, 541, 1, 33852, 32, 68246, 32, 72362, 32, 7243, 32, 7391, 32, 11546, 32,
+    85848, 228465, 122, 0, 1, 1, 90434, 519, 0, 1, 74433, 32, 85848, 228465, 122, 0, 1, 1, 85848,
+    228465, 122, 0, 1, 1, 270652, 22588, 4, 1457325, 64566, 4, 20467, 1, 4, 0, 141992, 32, 100788,
+    420, 1, 1, 81663, 32, 59498, 32, 20142, 32, 24588, 32, 20744, 32, 25933, 32, 24623, 32,
+    53384111, 14333, 10,
+];
+
+pub(crate) const PLUTUS_V2: [i64; 175] = [
+    100788, 420, 1, 1, 1000, 173, 0, 1, 1000, 59957, 4, 1, 11183, 32, 201305, 8356, 4, 16000, 100,
+    16000, 100, 16000, 100, 16000, 100, 16000, 100, 16000, 100, 100, 100, 16000, 100, 94375, 32,
+    132994, 32, 61462, 4, 72010, 178, 0, 1, 22151, 32, 91189, 769, 4, 2, 85848, 228465, 122, 0, 1,
+    1, 1000, 42921, 4, 2, 24548, 29498, 38, 1, 898148, 27279, 1, 51775, 558, 1, 39184, 1000, 60594,
+    1, 141895, 32, 83150, 32, 15299, 32, 76049, 1, 13169, 4, 22100, 10, 28999, 74, 1, 28999, 74, 1,
+    43285, 552, 1, 44749, 541, 1, 33852, 32, 68246, 32, 72362, 32, 7243, 32, 7391, 32, 11546, 32,
+    85848, 228465, 122, 0, 1, 1, 90434, 519, 0, 1, 74433, 32, 85848, 228465, 122, 0, 1, 1, 85848,
+    228465, 122, 0, 1, 1, 955506, 213312, 0, 2, 270652, 22588, 4, 1457325, 64566, 4, 20467, 1, 4,
+    0, 141992, 32, 100788, 420, 1, 1, 81663, 32, 59498, 32, 20142, 32, 24588, 32, 20744, 32, 25933,
+    32, 24623, 32, 43053543, 10, 53384111, 14333, 10, 43574283, 26308, 10,
+];
+
+pub(crate) const PLUTUS_V2_PLOMIN: [i64; 185] = [
+    100788, 420, 1, 1, 1000, 173, 0, 1, 1000, 59957, 4, 1, 11183, 32, 201305, 8356, 4, 16000, 100,
+    16000, 100, 16000, 100, 16000, 100, 16000, 100, 16000, 100, 100, 100, 16000, 100, 94375, 32,
+    132994, 32, 61462, 4, 72010, 178, 0, 1, 22151, 32, 91189, 769, 4, 2, 85848, 228465, 122, 0, 1,
+    1, 1000, 42921, 4, 2, 24548, 29498, 38, 1, 898148, 27279, 1, 51775, 558, 1, 39184, 1000, 60594,
+    1, 141895, 32, 83150, 32, 15299, 32, 76049, 1, 13169, 4, 22100, 10, 28999, 74, 1, 28999, 74, 1,
+    43285, 552, 1, 44749, 541, 1, 33852, 32, 68246, 32, 72362, 32, 7243, 32, 7391, 32, 11546, 32,
+    85848, 228465, 122, 0, 1, 1, 90434, 519, 0, 1, 74433, 32, 85848, 228465, 122, 0, 1, 1, 85848,
+    228465, 122, 0, 1, 1, 955506, 213312, 0, 2, 270652, 22588, 4, 1457325, 64566, 4, 20467, 1, 4,
+    0, 141992, 32, 100788, 420, 1, 1, 81663, 32, 59498, 32, 20142, 32, 24588, 32, 20744, 32, 25933,
+    32, 24623, 32, 43053543, 10, 53384111, 14333, 10, 43574283, 26308, 10, 1293828, 28716, 63, 0,
+    1, 1006041, 43623, 251, 0, 1,
+];
+
+pub(crate) const PLUTUS_V3_CHANG: [i64; 251] = [
+    100788, 420, 1, 1, 1000, 173, 0, 1, 1000, 59957, 4, 1, 11183, 32, 201305, 8356, 4, 16000, 100,
+    16000, 100, 16000, 100, 16000, 100, 16000, 100, 16000, 100, 100, 100, 16000, 100, 94375, 32,
+    132994, 32, 61462, 4, 72010, 178, 0, 1, 22151, 32, 91189, 769, 4, 2, 85848, 123203, 7305, -900,
+    1716, 549, 57, 85848, 0, 1, 1, 1000, 42921, 4, 2, 24548, 29498, 38, 1, 898148, 27279, 1, 51775,
+    558, 1, 39184, 1000, 60594, 1, 141895, 32, 83150, 32, 15299, 32, 76049, 1, 13169, 4, 22100, 10,
+    28999, 74, 1, 28999, 74, 1, 43285, 552, 1, 44749, 541, 1, 33852, 32, 68246, 32, 72362, 32,
+    7243, 32, 7391, 32, 11546, 32, 85848, 123203, 7305, -900, 1716, 549, 57, 85848, 0, 1, 90434,
+    519, 0, 1, 74433, 32, 85848, 123203, 7305, -900, 1716, 549, 57, 85848, 0, 1, 1, 85848, 123203,
+    7305, -900, 1716, 549, 57, 85848, 0, 1, 955506, 213312, 0, 2, 270652, 22588, 4, 1457325, 64566,
+    4, 20467, 1, 4, 0, 141992, 32, 100788, 420, 1, 1, 81663, 32, 59498, 32, 20142, 32, 24588, 32,
+    20744, 32, 25933, 32, 24623, 32, 43053543, 10, 53384111, 14333, 10, 43574283, 26308, 10, 16000,
+    100, 16000, 100, 962335, 18, 2780678, 6, 442008, 1, 52538055, 3756, 18, 267929, 18, 76433006,
+    8868, 18, 52948122, 18, 1995836, 36, 3227919, 12, 901022, 1, 166917843, 4307, 36, 284546, 36,
+    158221314, 26549, 36, 74698472, 36, 333849714, 1, 254006273, 72, 2174038, 72, 2261318, 64571,
+    4, 207616, 8310, 4, 1293828, 28716, 63, 0, 1, 1006041, 43623, 251, 0, 1,
+];
+
+pub(crate) const PLUTUS_V3: [i64; 297] = [
+    100788, 420, 1, 1, 1000, 173, 0, 1, 1000, 59957, 4, 1, 11183, 32, 201305, 8356, 4, 16000, 100,
+    16000, 100, 16000, 100, 16000, 100, 16000, 100, 16000, 100, 100, 100, 16000, 100, 94375, 32,
+    132994, 32, 61462, 4, 72010, 178, 0, 1, 22151, 32, 91189, 769, 4, 2, 85848, 123203, 7305, -900,
+    1716, 960, 57, 85848, 0, 1, 1, 1000, 42921, 4, 2, 30623, 28755, 75, 1, 898148, 27279, 1, 51775,
+    558, 1, 39184, 1000, 60594, 1, 141895, 32, 83150, 32, 15299, 32, 76049, 1, 13169, 4, 22100, 10,
+    28999, 74, 1, 28999, 74, 1, 43285, 552, 1, 44749, 541, 1, 33852, 32, 68246, 32, 72362, 32,
+    7243, 32, 7391, 32, 11546, 32, 85848, 123203, 7305, -900, 1716, 960, 57, 85848, 0, 1, 90434,
+    519, 0, 1, 74433, 32, 85848, 123203, 7305, -900, 1716, 960, 57, 85848, 0, 1, 1, 85848, 123203,
+    7305, -900, 1716, 960, 57, 85848, 0, 1, 955506, 213312, 0, 2, 270652, 22588, 4, 1457325, 64566,
+    4, 20467, 1, 4, 0, 141992, 32, 100788, 420, 1, 1, 81663, 32, 59498, 32, 20142, 32, 24588, 32,
+    20744, 32, 25933, 32, 24623, 32, 43053543, 10, 53384111, 14333, 10, 43574283, 26308, 10, 16000,
+    100, 16000, 100, 962335, 18, 2780678, 6, 442008, 1, 52538055, 3756, 18, 267929, 18, 76433006,
+    8868, 18, 52948122, 18, 1995836, 36, 3227919, 12, 901022, 1, 166917843, 4307, 36, 284546, 36,
+    158221314, 26549, 36, 74698472, 36, 333849714, 1, 254006273, 72, 2174038, 72, 2261318, 64571,
+    4, 207616, 8310, 4, 1293828, 28716, 63, 0, 1, 1006041, 43623, 251, 0, 1, 100181, 726, 719, 0,
+    1, 100181, 726, 719, 0, 1, 100181, 726, 719, 0, 1, 107878, 680, 0, 1, 95336, 1, 281145, 18848,
+    0, 1, 180194, 159, 1, 1, 158519, 8942, 0, 1, 159378, 8813, 0, 1, 107490, 3298, 1, 106057, 655,
+    1, 1964219, 24520, 3,
+];
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::mainnet_params;
+
+    use crate::{
+        builtin::DefaultFunction,
+        machine::{
+            cost_model::builtin_costs::{
+                builtin_costs_v1::BuiltinCostsV1, builtin_costs_v2::BuiltinCostsV2,
+                builtin_costs_v3::BuiltinCostsV3, BuiltinCostModel,
+            },
+            cost_model::cost_map::MISSING_PARAMETER_COST,
+            CostModel, CostModelError, PlutusVersion,
+        },
+    };
+
+    fn param(named: &[(&str, i64)], name: &str) -> i64 {
+        named
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, value)| *value)
+            .unwrap()
+    }
+
+    #[test]
+    fn ships_mainnet_values() {
+        for protocol_version in [7, 8, 9, 10] {
+            let v1 = CostModel::<BuiltinCostsV1>::for_network(&PlutusVersion::V1, protocol_version)
+                .unwrap()
+                .to_named_params();
+            let v2 = CostModel::<BuiltinCostsV2>::for_network(&PlutusVersion::V2, protocol_version)
+                .unwrap()
+                .to_named_params();
+
+            assert_eq!(param(&v1, "add_integer-cpu-arguments-intercept"), 100788);
+            assert_eq!(param(&v1, "cek_startup_cost-exBudgetCPU"), 100);
+            assert_eq!(param(&v2, "serialise_data-cpu-arguments-intercept"), 955506);
+            assert_eq!(
+                param(&v2, "verify_ecdsa_secp256k1_signature-cpu-arguments"),
+                43053543
+            );
+        }
+
+        let v3 = CostModel::<BuiltinCostsV3>::for_network(&PlutusVersion::V3, 10).unwrap();
+
+        assert_eq!(
+            v3.builtin_costs
+                .get_cost(DefaultFunction::DivideInteger, &[1, 1]),
+            BuiltinCostsV3::default().get_cost(DefaultFunction::DivideInteger, &[1, 1])
+        );
+
+        let v3 = v3.to_named_params();
+
+        assert_eq!(param(&v3, "divide_integer-cpu-arguments-c00"), 123203);
+        assert_eq!(param(&v3, "integerToByteString-cpu-arguments-c0"), 1293828);
+        assert_eq!(param(&v3, "readBit-cpu-arguments"), 95336);
+    }
+
+    #[test]
+    fn ships_every_protocol_version() {
+        for (version, protocol_version, len) in [
+            (PlutusVersion::V1, 7, 166),
+            (PlutusVersion::V1, 9, 166),
+            (PlutusVersion::V1, 11, 166),
+            (PlutusVersion::V2, 7, 175),
+            (PlutusVersion::V2, 9, 175),
+            (PlutusVersion::V2, 10, 185),
+            (PlutusVersion::V2, 11, 185),
+            (PlutusVersion::V3, 9, 251),
+            (PlutusVersion::V3, 10, 297),
+            (PlutusVersion::V3, 11, 297),
+        ] {
+            assert_eq!(
+                mainnet_params(&version, protocol_version).map(<[i64]>::len),
+                Ok(len),
+                "{version:?} at protocol version {protocol_version}"
+            );
+        }
+
+        // Plomin raised the cost of division and equalsByteString in V3.
+        let chang = CostModel::<BuiltinCostsV3>::for_network(&PlutusVersion::V3, 9)
+            .unwrap()
+            .to_named_params();
+
+        assert_eq!(param(&chang, "divide_integer-cpu-arguments-c11"), 549);
+        assert_eq!(
+            param(&chang, "equals_byte_string-cpu-arguments-constant"),
+            24548
+        );
+        assert_eq!(
+            param(&chang, "readBit-cpu-arguments"),
+            MISSING_PARAMETER_COST
+        );
+
+        let plomin = CostModel::<BuiltinCostsV2>::for_network(&PlutusVersion::V2, 10).unwrap();
+
+        assert_eq!(
+            plomin,
+            CostModel::<BuiltinCostsV2>::for_network(&PlutusVersion::V2, 9).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_protocol_versions_without_data() {
+        for (version, protocol_version) in [
+            (PlutusVersion::V1, 4),
+            (PlutusVersion::V2, 6),
+            (PlutusVersion::V3, 8),
+        ] {
+            assert_eq!(
+                mainnet_params(&version, protocol_version),
+                Err(CostModelError::UnavailableVersion {
+                    version,
+                    protocol_version,
+                })
+            );
+        }
+
+        for (version, protocol_version) in [
+            (PlutusVersion::V1, 5),
+            (PlutusVersion::V1, 12),
+            (PlutusVersion::V2, 12),
+            (PlutusVersion::V3, 12),
+        ] {
+            assert_eq!(
+                mainnet_params(&version, protocol_version),
+                Err(CostModelError::UnshippedParameters {
+                    version,
+                    protocol_version,
+                })
+            );
+        }
+    }
+}
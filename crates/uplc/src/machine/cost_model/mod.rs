@@ -1,17 +1,39 @@
 pub mod builtin_costs;
+mod calibration;
 pub(crate) mod cost_map;
-mod costing;
+pub(crate) mod costing;
+mod error;
 pub mod ex_budget;
+mod json;
 mod machine_costs;
+mod mainnet;
 mod value;
 
+pub use calibration::{BuiltinCalibration, CalibrationConfig, CalibrationReport, Sample};
+pub use error::*;
+pub use mainnet::mainnet_params;
 pub use value::*;
 
-use crate::machine::{
-    cost_model::{builtin_costs::BuiltinCostModel, machine_costs::MachineCosts},
-    ExBudget, PlutusVersion,
+use crate::{
+    builtin::DefaultFunction,
+    machine::{
+        cost_model::{
+            builtin_costs::{BuiltinCostModel, BuiltinCosting},
+            cost_map::CostMap,
+            machine_costs::MachineCosts,
+        },
+        ExBudget, PlutusVersion,
+    },
 };
 
+/// The symbolic cost of one builtin, see [`CostModel::formulas`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuiltinFormula {
+    pub builtin: DefaultFunction,
+    pub mem: String,
+    pub cpu: String,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CostModel<B: BuiltinCostModel> {
     pub machine_startup: ExBudget,
@@ -20,15 +42,102 @@ pub struct CostModel<B: BuiltinCostModel> {
 }
 
 impl<B: BuiltinCostModel> CostModel<B> {
-    pub fn initialize_cost_model(version: &PlutusVersion, cost_model: &[i64]) -> CostModel<B> {
-        let cost_map = cost_map::CostMap::new(version, cost_model);
+    pub fn initialize_cost_model(
+        version: &PlutusVersion,
+        cost_model: &[i64],
+    ) -> Result<CostModel<B>, CostModelError> {
+        CostMap::new(version, cost_model).map(|cost_map| Self::from_cost_map(&cost_map))
+    }
+
+    /// The mainnet cost model of `version` at `protocol_version`, see
+    /// [`mainnet_params`].
+    pub fn for_network(
+        version: &PlutusVersion,
+        protocol_version: u32,
+    ) -> Result<CostModel<B>, CostModelError> {
+        Self::initialize_cost_model(version, mainnet_params(version, protocol_version)?)
+    }
+
+    /// Load a cost model from named parameters, as found in the ledger's
+    /// protocol parameters.
+    pub fn from_named_params<'k>(
+        version: &PlutusVersion,
+        params: impl IntoIterator<Item = (&'k str, i64)>,
+    ) -> Result<CostModel<B>, CostModelError> {
+        CostMap::from_named(version, params).map(|cost_map| Self::from_cost_map(&cost_map))
+    }
+
+    /// Load a cost model from a JSON object mapping parameter names to values.
+    pub fn from_named_json(
+        version: &PlutusVersion,
+        json: &str,
+    ) -> Result<CostModel<B>, CostModelError> {
+        json::from_named(version, json).map(|cost_map| Self::from_cost_map(&cost_map))
+    }
+
+    /// Load a cost model from the contents of Plutus' `builtinCostModel*.json`
+    /// and `cekMachineCosts*.json` files. The declared shape of every costing
+    /// function must be the one the model uses.
+    pub fn from_plutus_json(
+        version: &PlutusVersion,
+        builtin_costs: &str,
+        machine_costs: &str,
+    ) -> Result<CostModel<B>, CostModelError> {
+        let (cost_map, shapes) = json::from_plutus(version, builtin_costs, machine_costs)?;
+
+        let cost_model = Self::from_cost_map(&cost_map);
+
+        json::check_shapes(&shapes, &cost_model.builtin_costs_json())?;
+
+        Ok(cost_model)
+    }
+
+    /// Every positional parameter of the model, by name.
+    pub fn to_named_params(&self) -> Vec<(&'static str, i64)> {
+        json::named_params(
+            &B::PLUTUS_VERSION,
+            &self.builtin_costs_json(),
+            &self.machine_costs_json(),
+        )
+    }
+
+    /// The builtin costs in the layout of Plutus' `builtinCostModel*.json`.
+    pub fn builtin_costs_json(&self) -> serde_json::Value {
+        json::builtin_costs(&self.builtin_costs)
+    }
+
+    /// The machine costs in the layout of Plutus' `cekMachineCosts*.json`.
+    pub fn machine_costs_json(&self) -> serde_json::Value {
+        json::machine_costs(self.machine_startup, &self.machine_costs)
+    }
+
+    /// The memory and CPU cost of every builtin as a formula over the sizes
+    /// of its arguments.
+    pub fn formulas(&self) -> Vec<BuiltinFormula> {
+        self.builtin_costs
+            .costings()
+            .into_iter()
+            .map(|(builtin, costing)| {
+                let (mem, cpu) = match costing {
+                    BuiltinCosting::One(c) => (c.mem.to_string(), c.cpu.to_string()),
+                    BuiltinCosting::Two(c) => (c.mem.to_string(), c.cpu.to_string()),
+                    BuiltinCosting::Three(c) => (c.mem.to_string(), c.cpu.to_string()),
+                    BuiltinCosting::Six(c) => (c.mem.to_string(), c.cpu.to_string()),
+                };
+
+                BuiltinFormula { builtin, mem, cpu }
+            })
+            .collect()
+    }
+
+    fn from_cost_map(cost_map: &CostMap) -> CostModel<B> {
         Self {
             machine_startup: ExBudget {
                 mem: cost_map["cek_startup_cost-exBudgetmem"],
                 cpu: cost_map["cek_startup_cost-exBudgetCPU"],
             },
-            machine_costs: MachineCosts::initialize_machine_costs(&cost_map),
-            builtin_costs: B::initialize(&cost_map),
+            machine_costs: MachineCosts::initialize_machine_costs(cost_map),
+            builtin_costs: B::initialize(cost_map),
         }
     }
 }
@@ -0,0 +1,361 @@
+use std::fmt;
+
+use super::{ErrorKind, EvalSnapshot, ExBudget, OwnedNode, OwnedTerm, PlutusVersion};
+
+/// One configuration to evaluate a program under.
+///
+/// Without an explicit cost model the default one for `plutus_version` is used.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalConfig<'c> {
+    pub plutus_version: PlutusVersion,
+    pub cost_model: Option<&'c [i64]>,
+    pub initial_budget: ExBudget,
+}
+
+impl<'c> EvalConfig<'c> {
+    pub fn new(plutus_version: PlutusVersion) -> Self {
+        EvalConfig {
+            plutus_version,
+            cost_model: None,
+            initial_budget: ExBudget::default(),
+        }
+    }
+
+    pub fn with_cost_model(self, cost_model: &'c [i64]) -> Self {
+        EvalConfig {
+            cost_model: Some(cost_model),
+            ..self
+        }
+    }
+
+    pub fn with_budget(self, initial_budget: ExBudget) -> Self {
+        EvalConfig {
+            initial_budget,
+            ..self
+        }
+    }
+}
+
+/// The outcome of evaluating one program under several configurations.
+///
+/// The first configuration is the baseline, every other one is compared
+/// against it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifferentialReport {
+    pub snapshots: Vec<EvalSnapshot>,
+    pub comparisons: Vec<Comparison>,
+}
+
+/// Differences between the baseline and the configuration at `config`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub config: usize,
+    pub differences: Vec<Difference>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// One configuration succeeded where the other failed.
+    Outcome {
+        baseline: Option<ErrorKind>,
+        other: Option<ErrorKind>,
+    },
+    /// Both configurations failed, with different errors.
+    Error {
+        baseline: String,
+        other: String,
+    },
+    /// Both configurations succeeded with different terms. `path` leads from
+    /// the root to the first node that differs.
+    Term {
+        path: String,
+    },
+    /// The logs differ, starting at line `index`.
+    Logs {
+        index: usize,
+        baseline: Option<String>,
+        other: Option<String>,
+    },
+    Budget {
+        baseline: ExBudget,
+        other: ExBudget,
+    },
+}
+
+impl DifferentialReport {
+    pub fn new(snapshots: Vec<EvalSnapshot>) -> Self {
+        let comparisons = match snapshots.split_first() {
+            Some((baseline, others)) => others
+                .iter()
+                .enumerate()
+                .map(|(index, other)| Comparison {
+                    config: index + 1,
+                    differences: compare(baseline, other),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        DifferentialReport {
+            snapshots,
+            comparisons,
+        }
+    }
+
+    /// Whether every configuration behaved exactly like the baseline.
+    pub fn is_consistent(&self) -> bool {
+        self.comparisons
+            .iter()
+            .all(|comparison| comparison.differences.is_empty())
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comparison in &self.comparisons {
+            if comparison.differences.is_empty() {
+                writeln!(f, "config #{}: same as baseline", comparison.config)?;
+
+                continue;
+            }
+
+            writeln!(f, "config #{}:", comparison.config)?;
+
+            for difference in &comparison.differences {
+                writeln!(f, "  - {difference}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = |kind: &Option<ErrorKind>| match kind {
+            Some(kind) => format!("failed with {kind:?}"),
+            None => "succeeded".to_string(),
+        };
+
+        match self {
+            Difference::Outcome { baseline, other } => write!(
+                f,
+                "outcome: baseline {}, other {}",
+                outcome(baseline),
+                outcome(other)
+            ),
+            Difference::Error { baseline, other } => {
+                write!(f, "error: baseline \"{baseline}\", other \"{other}\"")
+            }
+            Difference::Term { path } => write!(f, "result term differs at {path}"),
+            Difference::Logs {
+                index,
+                baseline,
+                other,
+            } => write!(
+                f,
+                "logs differ at line {index}: baseline {baseline:?}, other {other:?}"
+            ),
+            Difference::Budget { baseline, other } => write!(
+                f,
+                "budget: baseline {{cpu: {}, mem: {}}}, other {{cpu: {}, mem: {}}} (delta cpu: {:+}, mem: {:+})",
+                baseline.cpu,
+                baseline.mem,
+                other.cpu,
+                other.mem,
+                other.cpu - baseline.cpu,
+                other.mem - baseline.mem,
+            ),
+        }
+    }
+}
+
+fn compare(baseline: &EvalSnapshot, other: &EvalSnapshot) -> Vec<Difference> {
+    let mut differences = Vec::new();
+
+    match (&baseline.term, &other.term) {
+        (Ok(left), Ok(right)) => {
+            if let Some(path) = first_difference(left, right) {
+                differences.push(Difference::Term { path });
+            }
+        }
+        (Err(left), Err(right)) => {
+            if left != right {
+                differences.push(Difference::Error {
+                    baseline: left.message.clone(),
+                    other: right.message.clone(),
+                });
+            }
+        }
+        (left, right) => differences.push(Difference::Outcome {
+            baseline: left.as_ref().err().map(|e| e.kind),
+            other: right.as_ref().err().map(|e| e.kind),
+        }),
+    }
+
+    let left_logs = &baseline.info.logs;
+    let right_logs = &other.info.logs;
+
+    if left_logs != right_logs {
+        let index = left_logs
+            .iter()
+            .zip(right_logs)
+            .position(|(l, r)| l != r)
+            .unwrap_or(left_logs.len().min(right_logs.len()));
+
+        differences.push(Difference::Logs {
+            index,
+            baseline: left_logs.get(index).cloned(),
+            other: right_logs.get(index).cloned(),
+        });
+    }
+
+    if baseline.info.consumed_budget != other.info.consumed_budget {
+        differences.push(Difference::Budget {
+            baseline: baseline.info.consumed_budget,
+            other: other.info.consumed_budget,
+        });
+    }
+
+    differences
+}
+
+/// Walk both terms from the root and return the path to the first node whose
+/// shape differs.
+fn first_difference(left: &OwnedTerm, right: &OwnedTerm) -> Option<String> {
+    let root = |term: &OwnedTerm| term.nodes().len() - 1;
+
+    let mut stack = vec![(root(left), root(right), String::from("term"))];
+
+    while let Some((l, r, path)) = stack.pop() {
+        let children = match (left.node(l), right.node(r)) {
+            (OwnedNode::Lambda(lb), OwnedNode::Lambda(rb)) => vec![(*lb, *rb, "lam.body")],
+            (OwnedNode::Delay(lb), OwnedNode::Delay(rb)) => vec![(*lb, *rb, "delay")],
+            (OwnedNode::Force(lb), OwnedNode::Force(rb)) => vec![(*lb, *rb, "force")],
+            (
+                OwnedNode::Apply {
+                    function: lf,
+                    argument: la,
+                },
+                OwnedNode::Apply {
+                    function: rf,
+                    argument: ra,
+                },
+            ) => vec![(*la, *ra, "argument"), (*lf, *rf, "function")],
+            (
+                OwnedNode::Case {
+                    constr: lc,
+                    branches: lbs,
+                },
+                OwnedNode::Case {
+                    constr: rc,
+                    branches: rbs,
+                },
+            ) if lbs.len() == rbs.len() => {
+                for (i, (lb, rb)) in lbs.iter().zip(rbs).enumerate().rev() {
+                    stack.push((*lb, *rb, format!("{path}.branches[{i}]")));
+                }
+
+                vec![(*lc, *rc, "constr")]
+            }
+            (
+                OwnedNode::Constr {
+                    tag: lt,
+                    fields: lfs,
+                },
+                OwnedNode::Constr {
+                    tag: rt,
+                    fields: rfs,
+                },
+            ) if lt == rt && lfs.len() == rfs.len() => {
+                for (i, (lf, rf)) in lfs.iter().zip(rfs).enumerate().rev() {
+                    stack.push((*lf, *rf, format!("{path}.fields[{i}]")));
+                }
+
+                vec![]
+            }
+            (l, r) if l == r => vec![],
+            _ => return Some(path),
+        };
+
+        for (l, r, step) in children {
+            stack.push((l, r, format!("{path}.{step}")));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arena::Arena,
+        machine::{ErrorKind, ExBudget, OwnedTerm, PlutusVersion},
+        syn::parse_program,
+    };
+
+    use super::{first_difference, Difference, EvalConfig};
+
+    #[test]
+    fn reports_outcome_logs_and_budget() {
+        let arena = Arena::new();
+
+        let program = parse_program(
+            &arena,
+            r#"(program 1.0.0 [(force (builtin trace)) (con string "hi") (con integer 1)])"#,
+        )
+        .into_result()
+        .unwrap();
+
+        let configs = [
+            EvalConfig::new(PlutusVersion::V3),
+            EvalConfig::new(PlutusVersion::V3),
+            EvalConfig::new(PlutusVersion::V3).with_budget(ExBudget::new(500, 50_000)),
+        ];
+
+        let report = program.eval_differential(&arena, &configs).unwrap();
+
+        assert_eq!(report.snapshots.len(), 3);
+        assert!(report.comparisons[0].differences.is_empty());
+        assert!(!report.is_consistent());
+
+        let differences = &report.comparisons[1].differences;
+
+        assert_eq!(
+            differences[0],
+            Difference::Outcome {
+                baseline: None,
+                other: Some(ErrorKind::OutOfExError),
+            }
+        );
+        assert_eq!(
+            differences[1],
+            Difference::Logs {
+                index: 0,
+                baseline: Some("hi".to_string()),
+                other: None,
+            }
+        );
+        assert!(matches!(differences[2], Difference::Budget { .. }));
+    }
+
+    #[test]
+    fn locates_first_term_difference() {
+        let arena = Arena::new();
+
+        let parse = |input| {
+            let program = parse_program(&arena, input).into_result().unwrap();
+
+            OwnedTerm::from_term(program.term)
+        };
+
+        let left = parse("(program 1.1.0 (lam x [x (constr 0 (con integer 1) x)]))");
+        let right = parse("(program 1.1.0 (lam x [x (constr 0 (con integer 2) x)]))");
+
+        assert_eq!(first_difference(&left, &left), None);
+        assert_eq!(
+            first_difference(&left, &right).as_deref(),
+            Some("term.lam.body.argument.fields[0]")
+        );
+    }
+}
@@ -1,93 +1,423 @@
-use bumpalo::collections::CollectIn;
 use bumpalo::collections::Vec as BumpVec;
 
 use crate::arena::Arena;
-use crate::{binder::Eval, term::Term};
+use crate::{binder::Eval, builtin::DefaultFunction, constant::Constant, term::Term};
 
 use super::{env::Env, value::Value};
 
+/// What a discharge produces. The walk decides what to substitute and in
+/// which order, a builder only creates the nodes: arena terms for the
+/// machine, owned nodes for snapshots.
+pub(super) trait Build<'a, V>
+where
+    V: Eval<'a>,
+{
+    type Output: Copy;
+
+    /// Reuse a term that has nothing to substitute, or `None` to walk it.
+    fn shared(&mut self, term: &'a Term<'a, V>) -> Option<Self::Output>;
+
+    /// An unbound variable, a constant, a builtin or an error term.
+    fn leaf(&mut self, term: &'a Term<'a, V>) -> Self::Output;
+
+    fn constant(&mut self, constant: &'a Constant<'a>) -> Self::Output;
+
+    fn builtin(&mut self, fun: &'a DefaultFunction) -> Self::Output;
+
+    fn lambda(&mut self, parameter: &'a V, body: Self::Output) -> Self::Output;
+
+    fn apply(&mut self, function: Self::Output, argument: Self::Output) -> Self::Output;
+
+    fn delay(&mut self, body: Self::Output) -> Self::Output;
+
+    fn force(&mut self, body: Self::Output) -> Self::Output;
+
+    fn constr(&mut self, tag: usize, fields: std::vec::Drain<'_, Self::Output>) -> Self::Output;
+
+    fn case(
+        &mut self,
+        constr: Self::Output,
+        branches: std::vec::Drain<'_, Self::Output>,
+    ) -> Self::Output;
+}
+
+/// Pending work for the discharge loop. `Value` and `Term` items produce a
+/// node on the output stack, the remaining items pop their children from it
+/// and rebuild the node.
+enum Work<'a, V>
+where
+    V: Eval<'a>,
+{
+    Value(&'a Value<'a, V>),
+    Term {
+        lam_cnt: usize,
+        env: Option<&'a Env<'a, V>>,
+        term: &'a Term<'a, V>,
+    },
+    Lambda(&'a V),
+    Apply,
+    Delay,
+    Force,
+    Constr {
+        tag: usize,
+        arity: usize,
+    },
+    Case {
+        arity: usize,
+    },
+}
+
+/// Turn a value back into a closed term by substituting the environment of
+/// every closure into its body.
+///
+/// This uses an explicit work stack instead of recursion so that arbitrarily
+/// deep results cannot overflow the native stack.
 pub fn value_as_term<'a, V>(arena: &'a Arena, value: &'a Value<'a, V>) -> &'a Term<'a, V>
 where
     V: Eval<'a>,
+{
+    discharge_value(&mut ArenaBuild(arena), value)
+}
+
+/// Build a value with `build`, see [`value_as_term`].
+pub(super) fn discharge_value<'a, V, B>(build: &mut B, value: &'a Value<'a, V>) -> B::Output
+where
+    V: Eval<'a>,
+    B: Build<'a, V>,
+{
+    walk(build, Work::Value(value))
+}
+
+/// Build a term with `build`, without anything to substitute.
+pub(super) fn discharge_term<'a, V, B>(build: &mut B, term: &'a Term<'a, V>) -> B::Output
+where
+    V: Eval<'a>,
+    B: Build<'a, V>,
+{
+    walk(
+        build,
+        Work::Term {
+            lam_cnt: 0,
+            env: None,
+            term,
+        },
+    )
+}
+
+fn walk<'a, V, B>(build: &mut B, start: Work<'a, V>) -> B::Output
+where
+    V: Eval<'a>,
+    B: Build<'a, V>,
+{
+    let mut work = vec![start];
+    let mut done: Vec<B::Output> = Vec::new();
+
+    while let Some(item) = work.pop() {
+        match item {
+            Work::Value(value) => value_work(build, value, &mut work, &mut done),
+            Work::Term { lam_cnt, env, term } => {
+                with_env(build, lam_cnt, env, term, &mut work, &mut done)
+            }
+            Work::Lambda(parameter) => {
+                let body = pop(&mut done);
+
+                done.push(build.lambda(parameter, body));
+            }
+            Work::Apply => {
+                let argument = pop(&mut done);
+                let function = pop(&mut done);
+
+                done.push(build.apply(function, argument));
+            }
+            Work::Delay => {
+                let body = pop(&mut done);
+
+                done.push(build.delay(body));
+            }
+            Work::Force => {
+                let body = pop(&mut done);
+
+                done.push(build.force(body));
+            }
+            Work::Constr { tag, arity } => {
+                let start = done.len() - arity;
+
+                let term = build.constr(tag, done.drain(start..));
+
+                done.push(term);
+            }
+            Work::Case { arity } => {
+                let start = done.len() - arity;
+                let constr = done[start - 1];
+
+                let term = build.case(constr, done.drain(start..));
+
+                pop(&mut done);
+
+                done.push(term);
+            }
+        }
+    }
+
+    pop(&mut done)
+}
+
+fn value_work<'a, V, B>(
+    build: &mut B,
+    value: &'a Value<'a, V>,
+    work: &mut Vec<Work<'a, V>>,
+    done: &mut Vec<B::Output>,
+) where
+    V: Eval<'a>,
+    B: Build<'a, V>,
 {
     match value {
-        Value::Con(x) => arena.alloc(Term::Constant(x)),
+        Value::Con(x) => done.push(build.constant(x)),
         Value::Builtin(runtime) => {
-            let mut term = Term::builtin(arena, runtime.fun);
+            let mut term = build.builtin(runtime.fun);
 
             for _ in 0..runtime.forces {
-                term = term.force(arena);
+                term = build.force(term);
             }
 
-            for arg in &runtime.args {
-                term = term.apply(arena, value_as_term(arena, arg));
-            }
+            done.push(term);
 
-            term
+            for arg in runtime.args.iter().rev() {
+                work.push(Work::Apply);
+                work.push(Work::Value(arg));
+            }
+        }
+        Value::Delay(body, env) => {
+            work.push(Work::Delay);
+            work.push(Work::Term {
+                lam_cnt: 0,
+                env: Some(env),
+                term: body,
+            });
         }
-        Value::Delay(body, env) => with_env(arena, 0, env, body.delay(arena)),
         Value::Lambda {
             parameter,
             body,
             env,
-        } => with_env(arena, 0, env, body.lambda(arena, parameter)),
+        } => {
+            work.push(Work::Lambda(parameter));
+            work.push(Work::Term {
+                lam_cnt: 1,
+                env: Some(env),
+                term: body,
+            });
+        }
         Value::Constr(tag, fields) => {
-            let fields: BumpVec<'_, _> = fields
-                .iter()
-                .map(|value| value_as_term(arena, value))
-                .collect_in(arena.as_bump());
+            work.push(Work::Constr {
+                tag: *tag,
+                arity: fields.len(),
+            });
 
-            let fields = arena.alloc(fields);
-
-            Term::constr(arena, *tag, fields)
+            for field in fields.iter().rev() {
+                work.push(Work::Value(field));
+            }
         }
     }
 }
 
-fn with_env<'a, V>(
-    arena: &'a Arena,
+fn with_env<'a, V, B>(
+    build: &mut B,
     lam_cnt: usize,
-    env: &'a Env<'a, V>,
+    env: Option<&'a Env<'a, V>>,
     term: &'a Term<'a, V>,
-) -> &'a Term<'a, V>
-where
+    work: &mut Vec<Work<'a, V>>,
+    done: &mut Vec<B::Output>,
+) where
     V: Eval<'a>,
+    B: Build<'a, V>,
 {
+    // Nothing to substitute, the builder may share the term as is.
+    if let None | Some(Env::Empty) = env {
+        if let Some(shared) = build.shared(term) {
+            done.push(shared);
+
+            return;
+        }
+    }
+
+    let child = |term| Work::Term { lam_cnt, env, term };
+
     match term {
         Term::Var(name) => {
             let index = name.index();
 
-            if lam_cnt >= index {
-                Term::var(arena, name)
-            } else {
-                env.lookup(index - lam_cnt).map_or_else(
-                    || Term::var(arena, *name),
-                    |value| value_as_term(arena, value),
-                )
+            let value = match env {
+                Some(env) if index > lam_cnt => env.lookup(index - lam_cnt),
+                _ => None,
+            };
+
+            match value {
+                Some(value) => work.push(Work::Value(value)),
+                None => done.push(build.leaf(term)),
             }
         }
         Term::Lambda { parameter, body } => {
-            let body = with_env(arena, lam_cnt + 1, env, body);
-
-            body.lambda(arena, *parameter)
+            work.push(Work::Lambda(parameter));
+            work.push(Work::Term {
+                lam_cnt: lam_cnt + 1,
+                env,
+                term: body,
+            });
         }
         Term::Apply { function, argument } => {
-            let function = with_env(arena, lam_cnt, env, function);
-            let argument = with_env(arena, lam_cnt, env, argument);
+            work.push(Work::Apply);
+            work.push(child(argument));
+            work.push(child(function));
+        }
+        Term::Delay(body) => {
+            work.push(Work::Delay);
+            work.push(child(body));
+        }
+        Term::Force(body) => {
+            work.push(Work::Force);
+            work.push(child(body));
+        }
+        Term::Constr { tag, fields } => {
+            work.push(Work::Constr {
+                tag: *tag,
+                arity: fields.len(),
+            });
 
-            function.apply(arena, argument)
+            for field in fields.iter().rev() {
+                work.push(child(field));
+            }
         }
+        Term::Case { constr, branches } => {
+            work.push(Work::Case {
+                arity: branches.len(),
+            });
+
+            for branch in branches.iter().rev() {
+                work.push(child(branch));
+            }
+
+            work.push(child(constr));
+        }
+        Term::Constant(_) | Term::Builtin(_) | Term::Error => done.push(build.leaf(term)),
+    }
+}
+
+fn pop<T>(done: &mut Vec<T>) -> T {
+    done.pop().expect("discharge stack should not be empty")
+}
+
+/// Builds arena terms, sharing every subterm that has nothing to substitute.
+struct ArenaBuild<'a>(&'a Arena);
+
+impl<'a, V> Build<'a, V> for ArenaBuild<'a>
+where
+    V: Eval<'a> + 'a,
+{
+    type Output = &'a Term<'a, V>;
+
+    fn shared(&mut self, term: &'a Term<'a, V>) -> Option<Self::Output> {
+        Some(term)
+    }
+
+    fn leaf(&mut self, term: &'a Term<'a, V>) -> Self::Output {
+        term
+    }
+
+    fn constant(&mut self, constant: &'a Constant<'a>) -> Self::Output {
+        Term::constant(self.0, constant)
+    }
+
+    fn builtin(&mut self, fun: &'a DefaultFunction) -> Self::Output {
+        Term::builtin(self.0, fun)
+    }
+
+    fn lambda(&mut self, parameter: &'a V, body: Self::Output) -> Self::Output {
+        body.lambda(self.0, parameter)
+    }
+
+    fn apply(&mut self, function: Self::Output, argument: Self::Output) -> Self::Output {
+        function.apply(self.0, argument)
+    }
+
+    fn delay(&mut self, body: Self::Output) -> Self::Output {
+        body.delay(self.0)
+    }
+
+    fn force(&mut self, body: Self::Output) -> Self::Output {
+        body.force(self.0)
+    }
+
+    fn constr(&mut self, tag: usize, fields: std::vec::Drain<'_, Self::Output>) -> Self::Output {
+        Term::constr(self.0, tag, self.many(fields))
+    }
+
+    fn case(
+        &mut self,
+        constr: Self::Output,
+        branches: std::vec::Drain<'_, Self::Output>,
+    ) -> Self::Output {
+        Term::case(self.0, constr, self.many(branches))
+    }
+}
 
-        Term::Delay(x) => {
-            let body = with_env(arena, lam_cnt, env, x);
+impl<'a> ArenaBuild<'a> {
+    fn many<V>(&self, terms: std::vec::Drain<'_, &'a Term<'a, V>>) -> &'a [&'a Term<'a, V>]
+    where
+        V: Eval<'a>,
+    {
+        let mut many = BumpVec::with_capacity_in(terms.len(), self.0.as_bump());
 
-            body.delay(arena)
+        many.extend(terms);
+
+        self.0.alloc(many)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arena::Arena,
+        binder::DeBruijn,
+        program::{Program, Version},
+        term::Term,
+    };
+
+    #[test]
+    fn deep_closure_body() {
+        let arena = &Arena::new();
+
+        let depth = 100_000;
+
+        let mut body = Term::var(arena, DeBruijn::new(arena, 2));
+
+        for _ in 0..depth {
+            body = body.delay(arena);
         }
-        Term::Force(x) => {
-            let body = with_env(arena, lam_cnt, env, x);
 
-            body.force(arena)
+        let term = body
+            .lambda(arena, DeBruijn::zero(arena))
+            .lambda(arena, DeBruijn::zero(arena))
+            .apply(arena, Term::integer_from(arena, 42));
+
+        let version = Version::plutus_v3(arena);
+
+        let program = Program::<DeBruijn>::new(arena, version, term);
+
+        let result = program.eval(arena);
+
+        let Term::Lambda { body, .. } = result.term.unwrap() else {
+            panic!("expected a lambda");
+        };
+
+        let mut term = *body;
+        let mut delays = 0;
+
+        while let Term::Delay(inner) = term {
+            term = inner;
+            delays += 1;
         }
-        rest => rest,
+
+        assert_eq!(delays, depth);
+        assert_eq!(term, Term::integer_from(arena, 42));
     }
 }
@@ -36,14 +36,20 @@ where
             return None;
         }
 
-        match self {
-            Env::Empty => None,
-            Env::Cons { data, next: parent } => {
-                if index == 1 {
-                    return Some(data);
-                }
+        let mut env = self;
+        let mut index = index;
+
+        loop {
+            match env {
+                Env::Empty => return None,
+                Env::Cons { data, next: parent } => {
+                    if index == 1 {
+                        return Some(data);
+                    }
 
-                parent.lookup(index - 1)
+                    env = parent;
+                    index -= 1;
+                }
             }
         }
     }
@@ -0,0 +1,1239 @@
+use std::{collections::HashMap, rc::Rc};
+
+use num::{Signed, ToPrimitive};
+
+use crate::{
+    binder::Eval, builtin::DefaultFunction, constant::Constant, data::PlutusData, program::Version,
+    term::Term,
+};
+
+use super::{
+    cost_model::{
+        builtin_costs::{BuiltinCostModel, BuiltinCosting},
+        constant_ex_mem,
+        costing::{Cost as CostingFunction, Costing},
+        StepKind,
+    },
+    runtime::INTEGER_TO_BYTE_STRING_MAXIMUM_OUTPUT_LENGTH,
+    CostModel, ExBudget,
+};
+
+/// How many times the body of a lambda may be entered while it is still
+/// being evaluated before the analysis gives up on it as recursive.
+const MAX_UNROLLING: usize = 8;
+
+/// How many terms the analysis evaluates, over all paths, before giving up.
+const MAX_STEPS: usize = 1_000_000;
+
+/// The memory size of an unknown value, like a script argument, in the
+/// units the cost model measures it in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeRange {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl SizeRange {
+    pub fn new(min: i64, max: i64) -> Self {
+        SizeRange { min, max }
+    }
+
+    pub fn exact(size: i64) -> Self {
+        SizeRange::new(size, size)
+    }
+
+    fn any() -> Self {
+        SizeRange::new(0, i64::MAX)
+    }
+
+    fn union(self, other: SizeRange) -> Self {
+        SizeRange::new(self.min.min(other.min), self.max.max(other.max))
+    }
+}
+
+/// Why a script has no upper bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unbounded {
+    /// Some path recurses, through a fixpoint combinator or otherwise.
+    Recursion,
+    /// The script has too many paths to analyse.
+    Limit,
+    /// The cost of a builtin cannot be bounded over the sizes of its
+    /// arguments, as when it may saturate.
+    Costing,
+}
+
+/// Bounds on the budget a script can consume, see
+/// [`Program::estimate_budget`](crate::program::Program::estimate_budget).
+///
+/// `lower` is the cheapest way through the script that does not fail, or
+/// the cheapest failure if every path fails.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetEstimate {
+    pub lower: ExBudget,
+    pub upper: Result<ExBudget, Unbounded>,
+    /// Whether some path ends in an error term or a machine error.
+    pub may_fail: bool,
+}
+
+impl BudgetEstimate {
+    pub fn is_exact(&self) -> bool {
+        self.upper == Ok(self.lower)
+    }
+
+    /// Whether the script needs more than `limit` on every path that
+    /// succeeds, so it can be rejected without running it.
+    pub fn never_fits(&self, limit: ExBudget) -> bool {
+        self.lower.cpu > limit.cpu || self.lower.mem > limit.mem
+    }
+}
+
+/// Estimate the budget of applying `term` to arguments of `args` sizes.
+pub(crate) fn estimate<'a, V, B>(
+    term: &'a Term<'a, V>,
+    version: &Version<'a>,
+    cost_model: &CostModel<B>,
+    args: &[SizeRange],
+) -> BudgetEstimate
+where
+    V: Eval<'a>,
+    B: BuiltinCostModel,
+{
+    let mut estimator = Estimator {
+        costs: cost_model,
+        costings: cost_model.builtin_costs.costings(),
+        case_on_constants: version.is_at_least_1_1_0(),
+        active: HashMap::new(),
+        steps: 0,
+        work: Vec::new(),
+        outcomes: Vec::new(),
+        recursive: false,
+        limited: false,
+        uncosted: false,
+        may_fail: false,
+    };
+
+    // The arguments are applied as constants, like the ledger does.
+    let mut cost = Cost::exact(cost_model.machine_startup);
+
+    for _ in args {
+        cost = cost
+            .then(estimator.step(StepKind::Apply))
+            .then(estimator.step(StepKind::Constant));
+    }
+
+    let (mut value, body_cost) = estimator.run(Work::Eval(term, None));
+
+    cost = cost.then(body_cost);
+
+    for size in args {
+        let Some(function) = value else { break };
+
+        let argument = Abstract::Unknown(*size, Shape::Other);
+        let (result, apply_cost) = estimator.run(Work::Apply(function, argument));
+
+        value = result;
+        cost = cost.then(apply_cost);
+    }
+
+    let upper = match cost.upper {
+        _ if estimator.limited => Err(Unbounded::Limit),
+        _ if estimator.recursive => Err(Unbounded::Recursion),
+        Some(upper) => Ok(upper),
+        None if estimator.uncosted => Err(Unbounded::Costing),
+        None => Err(Unbounded::Limit),
+    };
+
+    BudgetEstimate {
+        lower: cost.lower,
+        upper,
+        may_fail: estimator.may_fail,
+    }
+}
+
+/// What an unknown value can be scrutinised as by `case`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Bool,
+    Integer,
+    List,
+    Pair,
+    Other,
+}
+
+/// A value as far as the analysis knows it.
+#[derive(Debug)]
+enum Abstract<'a, V>
+where
+    V: Eval<'a>,
+{
+    /// A constant of the script itself.
+    Known(&'a Constant<'a>),
+    /// Any value of the given size, like an argument or a builtin's result.
+    Unknown(SizeRange, Shape),
+    Lambda(&'a Term<'a, V>, Env<'a, V>),
+    Delay(&'a Term<'a, V>, Env<'a, V>),
+    Builtin(&'a DefaultFunction, usize, Rc<Vec<Abstract<'a, V>>>),
+    Constr(usize, Rc<Vec<Abstract<'a, V>>>),
+    /// One of several values, after branching on something unknown.
+    OneOf(Rc<Vec<Abstract<'a, V>>>),
+}
+
+impl<'a, V> Clone for Abstract<'a, V>
+where
+    V: Eval<'a>,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Abstract::Known(constant) => Abstract::Known(constant),
+            Abstract::Unknown(size, shape) => Abstract::Unknown(*size, *shape),
+            Abstract::Lambda(body, env) => Abstract::Lambda(body, env.clone()),
+            Abstract::Delay(body, env) => Abstract::Delay(body, env.clone()),
+            Abstract::Builtin(fun, forces, args) => Abstract::Builtin(fun, *forces, args.clone()),
+            Abstract::Constr(tag, fields) => Abstract::Constr(*tag, fields.clone()),
+            Abstract::OneOf(values) => Abstract::OneOf(values.clone()),
+        }
+    }
+}
+
+type Env<'a, V> = Option<Rc<Frame<'a, V>>>;
+
+#[derive(Debug)]
+struct Frame<'a, V>
+where
+    V: Eval<'a>,
+{
+    value: Abstract<'a, V>,
+    next: Env<'a, V>,
+}
+
+fn lookup<'a, V>(env: &Env<'a, V>, index: usize) -> Option<Abstract<'a, V>>
+where
+    V: Eval<'a>,
+{
+    // De Bruijn indices are 1-based
+    let mut frame = env.as_ref()?;
+
+    for _ in 1..index {
+        frame = frame.next.as_ref()?;
+    }
+
+    (index > 0).then(|| frame.value.clone())
+}
+
+/// The cost of a computation. There is no upper bound when it may not
+/// terminate.
+#[derive(Debug, Clone, Copy)]
+struct Cost {
+    lower: ExBudget,
+    upper: Option<ExBudget>,
+}
+
+impl Cost {
+    fn exact(budget: ExBudget) -> Self {
+        Cost {
+            lower: budget,
+            upper: Some(budget),
+        }
+    }
+
+    fn zero() -> Self {
+        Cost::exact(ExBudget::new(0, 0))
+    }
+
+    fn unbounded() -> Self {
+        Cost {
+            lower: ExBudget::new(0, 0),
+            upper: None,
+        }
+    }
+
+    fn then(self, next: Cost) -> Self {
+        Cost {
+            lower: add(self.lower, next.lower),
+            upper: self.upper.zip(next.upper).map(|(a, b)| add(a, b)),
+        }
+    }
+}
+
+fn add(a: ExBudget, b: ExBudget) -> ExBudget {
+    ExBudget::new(a.mem.saturating_add(b.mem), a.cpu.saturating_add(b.cpu))
+}
+
+fn min(a: ExBudget, b: ExBudget) -> ExBudget {
+    ExBudget::new(a.mem.min(b.mem), a.cpu.min(b.cpu))
+}
+
+fn max(a: ExBudget, b: ExBudget) -> ExBudget {
+    ExBudget::new(a.mem.max(b.mem), a.cpu.max(b.cpu))
+}
+
+type Outcome<'a, V> = (Option<Abstract<'a, V>>, Cost);
+
+/// Pending work for the analysis loop. The first four items leave an
+/// outcome on the outcome stack, the others continue with the outcome on
+/// top of it.
+enum Work<'a, V>
+where
+    V: Eval<'a>,
+{
+    Eval(&'a Term<'a, V>, Env<'a, V>),
+    Apply(Abstract<'a, V>, Abstract<'a, V>),
+    Force(Abstract<'a, V>),
+    Case(Abstract<'a, V>, &'a [&'a Term<'a, V>], Env<'a, V>),
+    /// Count a cost before the outcome.
+    Then(Cost),
+    /// Force the value of the outcome.
+    Forced(Cost),
+    /// Evaluate the argument once the function is known.
+    Function(Cost, &'a Term<'a, V>, Env<'a, V>),
+    /// Apply the function to the value of the outcome.
+    Argument(Cost, Abstract<'a, V>),
+    /// Keep the value of a field and evaluate the remaining ones.
+    Field {
+        tag: usize,
+        cost: Cost,
+        values: Vec<Abstract<'a, V>>,
+        fields: &'a [&'a Term<'a, V>],
+        env: Env<'a, V>,
+    },
+    /// Select a branch with the value of the outcome.
+    Scrutinee(Cost, &'a [&'a Term<'a, V>], Env<'a, V>),
+    /// Apply the value of the outcome to the fields, in order.
+    Fields(std::vec::IntoIter<Abstract<'a, V>>),
+    /// Combine the outcomes of that many alternative paths.
+    Join(usize),
+    /// Leave the body of a lambda.
+    Leave(*const Term<'a, V>),
+}
+
+struct Estimator<'a, 'c, V, B>
+where
+    V: Eval<'a>,
+    B: BuiltinCostModel,
+{
+    costs: &'c CostModel<B>,
+    costings: Vec<(DefaultFunction, BuiltinCosting<'c>)>,
+    case_on_constants: bool,
+    /// How often the body of each lambda is being evaluated right now.
+    active: HashMap<*const Term<'a, V>, usize>,
+    steps: usize,
+    work: Vec<Work<'a, V>>,
+    outcomes: Vec<Outcome<'a, V>>,
+    recursive: bool,
+    limited: bool,
+    uncosted: bool,
+    may_fail: bool,
+}
+
+impl<'a, V, B> Estimator<'a, '_, V, B>
+where
+    V: Eval<'a>,
+    B: BuiltinCostModel,
+{
+    fn step(&self, kind: StepKind) -> Cost {
+        Cost::exact(self.costs.machine_costs.get(kind as usize))
+    }
+
+    fn fail(&mut self) -> Outcome<'a, V> {
+        self.may_fail = true;
+
+        (None, Cost::zero())
+    }
+
+    fn failed(&mut self) {
+        let outcome = self.fail();
+
+        self.outcomes.push(outcome);
+    }
+
+    /// Run the analysis loop until `work` has left its outcome.
+    ///
+    /// This uses an explicit work stack instead of recursion so that deeply
+    /// nested scripts cannot overflow the native stack.
+    fn run(&mut self, work: Work<'a, V>) -> Outcome<'a, V> {
+        self.work.push(work);
+
+        while let Some(work) = self.work.pop() {
+            match work {
+                Work::Eval(term, env) => self.eval(term, env),
+                Work::Apply(function, argument) => self.apply(function, argument),
+                Work::Force(value) => self.force(value),
+                Work::Case(value, branches, env) => self.case(value, branches, env),
+                Work::Then(cost) => {
+                    let outcome = self.pop();
+
+                    self.outcomes.push(sequence(cost, outcome));
+                }
+                Work::Forced(cost) => {
+                    let (value, body_cost) = self.pop();
+
+                    self.and_then(cost.then(body_cost), value, Work::Force);
+                }
+                Work::Function(cost, argument, env) => {
+                    let (function, function_cost) = self.pop();
+                    let cost = cost.then(function_cost);
+
+                    match function {
+                        Some(function) => {
+                            self.work.push(Work::Argument(cost, function));
+                            self.work.push(Work::Eval(argument, env));
+                        }
+                        None => self.outcomes.push((None, cost)),
+                    }
+                }
+                Work::Argument(cost, function) => {
+                    let (argument, argument_cost) = self.pop();
+
+                    self.and_then(cost.then(argument_cost), argument, |argument| {
+                        Work::Apply(function, argument)
+                    });
+                }
+                Work::Field {
+                    tag,
+                    cost,
+                    mut values,
+                    fields,
+                    env,
+                } => {
+                    let (value, field_cost) = self.pop();
+                    let cost = cost.then(field_cost);
+
+                    match value {
+                        Some(value) => {
+                            values.push(value);
+
+                            self.fields(tag, cost, values, fields, env);
+                        }
+                        None => self.outcomes.push((None, cost)),
+                    }
+                }
+                Work::Scrutinee(cost, branches, env) => {
+                    let (value, scrutinee_cost) = self.pop();
+
+                    self.and_then(cost.then(scrutinee_cost), value, |value| {
+                        Work::Case(value, branches, env)
+                    });
+                }
+                Work::Fields(mut fields) => {
+                    let (value, cost) = self.pop();
+
+                    match (value, fields.next()) {
+                        (Some(function), Some(field)) => {
+                            self.work.push(Work::Fields(fields));
+                            self.work.push(Work::Then(cost));
+                            self.work.push(Work::Apply(function, field));
+                        }
+                        (value, _) => self.outcomes.push((value, cost)),
+                    }
+                }
+                Work::Join(alternatives) => {
+                    let outcomes = self.outcomes.split_off(self.outcomes.len() - alternatives);
+
+                    self.outcomes.push(join(outcomes));
+                }
+                Work::Leave(key) => {
+                    if let Some(entered) = self.active.get_mut(&key) {
+                        *entered -= 1;
+                    }
+                }
+            }
+        }
+
+        self.pop()
+    }
+
+    fn pop(&mut self) -> Outcome<'a, V> {
+        self.outcomes
+            .pop()
+            .expect("every work item leaves an outcome")
+    }
+
+    /// Continue with `next` on the value, or stop at `cost` without one.
+    fn and_then(
+        &mut self,
+        cost: Cost,
+        value: Option<Abstract<'a, V>>,
+        next: impl FnOnce(Abstract<'a, V>) -> Work<'a, V>,
+    ) {
+        match value {
+            Some(value) => {
+                self.work.push(Work::Then(cost));
+                self.work.push(next(value));
+            }
+            None => self.outcomes.push((None, cost)),
+        }
+    }
+
+    fn eval(&mut self, term: &'a Term<'a, V>, env: Env<'a, V>) {
+        if self.limited || self.steps >= MAX_STEPS {
+            self.limited = true;
+
+            return self.outcomes.push((
+                Some(Abstract::Unknown(SizeRange::any(), Shape::Other)),
+                Cost::unbounded(),
+            ));
+        }
+
+        self.steps += 1;
+
+        let outcome = match term {
+            Term::Var(name) => {
+                let cost = self.step(StepKind::Var);
+
+                match lookup(&env, name.index()) {
+                    Some(value) => (Some(value), cost),
+                    None => sequence(cost, self.fail()),
+                }
+            }
+            Term::Lambda { body, .. } => (
+                Some(Abstract::Lambda(body, env)),
+                self.step(StepKind::Lambda),
+            ),
+            Term::Delay(body) => (Some(Abstract::Delay(body, env)), self.step(StepKind::Delay)),
+            Term::Force(body) => {
+                self.work.push(Work::Forced(self.step(StepKind::Force)));
+                self.work.push(Work::Eval(body, env));
+
+                return;
+            }
+            Term::Apply { function, argument } => {
+                self.work.push(Work::Function(
+                    self.step(StepKind::Apply),
+                    argument,
+                    env.clone(),
+                ));
+                self.work.push(Work::Eval(function, env));
+
+                return;
+            }
+            Term::Constr { tag, fields } => {
+                let cost = self.step(StepKind::Constr);
+
+                return self.fields(*tag, cost, Vec::with_capacity(fields.len()), fields, env);
+            }
+            Term::Case { constr, branches } => {
+                self.work.push(Work::Scrutinee(
+                    self.step(StepKind::Case),
+                    branches,
+                    env.clone(),
+                ));
+                self.work.push(Work::Eval(constr, env));
+
+                return;
+            }
+            Term::Constant(constant) => (
+                Some(Abstract::Known(constant)),
+                self.step(StepKind::Constant),
+            ),
+            Term::Builtin(fun) => (
+                Some(Abstract::Builtin(fun, 0, Rc::new(Vec::new()))),
+                self.step(StepKind::Builtin),
+            ),
+            Term::Error => self.fail(),
+        };
+
+        self.outcomes.push(outcome);
+    }
+
+    /// Evaluate the remaining `fields` of a constructor after `values`.
+    fn fields(
+        &mut self,
+        tag: usize,
+        cost: Cost,
+        values: Vec<Abstract<'a, V>>,
+        fields: &'a [&'a Term<'a, V>],
+        env: Env<'a, V>,
+    ) {
+        match fields.split_first() {
+            Some((field, fields)) => {
+                self.work.push(Work::Field {
+                    tag,
+                    cost,
+                    values,
+                    fields,
+                    env: env.clone(),
+                });
+                self.work.push(Work::Eval(field, env));
+            }
+            None => self
+                .outcomes
+                .push((Some(Abstract::Constr(tag, Rc::new(values))), cost)),
+        }
+    }
+
+    /// Take every alternative path in turn, then combine their outcomes.
+    fn alternatives(&mut self, alternatives: Vec<Work<'a, V>>) {
+        self.work.push(Work::Join(alternatives.len()));
+        self.work.extend(alternatives.into_iter().rev());
+    }
+
+    fn apply(&mut self, function: Abstract<'a, V>, argument: Abstract<'a, V>) {
+        let outcome = match function {
+            Abstract::OneOf(functions) => {
+                return self.alternatives(
+                    functions
+                        .iter()
+                        .map(|function| Work::Apply(function.clone(), argument.clone()))
+                        .collect(),
+                );
+            }
+            Abstract::Lambda(body, env) => {
+                let key = body as *const Term<'a, V>;
+                let entered = self.active.entry(key).or_default();
+
+                if *entered >= MAX_UNROLLING {
+                    self.recursive = true;
+
+                    return self.outcomes.push((
+                        Some(Abstract::Unknown(SizeRange::any(), Shape::Other)),
+                        Cost::unbounded(),
+                    ));
+                }
+
+                *entered += 1;
+
+                let env = Some(Rc::new(Frame {
+                    value: argument,
+                    next: env,
+                }));
+
+                self.work.push(Work::Leave(key));
+                self.work.push(Work::Eval(body, env));
+
+                return;
+            }
+            Abstract::Builtin(fun, forces, args)
+                if forces == fun.force_count() && args.len() < fun.arity() =>
+            {
+                let mut args = args.as_ref().clone();
+
+                args.push(argument);
+
+                if args.len() == fun.arity() {
+                    self.call(fun, &args)
+                } else {
+                    (
+                        Some(Abstract::Builtin(fun, forces, Rc::new(args))),
+                        Cost::zero(),
+                    )
+                }
+            }
+            _ => self.fail(),
+        };
+
+        self.outcomes.push(outcome);
+    }
+
+    fn force(&mut self, value: Abstract<'a, V>) {
+        let outcome = match value {
+            Abstract::OneOf(values) => {
+                return self.alternatives(
+                    values
+                        .iter()
+                        .map(|value| Work::Force(value.clone()))
+                        .collect(),
+                );
+            }
+            Abstract::Delay(body, env) => return self.work.push(Work::Eval(body, env)),
+            Abstract::Builtin(fun, forces, args) if forces < fun.force_count() => {
+                if args.len() == fun.arity() {
+                    self.call(fun, &args)
+                } else {
+                    (Some(Abstract::Builtin(fun, forces + 1, args)), Cost::zero())
+                }
+            }
+            _ => self.fail(),
+        };
+
+        self.outcomes.push(outcome);
+    }
+
+    fn case(&mut self, value: Abstract<'a, V>, branches: &'a [&'a Term<'a, V>], env: Env<'a, V>) {
+        let unknown = |shape| Abstract::Unknown(SizeRange::any(), shape);
+
+        // Every way the value can select a branch, with the fields the
+        // branch is applied to, and how many branches the value allows.
+        let (alternatives, max_branches) = match value {
+            Abstract::OneOf(values) => {
+                return self.alternatives(
+                    values
+                        .iter()
+                        .map(|value| Work::Case(value.clone(), branches, env.clone()))
+                        .collect(),
+                );
+            }
+            Abstract::Constr(tag, fields) => (vec![(tag, fields.as_ref().clone())], usize::MAX),
+            Abstract::Known(constant) if self.case_on_constants => match constant {
+                Constant::Unit => (vec![(0, vec![])], 1),
+                Constant::Boolean(b) => (vec![(*b as usize, vec![])], 2),
+                Constant::Integer(i) => match i.to_usize() {
+                    Some(tag) => (vec![(tag, vec![])], usize::MAX),
+                    None => return self.failed(),
+                },
+                Constant::ProtoList(_, items) => match items.split_first() {
+                    Some((head, _)) => {
+                        // The tail is no constant of the script, but it is
+                        // only known by its size anyway.
+                        let tail = constant_ex_mem(constant) - constant_ex_mem(head);
+
+                        (
+                            vec![(
+                                0,
+                                vec![
+                                    Abstract::Known(head),
+                                    Abstract::Unknown(SizeRange::exact(tail), Shape::List),
+                                ],
+                            )],
+                            2,
+                        )
+                    }
+                    None => (vec![(1, vec![])], 2),
+                },
+                Constant::ProtoPair(_, _, first, second) => (
+                    vec![(0, vec![Abstract::Known(first), Abstract::Known(second)])],
+                    1,
+                ),
+                _ => return self.failed(),
+            },
+            Abstract::Unknown(size, shape) if self.case_on_constants => match shape {
+                Shape::Bool => (vec![(0, vec![]), (1, vec![])], 2),
+                Shape::Integer => (
+                    (0..branches.len()).map(|tag| (tag, vec![])).collect(),
+                    usize::MAX,
+                ),
+                Shape::List => (
+                    vec![
+                        (
+                            0,
+                            vec![
+                                Abstract::Unknown(SizeRange::new(0, size.max), Shape::Other),
+                                Abstract::Unknown(SizeRange::new(0, size.max), Shape::List),
+                            ],
+                        ),
+                        (1, vec![]),
+                    ],
+                    2,
+                ),
+                Shape::Pair => (
+                    vec![(0, vec![unknown(Shape::Other), unknown(Shape::Other)])],
+                    1,
+                ),
+                Shape::Other => return self.failed(),
+            },
+            _ => return self.failed(),
+        };
+
+        if branches.len() > max_branches {
+            return self.failed();
+        }
+
+        self.work.push(Work::Join(alternatives.len()));
+
+        for (tag, fields) in alternatives.into_iter().rev() {
+            match branches.get(tag) {
+                Some(branch) => {
+                    self.work.push(Work::Fields(fields.into_iter()));
+                    self.work.push(Work::Eval(branch, env.clone()));
+                }
+                None => self.failed(),
+            }
+        }
+    }
+
+    /// Cost a saturated builtin by the sizes of its arguments and work out
+    /// what it returns.
+    fn call(&mut self, fun: &'a DefaultFunction, args: &[Abstract<'a, V>]) -> Outcome<'a, V> {
+        let sizes: Vec<SizeRange> = args
+            .iter()
+            .enumerate()
+            .map(|(index, arg)| argument_size(*fun, index, arg))
+            .collect();
+
+        let Some(cost) = self.builtin_cost(*fun, &sizes) else {
+            return self.fail();
+        };
+
+        let value = match (fun, args) {
+            (DefaultFunction::IfThenElse, [condition, then, otherwise]) => match condition {
+                Abstract::Known(Constant::Boolean(true)) => then.clone(),
+                Abstract::Known(Constant::Boolean(false)) => otherwise.clone(),
+                _ => one_of(vec![then.clone(), otherwise.clone()]),
+            },
+            (DefaultFunction::ChooseUnit, [_, value]) | (DefaultFunction::Trace, [_, value]) => {
+                value.clone()
+            }
+            (DefaultFunction::ChooseList, [list, empty, other]) => match list {
+                Abstract::Known(Constant::ProtoList(_, [])) => empty.clone(),
+                Abstract::Known(Constant::ProtoList(..)) => other.clone(),
+                _ => one_of(vec![empty.clone(), other.clone()]),
+            },
+            (DefaultFunction::ChooseData, [data, branches @ ..]) => match data {
+                Abstract::Known(Constant::Data(data)) => {
+                    let index = match data {
+                        PlutusData::Constr { .. } => 0,
+                        PlutusData::Map(_) => 1,
+                        PlutusData::List(_) => 2,
+                        PlutusData::Integer(_) => 3,
+                        PlutusData::ByteString(_) => 4,
+                    };
+
+                    branches[index].clone()
+                }
+                _ => one_of(branches.to_vec()),
+            },
+            _ => {
+                // The memory cost of a builtin bounds the size of its result
+                // from above, except for those that take their result out
+                // of an argument. It says nothing about how small it is.
+                let size = if takes_result_from_argument(*fun) {
+                    SizeRange::new(0, sizes.iter().map(|s| s.max).max().unwrap_or_default())
+                } else {
+                    SizeRange::new(0, cost.upper.map_or(i64::MAX, |u| u.mem))
+                };
+
+                Abstract::Unknown(size, result_shape(*fun))
+            }
+        };
+
+        (Some(value), cost)
+    }
+
+    /// The cheapest and most expensive cost of `fun` over the sizes of its
+    /// arguments, with no upper bound when a costing function cannot be
+    /// bounded over them.
+    fn builtin_cost(&mut self, fun: DefaultFunction, sizes: &[SizeRange]) -> Option<Cost> {
+        let (_, costing) = self.costings.iter().find(|(other, _)| *other == fun)?;
+
+        let bounds = match costing {
+            BuiltinCosting::One(costing) => costing_bounds(costing, sizes),
+            BuiltinCosting::Two(costing) => costing_bounds(costing, sizes),
+            BuiltinCosting::Three(costing) => costing_bounds(costing, sizes),
+            BuiltinCosting::Six(costing) => costing_bounds(costing, sizes),
+        };
+
+        let Some((lower, upper)) = bounds else {
+            self.uncosted = true;
+
+            return Some(Cost::unbounded());
+        };
+
+        Some(Cost {
+            lower,
+            upper: Some(upper),
+        })
+    }
+}
+
+/// The cheapest and most expensive budget of a costing over `sizes`.
+fn costing_bounds<const N: usize, T>(
+    costing: &Costing<N, T>,
+    sizes: &[SizeRange],
+) -> Option<(ExBudget, ExBudget)>
+where
+    T: CostingFunction<N>,
+{
+    let ranges = std::array::from_fn(|i| (sizes[i].min, sizes[i].max));
+
+    let (mem_lower, mem_upper) = costing.mem.bounds(ranges)?;
+    let (cpu_lower, cpu_upper) = costing.cpu.bounds(ranges)?;
+
+    Some((
+        ExBudget::new(mem_lower, cpu_lower),
+        ExBudget::new(mem_upper, cpu_upper),
+    ))
+}
+
+fn sequence<'a, V>(cost: Cost, (value, next): Outcome<'a, V>) -> Outcome<'a, V>
+where
+    V: Eval<'a>,
+{
+    (value, cost.then(next))
+}
+
+/// Combine the outcomes of the paths a computation may take.
+///
+/// Paths that fail do not count towards the lower bound, unless all of
+/// them fail. They do count towards the upper bound since they still
+/// consume budget.
+fn join<'a, V>(outcomes: Vec<Outcome<'a, V>>) -> Outcome<'a, V>
+where
+    V: Eval<'a>,
+{
+    let succeeds = outcomes.iter().any(|(value, _)| value.is_some());
+
+    let lower = outcomes
+        .iter()
+        .filter(|(value, _)| value.is_some() || !succeeds)
+        .map(|(_, cost)| cost.lower)
+        .reduce(min)
+        .unwrap_or(ExBudget::new(0, 0));
+
+    let upper = outcomes
+        .iter()
+        .map(|(_, cost)| cost.upper)
+        .try_fold(ExBudget::new(0, 0), |upper, cost| Some(max(upper, cost?)));
+
+    let values: Vec<_> = outcomes
+        .into_iter()
+        .filter_map(|(value, _)| value)
+        .collect();
+
+    let value = (!values.is_empty()).then(|| one_of(values));
+
+    (value, Cost { lower, upper })
+}
+
+fn one_of<'a, V>(values: Vec<Abstract<'a, V>>) -> Abstract<'a, V>
+where
+    V: Eval<'a>,
+{
+    let mut flat: Vec<Abstract<'a, V>> = Vec::with_capacity(values.len());
+
+    for value in values {
+        let nested = match value {
+            Abstract::OneOf(values) => values.as_ref().clone(),
+            value => vec![value],
+        };
+
+        for value in nested {
+            // Unknown values of the same shape only differ in size.
+            let merged = flat.iter_mut().any(|known| match (known, &value) {
+                (Abstract::Unknown(size, shape), Abstract::Unknown(other, other_shape))
+                    if shape == other_shape =>
+                {
+                    *size = size.union(*other);
+
+                    true
+                }
+                _ => false,
+            });
+
+            if !merged {
+                flat.push(value);
+            }
+        }
+    }
+
+    if flat.len() == 1 {
+        flat.remove(0)
+    } else {
+        Abstract::OneOf(Rc::new(flat))
+    }
+}
+
+/// The size the machine costs argument `index` of `fun` by. Some builtins
+/// cost an integer by its value rather than its size.
+fn argument_size<'a, V>(fun: DefaultFunction, index: usize, arg: &Abstract<'a, V>) -> SizeRange
+where
+    V: Eval<'a>,
+{
+    let literal = match (fun, index) {
+        (DefaultFunction::ReplicateByte, 0) | (DefaultFunction::IntegerToByteString, 1) => {
+            Some(Literal::Bytes)
+        }
+        (DefaultFunction::DropList, 0)
+        | (DefaultFunction::ShiftByteString, 1)
+        | (DefaultFunction::RotateByteString, 1) => Some(Literal::Value),
+        _ => None,
+    };
+
+    match (arg, literal) {
+        (Abstract::Known(Constant::Integer(i)), Some(literal)) => {
+            let value = i.abs().to_i64().unwrap_or(i64::MAX);
+
+            SizeRange::exact(literal.size(value))
+        }
+        (_, Some(literal)) => SizeRange::new(
+            0,
+            literal.size(match literal {
+                Literal::Bytes => INTEGER_TO_BYTE_STRING_MAXIMUM_OUTPUT_LENGTH,
+                Literal::Value => i64::MAX,
+            }),
+        ),
+        (Abstract::Known(constant), None) => SizeRange::exact(constant_ex_mem(constant)),
+        (Abstract::Unknown(size, _), None) => *size,
+        (Abstract::OneOf(values), None) => values
+            .iter()
+            .map(|value| argument_size(fun, index, value))
+            .reduce(SizeRange::union)
+            .unwrap_or(SizeRange::exact(1)),
+        _ => SizeRange::exact(1),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Literal {
+    /// A number of bytes, costed in words.
+    Bytes,
+    Value,
+}
+
+impl Literal {
+    fn size(self, value: i64) -> i64 {
+        match self {
+            Literal::Bytes if value == 0 => 0,
+            Literal::Bytes => (value - 1) / 8 + 1,
+            Literal::Value => value,
+        }
+    }
+}
+
+fn takes_result_from_argument(fun: DefaultFunction) -> bool {
+    use DefaultFunction::*;
+
+    matches!(
+        fun,
+        HeadList
+            | TailList
+            | FstPair
+            | SndPair
+            | UnConstrData
+            | UnMapData
+            | UnListData
+            | UnIData
+            | UnBData
+            | DropList
+            | IndexArray
+    )
+}
+
+fn result_shape(fun: DefaultFunction) -> Shape {
+    use DefaultFunction::*;
+
+    match fun {
+        EqualsInteger
+        | LessThanInteger
+        | LessThanEqualsInteger
+        | EqualsByteString
+        | LessThanByteString
+        | LessThanEqualsByteString
+        | EqualsString
+        | NullList
+        | EqualsData
+        | VerifyEd25519Signature
+        | VerifyEcdsaSecp256k1Signature
+        | VerifySchnorrSecp256k1Signature
+        | Bls12_381_G1_Equal
+        | Bls12_381_G2_Equal
+        | Bls12_381_FinalVerify
+        | ReadBit
+        | ValueContains => Shape::Bool,
+        AddInteger | SubtractInteger | MultiplyInteger | DivideInteger | QuotientInteger
+        | RemainderInteger | ModInteger | LengthOfByteString | IndexByteString | UnIData
+        | ByteStringToInteger | CountSetBits | FindFirstSetBit | ExpModInteger | LengthOfArray
+        | LookupCoin => Shape::Integer,
+        TailList | MkCons | UnListData | UnMapData | MkNilData | MkNilPairData | DropList => {
+            Shape::List
+        }
+        UnConstrData | MkPairData => Shape::Pair,
+        _ => Shape::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arena::Arena,
+        binder::DeBruijn,
+        constant::{Constant, Integer},
+        machine::{ExBudget, PlutusVersion},
+        program::{Program, Version},
+        syn::parse_program,
+        term::Term,
+    };
+
+    use super::{SizeRange, Unbounded};
+
+    fn parse<'a>(arena: &'a Arena, input: &'a str) -> &'a Program<'a, DeBruijn> {
+        parse_program(arena, input).into_result().unwrap()
+    }
+
+    /// The budget of running `program` on integers of the given values.
+    fn run<'a>(arena: &'a Arena, program: &'a Program<'a, DeBruijn>, args: &[i128]) -> ExBudget {
+        let program = args.iter().fold(program, |program, arg| {
+            program.apply(
+                arena,
+                Term::constant(arena, Constant::integer_from(arena, *arg)),
+            )
+        });
+
+        program
+            .eval_version(arena, PlutusVersion::V3)
+            .info
+            .consumed_budget
+    }
+
+    #[test]
+    fn loop_free_code_is_exact() {
+        let arena = Arena::new();
+
+        let program = parse(
+            &arena,
+            "(program 1.1.0 (lam x [(builtin multiplyInteger) x (con integer 3)]))",
+        );
+
+        let estimate = program.estimate_budget(PlutusVersion::V3, &[SizeRange::exact(2)]);
+
+        assert!(estimate.is_exact());
+        assert!(!estimate.may_fail);
+        assert_eq!(estimate.lower, run(&arena, program, &[1 << 100]));
+    }
+
+    #[test]
+    fn deep_loop_free_code_is_bounded() {
+        let arena = Arena::new();
+
+        let one = Term::integer_from(&arena, 1);
+        let identity =
+            Term::var(&arena, DeBruijn::new(&arena, 1)).lambda(&arena, DeBruijn::zero(&arena));
+
+        // Nested builtin calls, force/delay pairs and applications.
+        for shape in 0..3 {
+            let term = (0..10_000).fold(one, |term, _| match shape {
+                0 => Term::add_integer(&arena)
+                    .apply(&arena, term)
+                    .apply(&arena, one),
+                1 => term.delay(&arena).force(&arena),
+                _ => identity.apply(&arena, term),
+            });
+
+            let program = Program::new(&arena, Version::plutus_v3(&arena), term);
+
+            let estimate = program.estimate_budget(PlutusVersion::V3, &[]);
+            let actual = run(&arena, program, &[]);
+            let upper = estimate.upper.expect("loop-free code is bounded");
+
+            assert!(estimate.lower.cpu <= actual.cpu && actual.cpu <= upper.cpu);
+            assert!(estimate.lower.mem <= actual.mem && actual.mem <= upper.mem);
+
+            // Only builtin results have unknown sizes.
+            if shape > 0 {
+                assert_eq!(estimate.lower, actual);
+                assert!(estimate.is_exact());
+            }
+        }
+    }
+
+    #[test]
+    fn sizes_bound_the_budget() {
+        let arena = Arena::new();
+
+        let program = parse(
+            &arena,
+            "(program 1.1.0 (lam x (lam y [(builtin multiplyInteger) x y])))",
+        );
+
+        let estimate = program.estimate_budget(
+            PlutusVersion::V3,
+            &[SizeRange::new(1, 2), SizeRange::exact(2)],
+        );
+
+        assert_eq!(estimate.lower, run(&arena, program, &[7, 1 << 100]));
+        assert_eq!(
+            estimate.upper,
+            Ok(run(&arena, program, &[1 << 100, 1 << 100]))
+        );
+    }
+
+    #[test]
+    fn bounds_hold_between_the_corners() {
+        let arena = Arena::new();
+
+        let program = parse(
+            &arena,
+            "(program 1.1.0 (lam x (lam y [(builtin divideInteger) x y])))",
+        );
+
+        let estimate = program.estimate_budget(
+            PlutusVersion::V3,
+            &[SizeRange::exact(100), SizeRange::new(1, 100)],
+        );
+
+        // An integer of `words` 64-bit words.
+        let integer = |words: usize| {
+            let value = arena.alloc_integer(Integer::from(1) << (64 * (words - 1)));
+
+            Term::constant(&arena, Constant::integer(&arena, value))
+        };
+
+        let consumed = program
+            .apply(&arena, integer(100))
+            .apply(&arena, integer(57))
+            .eval_version(&arena, PlutusVersion::V3)
+            .info
+            .consumed_budget;
+
+        assert_eq!(consumed.cpu, 4_005_188);
+        assert!(estimate.upper.unwrap().cpu >= consumed.cpu);
+        assert!(estimate.lower.cpu <= consumed.cpu);
+
+        let estimate = program.estimate_budget(
+            PlutusVersion::V3,
+            &[SizeRange::new(0, i64::MAX), SizeRange::exact(1)],
+        );
+
+        assert_eq!(estimate.upper, Err(Unbounded::Costing));
+    }
+
+    #[test]
+    fn branches_bound_the_budget() {
+        let arena = Arena::new();
+
+        let program = parse(
+            &arena,
+            r#"(program 1.1.0
+                (lam x
+                  (force
+                    [(force (builtin ifThenElse))
+                      [(builtin equalsInteger) x (con integer 0)]
+                      (delay (con integer 1))
+                      (delay [(builtin sha2_256) (con bytestring #00)])])))"#,
+        );
+
+        let estimate = program.estimate_budget(PlutusVersion::V3, &[SizeRange::exact(1)]);
+
+        assert_eq!(estimate.lower, run(&arena, program, &[0]));
+        assert_eq!(estimate.upper, Ok(run(&arena, program, &[1])));
+        assert!(!estimate.is_exact());
+
+        let failing = parse(
+            &arena,
+            r#"(program 1.1.0
+                (lam x
+                  (case [(builtin lessThanInteger) x (con integer 0)] (con integer 1) (error))))"#,
+        );
+
+        let estimate = failing.estimate_budget(PlutusVersion::V3, &[SizeRange::exact(1)]);
+
+        assert!(estimate.may_fail);
+        assert_eq!(estimate.lower, run(&arena, failing, &[1]));
+    }
+
+    #[test]
+    fn recursion_is_unbounded() {
+        let arena = Arena::new();
+
+        // A countdown through the Z combinator.
+        let program = parse(
+            &arena,
+            r#"(program 1.1.0
+                (lam n
+                  [(lam f [(lam x [f (lam v [x x v])]) (lam x [f (lam v [x x v])])])
+                   (lam self (lam i
+                     (force
+                       [(force (builtin ifThenElse))
+                         [(builtin equalsInteger) i (con integer 0)]
+                         (delay (con unit ()))
+                         (delay [self [(builtin subtractInteger) i (con integer 1)]])])))
+                   n]))"#,
+        );
+
+        let estimate = program.estimate_budget(PlutusVersion::V3, &[SizeRange::exact(1)]);
+
+        assert_eq!(estimate.upper, Err(Unbounded::Recursion));
+        assert!(estimate.lower.cpu <= run(&arena, program, &[0]).cpu);
+        assert!(estimate.never_fits(ExBudget::new(1, 1)));
+        assert!(!estimate.never_fits(ExBudget::default()));
+    }
+}
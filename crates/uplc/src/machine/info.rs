@@ -1,6 +1,6 @@
 use super::ExBudget;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MachineInfo {
     pub remaining_budget: ExBudget,
     pub consumed_budget: ExBudget,
@@ -1,20 +1,36 @@
 mod cek;
 mod context;
 pub(crate) mod cost_model;
+mod differential;
 mod discharge;
 mod env;
 mod error;
+mod estimate;
 mod eval_result;
 mod info;
 mod runtime;
+mod scaling;
+mod snapshot;
 mod state;
 mod value;
 
 pub use cek::*;
+pub use cost_model::builtin_costs::{
+    builtin_costs_v1::BuiltinCostsV1, builtin_costs_v2::BuiltinCostsV2,
+    builtin_costs_v3::BuiltinCostsV3,
+};
 pub use cost_model::ex_budget::*;
-pub use cost_model::CostModel;
+pub use cost_model::{
+    mainnet_params, BuiltinCalibration, BuiltinFormula, CalibrationConfig, CalibrationReport,
+    CostModel, CostModelError, Sample,
+};
+pub use differential::*;
 pub use error::*;
+pub(crate) use estimate::estimate;
+pub use estimate::{BudgetEstimate, SizeRange, Unbounded};
 pub use eval_result::*;
 pub use info::*;
 pub use runtime::BuiltinSemantics;
 pub use runtime::PlutusVersion;
+pub use scaling::*;
+pub use snapshot::*;
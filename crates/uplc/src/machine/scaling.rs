@@ -0,0 +1,441 @@
+use std::fmt;
+
+use crate::{arena::Arena, constant::Integer, data::PlutusData};
+
+use super::{ErrorKind, ExBudget, PlutusVersion};
+
+/// Sizes beyond this are not considered when looking for the largest input
+/// that fits a budget. `u32::MAX` also fits a 32-bit `usize`.
+const MAX_SEARCH_SIZE: usize = u32::MAX as usize;
+
+/// How to evaluate a program over inputs of growing size, see
+/// [`Program::eval_scaling`](crate::program::Program::eval_scaling).
+///
+/// Without an explicit cost model the default one for `plutus_version` is used.
+#[derive(Debug, Clone)]
+pub struct ScalingConfig<'c> {
+    pub plutus_version: PlutusVersion,
+    pub cost_model: Option<&'c [i64]>,
+    pub initial_budget: ExBudget,
+    pub sizes: Vec<usize>,
+}
+
+impl<'c> ScalingConfig<'c> {
+    pub fn new(plutus_version: PlutusVersion) -> Self {
+        ScalingConfig {
+            plutus_version,
+            cost_model: None,
+            initial_budget: ExBudget::default(),
+            sizes: vec![1, 2, 4, 8, 16, 32],
+        }
+    }
+
+    pub fn with_cost_model(self, cost_model: &'c [i64]) -> Self {
+        ScalingConfig {
+            cost_model: Some(cost_model),
+            ..self
+        }
+    }
+
+    pub fn with_budget(self, initial_budget: ExBudget) -> Self {
+        ScalingConfig {
+            initial_budget,
+            ..self
+        }
+    }
+
+    pub fn with_sizes(self, sizes: Vec<usize>) -> Self {
+        ScalingConfig { sizes, ..self }
+    }
+}
+
+/// Families of generated inputs, indexed by size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFamily {
+    /// A list of `size` integers.
+    List,
+    /// A map with `size` entries from integers to integers.
+    Map,
+    /// A constructor with `size` integer fields.
+    Constr,
+    /// An integer of `size` 64-bit words.
+    Integer,
+    /// A byte string of `size` bytes.
+    ByteString,
+}
+
+impl DataFamily {
+    pub fn generate(self, arena: &Arena, size: usize) -> &PlutusData<'_> {
+        let items = || -> &[&PlutusData<'_>] {
+            let items: Vec<_> = (0..size)
+                .map(|i| PlutusData::integer_from(arena, i as i128))
+                .collect();
+
+            arena.alloc(items)
+        };
+
+        match self {
+            DataFamily::List => PlutusData::list(arena, items()),
+            DataFamily::Map => {
+                let entries: Vec<_> = items().iter().map(|item| (*item, *item)).collect();
+
+                PlutusData::map(arena, arena.alloc(entries))
+            }
+            DataFamily::Constr => PlutusData::constr(arena, 0, items()),
+            DataFamily::Integer => {
+                let integer: Integer = (Integer::from(1) << (64 * size)) - 1;
+
+                PlutusData::integer(arena, arena.alloc_integer(integer))
+            }
+            DataFamily::ByteString => PlutusData::byte_string(arena, arena.alloc(vec![0xff; size])),
+        }
+    }
+}
+
+/// The outcome of evaluating the program on the input of one size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingSample {
+    pub size: usize,
+    pub consumed: ExBudget,
+    pub error: Option<ErrorKind>,
+}
+
+/// A polynomial of degree at most two in the input size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Curve {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl Curve {
+    pub fn at(&self, size: usize) -> f64 {
+        let n = size as f64;
+
+        self.constant + self.linear * n + self.quadratic * n * n
+    }
+
+    /// Least squares over `points`, of the highest degree their number of
+    /// distinct sizes allows.
+    fn fit(points: &[(usize, i64)]) -> Option<Curve> {
+        let mut sizes: Vec<_> = points.iter().map(|(size, _)| *size).collect();
+
+        sizes.sort_unstable();
+        sizes.dedup();
+
+        let coefficients = match sizes.len() {
+            0 => return None,
+            1 => vec![points.iter().map(|(_, v)| *v as f64).sum::<f64>() / points.len() as f64],
+            2 => solve(points, 2)?,
+            _ => solve(points, 3)?,
+        };
+
+        let coefficient = |i: usize| coefficients.get(i).copied().unwrap_or_default();
+
+        Some(Curve {
+            constant: coefficient(0),
+            linear: coefficient(1),
+            quadratic: coefficient(2),
+        })
+    }
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.0}", self.constant)?;
+
+        if self.linear != 0.0 {
+            write!(f, " {} {:.2} * n", sign(self.linear), self.linear.abs())?;
+        }
+
+        if self.quadratic != 0.0 {
+            write!(
+                f,
+                " {} {:.4} * n^2",
+                sign(self.quadratic),
+                self.quadratic.abs()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn sign(x: f64) -> char {
+    if x < 0.0 {
+        '-'
+    } else {
+        '+'
+    }
+}
+
+/// How the budget of a program grows with the size of its input.
+///
+/// The curves are fitted to the samples that succeeded only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalingReport {
+    pub samples: Vec<ScalingSample>,
+    pub cpu: Option<Curve>,
+    pub mem: Option<Curve>,
+}
+
+impl ScalingReport {
+    pub fn new(samples: Vec<ScalingSample>) -> Self {
+        let points = |budget: fn(&ExBudget) -> i64| -> Vec<(usize, i64)> {
+            samples
+                .iter()
+                .filter(|sample| sample.error.is_none())
+                .map(|sample| (sample.size, budget(&sample.consumed)))
+                .collect()
+        };
+
+        let cpu = Curve::fit(&points(|budget| budget.cpu));
+        let mem = Curve::fit(&points(|budget| budget.mem));
+
+        ScalingReport { samples, cpu, mem }
+    }
+
+    /// The budget the fitted curves predict for an input of `size`.
+    pub fn predict(&self, size: usize) -> Option<ExBudget> {
+        let (cpu, mem) = self.cpu.zip(self.mem)?;
+
+        Some(ExBudget::new(
+            mem.at(size).round() as i64,
+            cpu.at(size).round() as i64,
+        ))
+    }
+
+    /// The largest input size predicted to fit in `limit`, or `None` if not
+    /// even an empty input does.
+    pub fn max_size(&self, limit: ExBudget) -> Option<usize> {
+        let fits = |size| {
+            self.predict(size)
+                .is_some_and(|budget| budget.cpu <= limit.cpu && budget.mem <= limit.mem)
+        };
+
+        if !fits(0) {
+            return None;
+        }
+
+        // Find a size that does not fit, then bisect down to the boundary.
+        let mut low = 0;
+        let mut high = 1;
+
+        while fits(high) {
+            if high >= MAX_SEARCH_SIZE {
+                return Some(MAX_SEARCH_SIZE);
+            }
+
+            low = high;
+            high = high.saturating_mul(2).min(MAX_SEARCH_SIZE);
+        }
+
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+
+            if fits(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Some(low)
+    }
+}
+
+impl fmt::Display for ScalingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for sample in &self.samples {
+            write!(
+                f,
+                "size {}: cpu {}, mem {}",
+                sample.size, sample.consumed.cpu, sample.consumed.mem
+            )?;
+
+            match sample.error {
+                Some(kind) => writeln!(f, " (failed with {kind:?})")?,
+                None => writeln!(f)?,
+            }
+        }
+
+        let curve = |curve: &Option<Curve>| match curve {
+            Some(curve) => curve.to_string(),
+            None => "none".to_string(),
+        };
+
+        writeln!(f, "cpu: {}", curve(&self.cpu))?;
+        writeln!(f, "mem: {}", curve(&self.mem))
+    }
+}
+
+/// Solve the normal equations for the first `terms` powers of the size.
+fn solve(points: &[(usize, i64)], terms: usize) -> Option<Vec<f64>> {
+    let mut system = vec![vec![0.0f64; terms + 1]; terms];
+
+    for (size, value) in points {
+        let powers: Vec<f64> = (0..terms).map(|i| (*size as f64).powi(i as i32)).collect();
+
+        for (i, row) in system.iter_mut().enumerate() {
+            for (j, power) in powers.iter().enumerate() {
+                row[j] += powers[i] * power;
+            }
+
+            row[terms] += powers[i] * *value as f64;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for column in 0..terms {
+        let pivot = (column..terms).max_by(|a, b| {
+            system[*a][column]
+                .abs()
+                .total_cmp(&system[*b][column].abs())
+        })?;
+
+        if system[pivot][column].abs() < 1e-9 {
+            return None;
+        }
+
+        system.swap(column, pivot);
+
+        for row in column + 1..terms {
+            let factor = system[row][column] / system[column][column];
+
+            let (above, below) = system.split_at_mut(row);
+
+            for (cell, pivot) in below[0].iter_mut().zip(&above[column]).skip(column) {
+                *cell -= factor * pivot;
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; terms];
+
+    for row in (0..terms).rev() {
+        let known: f64 = (row + 1..terms).map(|j| system[row][j] * solution[j]).sum();
+
+        solution[row] = (system[row][terms] - known) / system[row][row];
+    }
+
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        arena::Arena,
+        machine::{ExBudget, PlutusVersion},
+        syn::parse_program,
+    };
+
+    use super::{Curve, DataFamily, ScalingConfig, ScalingReport, ScalingSample, MAX_SEARCH_SIZE};
+
+    #[test]
+    fn fits_exact_polynomials() {
+        let points: Vec<_> = (1..6)
+            .map(|n| (n, 7 + 3 * n as i64 + 2 * (n * n) as i64))
+            .collect();
+
+        let curve = Curve::fit(&points).unwrap();
+
+        assert!((curve.constant - 7.0).abs() < 1e-6);
+        assert!((curve.linear - 3.0).abs() < 1e-6);
+        assert!((curve.quadratic - 2.0).abs() < 1e-6);
+
+        let line = Curve::fit(&[(1, 10), (3, 20)]).unwrap();
+
+        assert!((line.at(5) - 30.0).abs() < 1e-6);
+        assert_eq!(line.quadratic, 0.0);
+    }
+
+    #[test]
+    fn finds_largest_size_within_limit() {
+        let samples = (1..4)
+            .map(|size| ScalingSample {
+                size,
+                consumed: ExBudget::new(10 * size as i64, 100 + 1000 * size as i64),
+                error: None,
+            })
+            .collect();
+
+        let report = ScalingReport::new(samples);
+
+        assert_eq!(report.max_size(ExBudget::new(1_000, 50_100)), Some(50));
+        assert_eq!(report.max_size(ExBudget::new(25, 50_100)), Some(2));
+        assert_eq!(report.max_size(ExBudget::new(1_000, 50)), None);
+
+        let flat = (1..4)
+            .map(|size| ScalingSample {
+                size,
+                consumed: ExBudget::new(10, 100),
+                error: None,
+            })
+            .collect();
+
+        assert_eq!(
+            ScalingReport::new(flat).max_size(ExBudget::new(10, 100)),
+            Some(MAX_SEARCH_SIZE)
+        );
+    }
+
+    #[test]
+    fn scales_with_list_length() {
+        let arena = Arena::new();
+
+        // Counts the elements of a list of data.
+        let program = parse_program(
+            &arena,
+            r#"(program 1.1.0
+                (lam d
+                  [(lam f [(lam x [f (lam v [x x v])]) (lam x [f (lam v [x x v])])])
+                   (lam self (lam xs
+                     (force
+                       [(force (force (builtin chooseList)))
+                         xs
+                         (delay (con integer 0))
+                         (delay [(builtin addInteger)
+                                  (con integer 1)
+                                  [self [(force (builtin tailList)) xs]]])])))
+                   [(builtin unListData) d]]))"#,
+        )
+        .into_result()
+        .unwrap();
+
+        let config = ScalingConfig::new(PlutusVersion::V3).with_sizes(vec![0, 5, 10, 20]);
+
+        let report = program
+            .eval_scaling(&arena, &config, |arena, size| {
+                DataFamily::List.generate(arena, size)
+            })
+            .unwrap();
+
+        assert!(report.samples.iter().all(|sample| sample.error.is_none()));
+
+        let cpu = report.cpu.unwrap();
+
+        assert!(cpu.linear > 0.0);
+        assert!(cpu.quadratic.abs() < 1e-3 * cpu.linear);
+
+        let limit = ExBudget::default();
+        let max = report.max_size(limit).unwrap();
+
+        let fits = |budget: ExBudget| budget.cpu <= limit.cpu && budget.mem <= limit.mem;
+
+        assert!(fits(report.predict(max).unwrap()));
+        assert!(!fits(report.predict(max + 1).unwrap()));
+
+        let failing = ScalingConfig::new(PlutusVersion::V3)
+            .with_sizes(vec![1, 100])
+            .with_budget(report.samples[2].consumed);
+
+        let report = program
+            .eval_scaling(&arena, &failing, |arena, size| {
+                DataFamily::List.generate(arena, size)
+            })
+            .unwrap();
+
+        assert!(report.samples[0].error.is_none());
+        assert!(report.samples[1].error.is_some());
+    }
+}
@@ -0,0 +1,696 @@
+use bumpalo::collections::{CollectIn, Vec as BumpVec};
+
+use crate::{
+    arena::Arena,
+    binder::{DeBruijn, Eval},
+    builtin::DefaultFunction,
+    constant::{Constant, Integer},
+    data::PlutusData,
+    ledger_value::{CurrencyEntry, LedgerValue, TokenEntry},
+    term::Term,
+    typ::Type,
+};
+
+use super::{
+    discharge::{self, Build},
+    info::MachineInfo,
+    value::Value,
+    EvalResult, MachineError, RuntimeError,
+};
+
+/// An owned, arena-independent outcome of an evaluation.
+///
+/// Unlike [`EvalResult`], a snapshot does not borrow from the [`Arena`] it was
+/// produced in, so it can outlive `arena.reset()` and be sent across threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalSnapshot {
+    pub term: Result<OwnedTerm, ErrorSnapshot>,
+    pub info: MachineInfo,
+}
+
+impl<'a, V> From<EvalResult<'a, V>> for EvalSnapshot
+where
+    V: Eval<'a>,
+{
+    fn from(result: EvalResult<'a, V>) -> Self {
+        EvalSnapshot {
+            term: result
+                .term
+                .as_ref()
+                .map(|term| OwnedTerm::from_term(term))
+                .map_err(ErrorSnapshot::from),
+            info: result.info,
+        }
+    }
+}
+
+/// A rendered [`MachineError`] together with owned copies of its payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorSnapshot {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub payload: Vec<Payload>,
+}
+
+/// The variant of the [`MachineError`] a snapshot was taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    ExplicitErrorTerm,
+    NonFunctionApplication,
+    NotAConstant,
+    OpenTermEvaluated,
+    OutOfExError,
+    UnexpectedBuiltinTermArgument,
+    NonPolymorphicInstantiation,
+    BuiltinTermArgumentExpected,
+    NonConstrScrutinized,
+    MissingCaseBranch,
+    Runtime,
+    MaxConstrTagExceeded,
+    NoCostForBuiltin,
+}
+
+/// A value carried by an error. Machine values are discharged to terms.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    Term(OwnedTerm),
+    Constant(OwnedConstant),
+    Data(OwnedData),
+    Type(OwnedType),
+}
+
+impl<'a, V> From<&MachineError<'a, V>> for ErrorSnapshot
+where
+    V: Eval<'a>,
+{
+    fn from(error: &MachineError<'a, V>) -> Self {
+        let value = |value| Payload::Term(OwnedTerm::from_value(value));
+        let term = |term| Payload::Term(OwnedTerm::from_term(term));
+
+        let (kind, payload) = match error {
+            MachineError::ExplicitErrorTerm => (ErrorKind::ExplicitErrorTerm, vec![]),
+            MachineError::NonFunctionApplication(argument, function) => (
+                ErrorKind::NonFunctionApplication,
+                vec![value(argument), value(function)],
+            ),
+            MachineError::NotAConstant(v) => (ErrorKind::NotAConstant, vec![value(v)]),
+            MachineError::OpenTermEvaluated(t) => (ErrorKind::OpenTermEvaluated, vec![term(t)]),
+            MachineError::OutOfExError(_) => (ErrorKind::OutOfExError, vec![]),
+            MachineError::UnexpectedBuiltinTermArgument(t) => {
+                (ErrorKind::UnexpectedBuiltinTermArgument, vec![term(t)])
+            }
+            MachineError::NonPolymorphicInstantiation(v) => {
+                (ErrorKind::NonPolymorphicInstantiation, vec![value(v)])
+            }
+            MachineError::BuiltinTermArgumentExpected(t) => {
+                (ErrorKind::BuiltinTermArgumentExpected, vec![term(t)])
+            }
+            MachineError::NonConstrScrutinized(v) => {
+                (ErrorKind::NonConstrScrutinized, vec![value(v)])
+            }
+            MachineError::MissingCaseBranch(branches, v) => {
+                let mut payload: Vec<_> = branches.iter().map(|branch| term(branch)).collect();
+
+                payload.push(value(v));
+
+                (ErrorKind::MissingCaseBranch, payload)
+            }
+            MachineError::Runtime(runtime) => (ErrorKind::Runtime, runtime_payload(runtime)),
+            MachineError::MaxConstrTagExceeded(v) => {
+                (ErrorKind::MaxConstrTagExceeded, vec![value(v)])
+            }
+            MachineError::NoCostForBuiltin(_) => (ErrorKind::NoCostForBuiltin, vec![]),
+        };
+
+        ErrorSnapshot {
+            kind,
+            message: error.to_string(),
+            payload,
+        }
+    }
+}
+
+fn runtime_payload(error: &RuntimeError<'_>) -> Vec<Payload> {
+    let constant = |c: &Constant<'_>| Payload::Constant(OwnedConstant::from(c));
+    let integer = |i: &Integer| Payload::Constant(OwnedConstant::Integer(i.clone()));
+
+    match error {
+        RuntimeError::ByteStringOutOfBounds(bytes, index) => vec![
+            Payload::Constant(OwnedConstant::ByteString(bytes.to_vec())),
+            integer(index),
+        ],
+        RuntimeError::TypeMismatch(expected, c) => {
+            vec![Payload::Type(OwnedType::from(expected)), constant(c)]
+        }
+        RuntimeError::ExpectedPair(c)
+        | RuntimeError::ExpectedList(c)
+        | RuntimeError::ExpectedArray(c)
+        | RuntimeError::NotData(c)
+        | RuntimeError::MkConsTypeMismatch(c) => vec![constant(c)],
+        RuntimeError::MalFormedData(d) | RuntimeError::SerializationError(d) => {
+            vec![Payload::Data(OwnedData::from(*d))]
+        }
+        RuntimeError::EmptyList(list) => list.iter().map(|c| constant(c)).collect(),
+        RuntimeError::DivisionByZero(numerator, denominator) => {
+            vec![integer(numerator), integer(denominator)]
+        }
+        RuntimeError::ByteStringConsNotAByte(i)
+        | RuntimeError::IntegerToByteStringSizeTooBig(i, _)
+        | RuntimeError::IntegerToByteStringSizeTooSmall(i, _)
+        | RuntimeError::IntegerToByteStringNegativeInput(i)
+        | RuntimeError::IntegerToByteStringNegativeSize(i)
+        | RuntimeError::ReadBitOutOfBounds(i, _)
+        | RuntimeError::WriteBitsOutOfBounds(i, _)
+        | RuntimeError::OutsideByteBounds(i)
+        | RuntimeError::OutsideUsizeBounds(i)
+        | RuntimeError::ReplicateByteSizeTooBig(i, _)
+        | RuntimeError::ReplicateByteSizeTooSmall(i, _)
+        | RuntimeError::ReplicateByteNegativeInput(i)
+        | RuntimeError::ReplicateByteNegativeSize(i)
+        | RuntimeError::IndexArrayOutOfBounds(i, _) => vec![integer(i)],
+        RuntimeError::UnexpectedEd25519PublicKeyLength(_)
+        | RuntimeError::UnexpectedEd25519SignatureLength(_)
+        | RuntimeError::Secp256k1(_)
+        | RuntimeError::DecodeUtf8(_)
+        | RuntimeError::Bls(_)
+        | RuntimeError::HashToCurveDstTooBig
+        | RuntimeError::EmptyByteArray
+        | RuntimeError::MultiScalarMulScalarOutOfBounds
+        | RuntimeError::Value(_) => vec![],
+    }
+}
+
+/// An owned copy of a [`Term`].
+///
+/// Nodes are stored in post-order, children always come before their parent
+/// and the root is the last node. Building, comparing and dropping a term is
+/// therefore iterative, whatever its depth. Binders are kept as De Bruijn
+/// indices, names of named binders are not preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedTerm {
+    nodes: Vec<OwnedNode>,
+}
+
+/// A single node of an [`OwnedTerm`]. Children are indices into the term's
+/// nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedNode {
+    Var(usize),
+    Lambda(usize),
+    Apply { function: usize, argument: usize },
+    Delay(usize),
+    Force(usize),
+    Case { constr: usize, branches: Vec<usize> },
+    Constr { tag: usize, fields: Vec<usize> },
+    Constant(OwnedConstant),
+    Builtin(DefaultFunction),
+    Error,
+}
+
+impl OwnedTerm {
+    pub fn from_term<'a, V>(term: &'a Term<'a, V>) -> Self
+    where
+        V: Eval<'a>,
+    {
+        let mut build = NodeBuild(Vec::new());
+
+        discharge::discharge_term(&mut build, term);
+
+        OwnedTerm { nodes: build.0 }
+    }
+
+    /// Discharge a machine value, substituting closure environments like
+    /// the machine does for its final result.
+    pub fn from_value<'a, V>(value: &'a Value<'a, V>) -> Self
+    where
+        V: Eval<'a>,
+    {
+        let mut build = NodeBuild(Vec::new());
+
+        discharge::discharge_value(&mut build, value);
+
+        OwnedTerm { nodes: build.0 }
+    }
+
+    pub fn root(&self) -> &OwnedNode {
+        &self.nodes[self.nodes.len() - 1]
+    }
+
+    pub fn node(&self, index: usize) -> &OwnedNode {
+        &self.nodes[index]
+    }
+
+    pub fn nodes(&self) -> &[OwnedNode] {
+        &self.nodes
+    }
+
+    /// Allocate the term back into an arena.
+    pub fn to_term<'a>(&self, arena: &'a Arena) -> &'a Term<'a, DeBruijn> {
+        let mut built: Vec<&'a Term<'a, DeBruijn>> = Vec::with_capacity(self.nodes.len());
+
+        let many = |built: &Vec<&'a Term<'a, DeBruijn>>, indices: &[usize]| {
+            let terms: BumpVec<'_, _> = indices
+                .iter()
+                .map(|index| built[*index])
+                .collect_in(arena.as_bump());
+
+            arena.alloc(terms) as &'a [&'a Term<'a, DeBruijn>]
+        };
+
+        for node in &self.nodes {
+            let term = match node {
+                OwnedNode::Var(index) => Term::var(arena, DeBruijn::new(arena, *index)),
+                OwnedNode::Lambda(body) => built[*body].lambda(arena, DeBruijn::zero(arena)),
+                OwnedNode::Apply { function, argument } => {
+                    built[*function].apply(arena, built[*argument])
+                }
+                OwnedNode::Delay(body) => built[*body].delay(arena),
+                OwnedNode::Force(body) => built[*body].force(arena),
+                OwnedNode::Case { constr, branches } => {
+                    Term::case(arena, built[*constr], many(&built, branches))
+                }
+                OwnedNode::Constr { tag, fields } => {
+                    Term::constr(arena, *tag, many(&built, fields))
+                }
+                OwnedNode::Constant(constant) => Term::constant(arena, constant.to_constant(arena)),
+                OwnedNode::Builtin(fun) => Term::builtin(arena, arena.alloc(*fun)),
+                OwnedNode::Error => Term::error(arena),
+            };
+
+            built.push(term);
+        }
+
+        built[built.len() - 1]
+    }
+}
+
+/// Appends the discharged nodes in post-order, never sharing a subterm.
+struct NodeBuild(Vec<OwnedNode>);
+
+impl NodeBuild {
+    fn push(&mut self, node: OwnedNode) -> usize {
+        self.0.push(node);
+
+        self.0.len() - 1
+    }
+}
+
+impl<'a, V> Build<'a, V> for NodeBuild
+where
+    V: Eval<'a>,
+{
+    type Output = usize;
+
+    fn shared(&mut self, _term: &'a Term<'a, V>) -> Option<usize> {
+        None
+    }
+
+    fn leaf(&mut self, term: &'a Term<'a, V>) -> usize {
+        let node = match term {
+            Term::Var(name) => OwnedNode::Var(name.index()),
+            Term::Constant(constant) => OwnedNode::Constant(OwnedConstant::from(*constant)),
+            Term::Builtin(fun) => OwnedNode::Builtin(**fun),
+            Term::Error => OwnedNode::Error,
+            Term::Lambda { .. } => unreachable!("a lambda is not a leaf"),
+            Term::Apply { .. } => unreachable!("an application is not a leaf"),
+            Term::Delay(_) => unreachable!("a delay is not a leaf"),
+            Term::Force(_) => unreachable!("a force is not a leaf"),
+            Term::Case { .. } => unreachable!("a case is not a leaf"),
+            Term::Constr { .. } => unreachable!("a constr is not a leaf"),
+        };
+
+        self.push(node)
+    }
+
+    fn constant(&mut self, constant: &'a Constant<'a>) -> usize {
+        self.push(OwnedNode::Constant(OwnedConstant::from(constant)))
+    }
+
+    fn builtin(&mut self, fun: &'a DefaultFunction) -> usize {
+        self.push(OwnedNode::Builtin(*fun))
+    }
+
+    fn lambda(&mut self, _parameter: &'a V, body: usize) -> usize {
+        self.push(OwnedNode::Lambda(body))
+    }
+
+    fn apply(&mut self, function: usize, argument: usize) -> usize {
+        self.push(OwnedNode::Apply { function, argument })
+    }
+
+    fn delay(&mut self, body: usize) -> usize {
+        self.push(OwnedNode::Delay(body))
+    }
+
+    fn force(&mut self, body: usize) -> usize {
+        self.push(OwnedNode::Force(body))
+    }
+
+    fn constr(&mut self, tag: usize, fields: std::vec::Drain<'_, usize>) -> usize {
+        let fields = fields.collect();
+
+        self.push(OwnedNode::Constr { tag, fields })
+    }
+
+    fn case(&mut self, constr: usize, branches: std::vec::Drain<'_, usize>) -> usize {
+        let branches = branches.collect();
+
+        self.push(OwnedNode::Case { constr, branches })
+    }
+}
+
+/// An owned copy of a [`Constant`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedConstant {
+    Integer(Integer),
+    ByteString(Vec<u8>),
+    String(String),
+    Boolean(bool),
+    Data(OwnedData),
+    ProtoList(OwnedType, Vec<OwnedConstant>),
+    ProtoArray(OwnedType, Vec<OwnedConstant>),
+    ProtoPair(OwnedType, OwnedType, Box<OwnedConstant>, Box<OwnedConstant>),
+    Unit,
+    Bls12_381G1Element(Box<blst::blst_p1>),
+    Bls12_381G2Element(Box<blst::blst_p2>),
+    Bls12_381MlResult(Box<blst::blst_fp12>),
+    /// Currency entries in ledger order.
+    Value(Vec<OwnedCurrencyEntry>),
+}
+
+/// An owned copy of a [`CurrencyEntry`], tokens are `(name, quantity)` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedCurrencyEntry {
+    pub currency: Vec<u8>,
+    pub tokens: Vec<(Vec<u8>, Integer)>,
+}
+
+impl From<&Constant<'_>> for OwnedConstant {
+    fn from(constant: &Constant<'_>) -> Self {
+        match constant {
+            Constant::Integer(i) => OwnedConstant::Integer((*i).clone()),
+            Constant::ByteString(b) => OwnedConstant::ByteString(b.to_vec()),
+            Constant::String(s) => OwnedConstant::String(s.to_string()),
+            Constant::Boolean(b) => OwnedConstant::Boolean(*b),
+            Constant::Data(d) => OwnedConstant::Data(OwnedData::from(*d)),
+            Constant::ProtoList(t, items) => OwnedConstant::ProtoList(
+                OwnedType::from(*t),
+                items.iter().map(|c| OwnedConstant::from(*c)).collect(),
+            ),
+            Constant::ProtoArray(t, items) => OwnedConstant::ProtoArray(
+                OwnedType::from(*t),
+                items.iter().map(|c| OwnedConstant::from(*c)).collect(),
+            ),
+            Constant::ProtoPair(t1, t2, first, second) => OwnedConstant::ProtoPair(
+                OwnedType::from(*t1),
+                OwnedType::from(*t2),
+                Box::new(OwnedConstant::from(*first)),
+                Box::new(OwnedConstant::from(*second)),
+            ),
+            Constant::Unit => OwnedConstant::Unit,
+            Constant::Bls12_381G1Element(g1) => OwnedConstant::Bls12_381G1Element(Box::new(**g1)),
+            Constant::Bls12_381G2Element(g2) => OwnedConstant::Bls12_381G2Element(Box::new(**g2)),
+            Constant::Bls12_381MlResult(ml) => OwnedConstant::Bls12_381MlResult(Box::new(**ml)),
+            Constant::Value(v) => OwnedConstant::Value(
+                v.entries
+                    .iter()
+                    .map(|entry| {
+                        let tokens = entry
+                            .tokens
+                            .iter()
+                            .map(|token| (token.name.to_vec(), token.quantity.clone()))
+                            .collect();
+
+                        OwnedCurrencyEntry {
+                            currency: entry.currency.to_vec(),
+                            tokens,
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl OwnedConstant {
+    pub fn to_constant<'a>(&self, arena: &'a Arena) -> &'a Constant<'a> {
+        let many = |items: &[OwnedConstant]| {
+            let constants: BumpVec<'_, _> = items
+                .iter()
+                .map(|c| c.to_constant(arena))
+                .collect_in(arena.as_bump());
+
+            arena.alloc(constants) as &'a [&'a Constant<'a>]
+        };
+
+        match self {
+            OwnedConstant::Integer(i) => Constant::integer(arena, arena.alloc_integer(i.clone())),
+            OwnedConstant::ByteString(b) => {
+                Constant::byte_string(arena, arena.alloc(b.clone()).as_slice())
+            }
+            OwnedConstant::String(s) => Constant::string(arena, arena.alloc(s.clone()).as_str()),
+            OwnedConstant::Boolean(b) => Constant::bool(arena, *b),
+            OwnedConstant::Data(d) => Constant::data(arena, d.to_data(arena)),
+            OwnedConstant::ProtoList(t, items) => {
+                Constant::proto_list(arena, t.to_type(arena), many(items))
+            }
+            OwnedConstant::ProtoArray(t, items) => {
+                Constant::proto_array(arena, t.to_type(arena), many(items))
+            }
+            OwnedConstant::ProtoPair(t1, t2, first, second) => Constant::proto_pair(
+                arena,
+                t1.to_type(arena),
+                t2.to_type(arena),
+                first.to_constant(arena),
+                second.to_constant(arena),
+            ),
+            OwnedConstant::Unit => Constant::unit(arena),
+            OwnedConstant::Bls12_381G1Element(g1) => Constant::g1(arena, arena.alloc(**g1)),
+            OwnedConstant::Bls12_381G2Element(g2) => Constant::g2(arena, arena.alloc(**g2)),
+            OwnedConstant::Bls12_381MlResult(ml) => Constant::ml_result(arena, arena.alloc(**ml)),
+            OwnedConstant::Value(entries) => {
+                let entries: BumpVec<'_, _> = entries
+                    .iter()
+                    .map(|entry| {
+                        let tokens: BumpVec<'_, _> = entry
+                            .tokens
+                            .iter()
+                            .map(|(name, quantity)| TokenEntry {
+                                name: arena.alloc(name.clone()).as_slice(),
+                                quantity: arena.alloc_integer(quantity.clone()),
+                            })
+                            .collect_in(arena.as_bump());
+
+                        CurrencyEntry {
+                            currency: arena.alloc(entry.currency.clone()).as_slice(),
+                            tokens: arena.alloc(tokens),
+                        }
+                    })
+                    .collect_in(arena.as_bump());
+
+                let entries = arena.alloc(entries);
+
+                let (size, negative_count) = crate::ledger_value::count_stats(entries);
+
+                Constant::ledger_value(
+                    arena,
+                    arena.alloc(LedgerValue {
+                        entries,
+                        size,
+                        negative_count,
+                    }),
+                )
+            }
+        }
+    }
+}
+
+/// An owned copy of a [`PlutusData`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedData {
+    Constr { tag: u64, fields: Vec<OwnedData> },
+    Map(Vec<(OwnedData, OwnedData)>),
+    Integer(Integer),
+    ByteString(Vec<u8>),
+    List(Vec<OwnedData>),
+}
+
+impl From<&PlutusData<'_>> for OwnedData {
+    fn from(data: &PlutusData<'_>) -> Self {
+        match data {
+            PlutusData::Constr { tag, fields } => OwnedData::Constr {
+                tag: *tag,
+                fields: fields.iter().map(|d| OwnedData::from(*d)).collect(),
+            },
+            PlutusData::Map(items) => OwnedData::Map(
+                items
+                    .iter()
+                    .map(|(k, v)| (OwnedData::from(*k), OwnedData::from(*v)))
+                    .collect(),
+            ),
+            PlutusData::Integer(i) => OwnedData::Integer((*i).clone()),
+            PlutusData::ByteString(b) => OwnedData::ByteString(b.to_vec()),
+            PlutusData::List(items) => {
+                OwnedData::List(items.iter().map(|d| OwnedData::from(*d)).collect())
+            }
+        }
+    }
+}
+
+impl OwnedData {
+    pub fn to_data<'a>(&self, arena: &'a Arena) -> &'a PlutusData<'a> {
+        let many = |items: &[OwnedData]| {
+            let items: BumpVec<'_, _> = items
+                .iter()
+                .map(|d| d.to_data(arena))
+                .collect_in(arena.as_bump());
+
+            arena.alloc(items) as &'a [&'a PlutusData<'a>]
+        };
+
+        match self {
+            OwnedData::Constr { tag, fields } => PlutusData::constr(arena, *tag, many(fields)),
+            OwnedData::Map(items) => {
+                let items: BumpVec<'_, _> = items
+                    .iter()
+                    .map(|(k, v)| (k.to_data(arena), v.to_data(arena)))
+                    .collect_in(arena.as_bump());
+
+                PlutusData::map(arena, arena.alloc(items))
+            }
+            OwnedData::Integer(i) => PlutusData::integer(arena, arena.alloc_integer(i.clone())),
+            OwnedData::ByteString(b) => {
+                PlutusData::byte_string(arena, arena.alloc(b.clone()).as_slice())
+            }
+            OwnedData::List(items) => PlutusData::list(arena, many(items)),
+        }
+    }
+}
+
+/// An owned copy of a [`Type`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnedType {
+    Bool,
+    Integer,
+    String,
+    ByteString,
+    Unit,
+    List(Box<OwnedType>),
+    Array(Box<OwnedType>),
+    Pair(Box<OwnedType>, Box<OwnedType>),
+    Data,
+    Bls12_381G1Element,
+    Bls12_381G2Element,
+    Bls12_381MlResult,
+    Value,
+}
+
+impl From<&Type<'_>> for OwnedType {
+    fn from(typ: &Type<'_>) -> Self {
+        match typ {
+            Type::Bool => OwnedType::Bool,
+            Type::Integer => OwnedType::Integer,
+            Type::String => OwnedType::String,
+            Type::ByteString => OwnedType::ByteString,
+            Type::Unit => OwnedType::Unit,
+            Type::List(t) => OwnedType::List(Box::new(OwnedType::from(*t))),
+            Type::Array(t) => OwnedType::Array(Box::new(OwnedType::from(*t))),
+            Type::Pair(t1, t2) => OwnedType::Pair(
+                Box::new(OwnedType::from(*t1)),
+                Box::new(OwnedType::from(*t2)),
+            ),
+            Type::Data => OwnedType::Data,
+            Type::Bls12_381G1Element => OwnedType::Bls12_381G1Element,
+            Type::Bls12_381G2Element => OwnedType::Bls12_381G2Element,
+            Type::Bls12_381MlResult => OwnedType::Bls12_381MlResult,
+            Type::Value => OwnedType::Value,
+        }
+    }
+}
+
+impl OwnedType {
+    pub fn to_type<'a>(&self, arena: &'a Arena) -> &'a Type<'a> {
+        match self {
+            OwnedType::Bool => Type::bool(arena),
+            OwnedType::Integer => Type::integer(arena),
+            OwnedType::String => Type::string(arena),
+            OwnedType::ByteString => Type::byte_string(arena),
+            OwnedType::Unit => Type::unit(arena),
+            OwnedType::List(t) => Type::list(arena, t.to_type(arena)),
+            OwnedType::Array(t) => Type::array(arena, t.to_type(arena)),
+            OwnedType::Pair(t1, t2) => Type::pair(arena, t1.to_type(arena), t2.to_type(arena)),
+            OwnedType::Data => Type::data(arena),
+            OwnedType::Bls12_381G1Element => Type::g1(arena),
+            OwnedType::Bls12_381G2Element => Type::g2(arena),
+            OwnedType::Bls12_381MlResult => Type::ml_result(arena),
+            OwnedType::Value => Type::value(arena),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{arena::Arena, machine::PlutusVersion, syn::parse_program};
+
+    use super::{ErrorKind, EvalSnapshot, OwnedConstant, Payload};
+
+    fn assert_send_static<T: Send + 'static>(_: &T) {}
+
+    #[test]
+    fn snapshot_outlives_arena_reset() {
+        let mut arena = Arena::new();
+
+        let snapshot = {
+            let program = parse_program(
+                &arena,
+                "(program 1.1.0 [(lam y (lam x (case x (lam a y)))) (con integer 7)])",
+            )
+            .into_result()
+            .unwrap();
+
+            EvalSnapshot::from(program.eval_version(&arena, PlutusVersion::V3))
+        };
+
+        arena.reset();
+
+        assert_send_static(&snapshot);
+
+        let expected = parse_program(
+            &arena,
+            "(program 1.1.0 (lam x (case x (lam a (con integer 7)))))",
+        )
+        .into_result()
+        .unwrap();
+
+        let term = snapshot.term.as_ref().unwrap();
+
+        assert_eq!(term.to_term(&arena), expected.term);
+        assert_eq!(snapshot.info.consumed_budget.cpu, 64100);
+    }
+
+    #[test]
+    fn snapshot_error_payload() {
+        let arena = Arena::new();
+
+        let program = parse_program(
+            &arena,
+            "(program 1.1.0 [[(builtin divideInteger) (con integer 1)] (con integer 0)])",
+        )
+        .into_result()
+        .unwrap();
+
+        let snapshot = EvalSnapshot::from(program.eval_version(&arena, PlutusVersion::V3));
+
+        let error = snapshot.term.unwrap_err();
+
+        assert_eq!(error.kind, ErrorKind::Runtime);
+        assert_eq!(error.message, "Division by zero");
+        assert_eq!(
+            error.payload,
+            vec![
+                Payload::Constant(OwnedConstant::Integer(1.into())),
+                Payload::Constant(OwnedConstant::Integer(0.into())),
+            ]
+        );
+    }
+}
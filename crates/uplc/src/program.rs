@@ -1,12 +1,17 @@
 use crate::{
     arena::Arena,
-    binder::Eval,
+    binder::{Binder, Eval},
+    data::PlutusData,
+    flat::{self, FlatEncodeError},
+    hash::{self, ScriptHash},
     machine::{
         cost_model::builtin_costs::{
             builtin_costs_v1::BuiltinCostsV1, builtin_costs_v2::BuiltinCostsV2,
             builtin_costs_v3::BuiltinCostsV3, BuiltinCostModel,
         },
-        BuiltinSemantics, CostModel, EvalResult, ExBudget, Machine, PlutusVersion,
+        estimate, BudgetEstimate, BuiltinSemantics, CostModel, CostModelError, DifferentialReport,
+        ErrorSnapshot, EvalConfig, EvalResult, EvalSnapshot, ExBudget, Machine, PlutusVersion,
+        ScalingConfig, ScalingReport, ScalingSample, SizeRange,
     },
     term::Term,
 };
@@ -31,6 +36,19 @@ impl<'a, V> Program<'a, V> {
     }
 }
 
+impl<'a, V> Program<'a, V>
+where
+    V: Binder<'a>,
+{
+    /// The ledger hash of this program as a script of the given language.
+    pub fn script_hash(
+        &'a self,
+        plutus_version: PlutusVersion,
+    ) -> Result<ScriptHash, FlatEncodeError> {
+        flat::encode(self).map(|flat| hash::script_hash(plutus_version, &flat))
+    }
+}
+
 impl<'a, V> Program<'a, V>
 where
     V: Eval<'a>,
@@ -95,34 +113,180 @@ where
         EvalResult { term, info }
     }
 
+    /// Evaluate the program with the positional cost model parameters of
+    /// `plutus_version`, failing if they do not form a valid cost model.
     pub fn eval_with_params(
         &'a self,
         arena: &'a Arena,
         plutus_version: PlutusVersion,
         cost_model: &[i64],
         initial_budget: ExBudget,
-    ) -> EvalResult<'a, V> {
-        match plutus_version {
+    ) -> Result<EvalResult<'a, V>, CostModelError> {
+        let result = match plutus_version {
             PlutusVersion::V1 => self.evaluate(
                 arena,
-                CostModel::<BuiltinCostsV1>::initialize_cost_model(&plutus_version, cost_model),
+                CostModel::<BuiltinCostsV1>::initialize_cost_model(&plutus_version, cost_model)?,
                 plutus_version,
                 initial_budget,
             ),
             PlutusVersion::V2 => self.evaluate(
                 arena,
-                CostModel::<BuiltinCostsV2>::initialize_cost_model(&plutus_version, cost_model),
+                CostModel::<BuiltinCostsV2>::initialize_cost_model(&plutus_version, cost_model)?,
                 plutus_version,
                 initial_budget,
             ),
             PlutusVersion::V3 => self.evaluate(
                 arena,
-                CostModel::<BuiltinCostsV3>::initialize_cost_model(&plutus_version, cost_model),
+                CostModel::<BuiltinCostsV3>::initialize_cost_model(&plutus_version, cost_model)?,
                 plutus_version,
                 initial_budget,
             ),
+        };
+
+        Ok(result)
+    }
+
+    /// Evaluate the program once per configuration and report how every
+    /// configuration differs from the first one.
+    ///
+    /// Fails if the cost model of any configuration is invalid.
+    pub fn eval_differential(
+        &'a self,
+        arena: &'a Arena,
+        configs: &[EvalConfig<'_>],
+    ) -> Result<DifferentialReport, CostModelError> {
+        let snapshots = configs
+            .iter()
+            .map(|config| {
+                let result = match config.cost_model {
+                    Some(cost_model) => self.eval_with_params(
+                        arena,
+                        config.plutus_version,
+                        cost_model,
+                        config.initial_budget,
+                    )?,
+                    None => self.eval_version_budget(
+                        arena,
+                        config.plutus_version,
+                        config.initial_budget,
+                    ),
+                };
+
+                Ok(EvalSnapshot::from(result))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(DifferentialReport::new(snapshots))
+    }
+
+    /// Evaluate the program applied to the `input` of every size in the
+    /// config and fit how its budget grows with that size.
+    ///
+    /// Fails if the cost model of the config is invalid.
+    pub fn eval_scaling<F>(
+        &'a self,
+        arena: &'a Arena,
+        config: &ScalingConfig<'_>,
+        input: F,
+    ) -> Result<ScalingReport, CostModelError>
+    where
+        F: Fn(&'a Arena, usize) -> &'a PlutusData<'a>,
+    {
+        let samples = config
+            .sizes
+            .iter()
+            .map(|size| {
+                let program = self.apply(arena, Term::data(arena, input(arena, *size)));
+
+                let result = match config.cost_model {
+                    Some(cost_model) => program.eval_with_params(
+                        arena,
+                        config.plutus_version,
+                        cost_model,
+                        config.initial_budget,
+                    )?,
+                    None => program.eval_version_budget(
+                        arena,
+                        config.plutus_version,
+                        config.initial_budget,
+                    ),
+                };
+
+                Ok(ScalingSample {
+                    size: *size,
+                    consumed: result.info.consumed_budget,
+                    error: result
+                        .term
+                        .as_ref()
+                        .err()
+                        .map(|error| ErrorSnapshot::from(error).kind),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(ScalingReport::new(samples))
+    }
+
+    /// Bound the budget of applying the program to arguments of the given
+    /// sizes without running it.
+    pub fn estimate_budget(
+        &'a self,
+        plutus_version: PlutusVersion,
+        args: &[SizeRange],
+    ) -> BudgetEstimate {
+        match plutus_version {
+            PlutusVersion::V1 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV1>::default(),
+                args,
+            ),
+            PlutusVersion::V2 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV2>::default(),
+                args,
+            ),
+            PlutusVersion::V3 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV3>::default(),
+                args,
+            ),
         }
     }
+
+    /// Like [`Program::estimate_budget`] with the positional cost model
+    /// parameters of `plutus_version`.
+    pub fn estimate_budget_with_params(
+        &'a self,
+        plutus_version: PlutusVersion,
+        cost_model: &[i64],
+        args: &[SizeRange],
+    ) -> Result<BudgetEstimate, CostModelError> {
+        let estimate = match plutus_version {
+            PlutusVersion::V1 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV1>::initialize_cost_model(&plutus_version, cost_model)?,
+                args,
+            ),
+            PlutusVersion::V2 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV2>::initialize_cost_model(&plutus_version, cost_model)?,
+                args,
+            ),
+            PlutusVersion::V3 => estimate(
+                self.term,
+                self.version,
+                &CostModel::<BuiltinCostsV3>::initialize_cost_model(&plutus_version, cost_model)?,
+                args,
+            ),
+        };
+
+        Ok(estimate)
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -0,0 +1,324 @@
+//! Scripts as they are deployed: flat-encoded programs wrapped in CBOR.
+//!
+//! The ledger stores a script as a CBOR byte string around its flat
+//! encoding. Blueprints and cardano-cli envelopes often add a second byte
+//! string around that, so both forms are accepted on input; the output is
+//! always the ledger form.
+
+mod format;
+
+pub use format::{load, save, Encoding, LoadedScript, ScriptFormat};
+
+use minicbor::{data::Type, Decoder};
+
+use crate::{
+    arena::Arena,
+    binder::DeBruijn,
+    constant::Constant,
+    data::PlutusData,
+    flat::{self, FlatDecodeError, FlatEncodeError},
+    hash::{self, ScriptHash},
+    machine::PlutusVersion,
+    program::Program,
+    term::Term,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum ScriptError {
+    #[error("Malformed CBOR wrapping: {0}")]
+    Cbor(#[from] minicbor::decode::Error),
+    #[error("Trailing bytes after the CBOR wrapping at offset {0}")]
+    TrailingBytes(usize),
+    #[error("Malformed text envelope: {0}")]
+    Envelope(String),
+    #[error("Expected a {expected:?} script, the text envelope declares {declared:?}")]
+    VersionMismatch {
+        expected: PlutusVersion,
+        declared: PlutusVersion,
+    },
+    #[error("The plutus version is required for scripts outside a text envelope")]
+    MissingVersion,
+    #[error("Not a script in any known format")]
+    UnknownFormat,
+    #[error(transparent)]
+    Decode(#[from] FlatDecodeError),
+    #[error(transparent)]
+    Encode(#[from] FlatEncodeError),
+}
+
+/// A script after parameter application.
+#[derive(Debug)]
+pub struct AppliedScript<'a> {
+    pub program: &'a Program<'a, DeBruijn>,
+    /// The ledger form: a single CBOR byte string around the flat encoding.
+    pub cbor: Vec<u8>,
+    pub hash: ScriptHash,
+}
+
+/// The flat encoding inside a single- or double-CBOR-wrapped script.
+///
+/// A flat program starts with its major version, 1, so it can never be
+/// mistaken for the header of another byte string.
+pub fn unwrap_cbor(cbor: &[u8]) -> Result<&[u8], ScriptError> {
+    let mut bytes = unwrap_once(cbor)?;
+
+    if Decoder::new(bytes).datatype().ok() == Some(Type::Bytes) {
+        bytes = unwrap_once(bytes)?;
+    }
+
+    Ok(bytes)
+}
+
+fn unwrap_once(cbor: &[u8]) -> Result<&[u8], ScriptError> {
+    let mut d = Decoder::new(cbor);
+
+    let bytes = d.bytes()?;
+
+    if d.position() != cbor.len() {
+        return Err(ScriptError::TrailingBytes(d.position()));
+    }
+
+    Ok(bytes)
+}
+
+/// Decode a CBOR-wrapped script with the rules of the given language and
+/// protocol version.
+pub fn decode<'a>(
+    arena: &'a Arena,
+    cbor: &[u8],
+    plutus_version: PlutusVersion,
+    protocol_version: u32,
+) -> Result<&'a Program<'a, DeBruijn>, ScriptError> {
+    let flat = unwrap_cbor(cbor)?;
+
+    Ok(flat::decode_strict(
+        arena,
+        flat,
+        plutus_version,
+        protocol_version,
+    )?)
+}
+
+/// Apply `params` in order to a CBOR-wrapped script and re-encode it.
+pub fn apply_params<'a>(
+    arena: &'a Arena,
+    cbor: &[u8],
+    plutus_version: PlutusVersion,
+    protocol_version: u32,
+    params: &[&'a PlutusData<'a>],
+) -> Result<AppliedScript<'a>, ScriptError> {
+    let program = decode(arena, cbor, plutus_version, protocol_version)?;
+
+    let program = params.iter().fold(program, |program, param| {
+        program.apply(arena, Term::data(arena, param))
+    });
+
+    let flat = flat::encode(program)?;
+
+    Ok(AppliedScript {
+        program,
+        cbor: hash::wrap_cbor(&flat),
+        hash: hash::script_hash(plutus_version, &flat),
+    })
+}
+
+/// A program split into its base and the constants applied to it.
+#[derive(Debug)]
+pub struct Instantiation<'a, V> {
+    pub base: &'a Program<'a, V>,
+    /// In application order, the first one being the innermost.
+    pub params: Vec<&'a Constant<'a>>,
+}
+
+impl<'a, V> Instantiation<'a, V> {
+    /// The parameters when they are all `Data`, as with scripts
+    /// instantiated by [`apply_params`].
+    pub fn data_params(&self) -> Option<Vec<&'a PlutusData<'a>>> {
+        self.params
+            .iter()
+            .map(|param| match param {
+                Constant::Data(data) => Some(*data),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Peel the outer chain of applications of constants off a program, the
+/// inverse of [`apply_params`].
+///
+/// Nothing tells a parameter from a constant argument the compiler left in
+/// place, so every such application is taken as one: compare the base with
+/// a known blueprint to be sure.
+pub fn extract_params<'a, V>(
+    arena: &'a Arena,
+    program: &'a Program<'a, V>,
+) -> Instantiation<'a, V> {
+    let mut term = program.term;
+
+    let mut params = Vec::new();
+
+    while let Term::Apply { function, argument } = term {
+        let Term::Constant(param) = argument else {
+            break;
+        };
+
+        params.push(*param);
+
+        term = function;
+    }
+
+    params.reverse();
+
+    Instantiation {
+        base: Program::new(arena, program.version, term),
+        params,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena, binder::DeBruijn, data::PlutusData, flat, hash, machine::PlutusVersion,
+        syn::parse_program, term::Term,
+    };
+
+    use super::{apply_params, extract_params, unwrap_cbor, ScriptError};
+
+    /// Succeeds when its parameter is 42, whatever the context.
+    const PARAMETERISED: &str = r#"(program 1.1.0
+        (lam param (lam ctx
+          (force
+            [(force (builtin ifThenElse))
+              [(builtin equalsData) param (con data (I 42))]
+              (delay (con unit ()))
+              (delay (error))]))))"#;
+
+    fn compiled(arena: &Arena) -> Vec<u8> {
+        let program = parse_program(arena, PARAMETERISED).into_result().unwrap();
+
+        hash::wrap_cbor(&flat::encode(program).unwrap())
+    }
+
+    #[test]
+    fn unwraps_single_and_double_wrapping() {
+        let flat = [0x01, 0x00, 0x00, 0x48, 0x81];
+
+        let single = hash::wrap_cbor(&flat);
+        let double = hash::wrap_cbor(&single);
+
+        assert_eq!(unwrap_cbor(&single).unwrap(), flat);
+        assert_eq!(unwrap_cbor(&double).unwrap(), flat);
+
+        assert!(matches!(
+            unwrap_cbor(&[single.clone(), vec![0x00]].concat()),
+            Err(ScriptError::TrailingBytes(6))
+        ));
+        assert!(matches!(unwrap_cbor(&flat), Err(ScriptError::Cbor(_))));
+    }
+
+    #[test]
+    fn applies_params_and_rehashes() {
+        let arena = Arena::new();
+
+        let cbor = compiled(&arena);
+
+        let param = PlutusData::integer_from(&arena, 42);
+
+        let single = apply_params(&arena, &cbor, PlutusVersion::V3, 10, &[param]).unwrap();
+        let double = apply_params(
+            &arena,
+            &hash::wrap_cbor(&cbor),
+            PlutusVersion::V3,
+            10,
+            &[param],
+        )
+        .unwrap();
+
+        assert_eq!(single.cbor, double.cbor);
+        assert_eq!(
+            single.hash,
+            hash::script_hash_cbor(PlutusVersion::V3, &single.cbor)
+        );
+        assert_ne!(
+            single.hash,
+            hash::script_hash_cbor(PlutusVersion::V3, &cbor)
+        );
+
+        let program = flat::decode_strict::<DeBruijn>(
+            &arena,
+            unwrap_cbor(&single.cbor).unwrap(),
+            PlutusVersion::V3,
+            10,
+        )
+        .unwrap();
+
+        let result = program
+            .apply(
+                &arena,
+                Term::data(&arena, PlutusData::constr(&arena, 0, &[])),
+            )
+            .eval_version(&arena, PlutusVersion::V3);
+
+        assert!(result.term.is_ok());
+    }
+
+    #[test]
+    fn reports_malformed_scripts() {
+        let arena = Arena::new();
+
+        let param = PlutusData::integer_from(&arena, 42);
+
+        assert!(matches!(
+            apply_params(
+                &arena,
+                &[0x43, 0x01, 0x00, 0x00],
+                PlutusVersion::V3,
+                10,
+                &[param]
+            ),
+            Err(ScriptError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn extracts_applied_params() {
+        let arena = Arena::new();
+
+        let cbor = compiled(&arena);
+
+        let params = [
+            PlutusData::integer_from(&arena, 42),
+            PlutusData::byte_string(&arena, &[0xca, 0xfe]),
+        ];
+
+        let applied = apply_params(&arena, &cbor, PlutusVersion::V3, 10, &params).unwrap();
+
+        let program = super::decode(&arena, &applied.cbor, PlutusVersion::V3, 10).unwrap();
+
+        let instantiation = extract_params(&arena, program);
+
+        assert_eq!(instantiation.data_params().unwrap(), params);
+        assert_eq!(
+            flat::encode(instantiation.base).unwrap(),
+            unwrap_cbor(&cbor).unwrap()
+        );
+
+        // Only the outer chain counts, and it stops at the first argument
+        // that is not a constant.
+        let program = parse_program(
+            &arena,
+            "(program 1.1.0 [[(lam a (lam b a)) (lam x x)] (con integer 1)])",
+        )
+        .into_result()
+        .unwrap();
+
+        let instantiation = extract_params(&arena, program);
+
+        assert_eq!(instantiation.params.len(), 1);
+        assert_eq!(instantiation.data_params(), None);
+        assert!(matches!(instantiation.base.term, Term::Apply { .. }));
+    }
+}
@@ -0,0 +1,312 @@
+//! The files scripts come in: cardano-cli text envelopes, raw flat, and
+//! single- or double-CBOR-wrapped bytes, in binary, hex or base64.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use crate::{
+    arena::Arena,
+    binder::{Binder, DeBruijn},
+    flat, hash,
+    machine::PlutusVersion,
+    program::Program,
+};
+
+use super::{unwrap_once, ScriptError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptFormat {
+    Flat,
+    /// The ledger form.
+    Cbor,
+    /// The ledger form in another byte string, as in blueprints of some
+    /// compilers and in text envelopes.
+    DoubleCbor,
+    /// A cardano-cli `.plutus` file.
+    TextEnvelope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Binary,
+    Hex,
+    Base64,
+}
+
+#[derive(Debug)]
+pub struct LoadedScript<'a> {
+    pub program: &'a Program<'a, DeBruijn>,
+    pub plutus_version: PlutusVersion,
+    pub format: ScriptFormat,
+    pub encoding: Encoding,
+}
+
+/// Detect the format of `contents` and decode the script in it.
+///
+/// Text envelopes declare their language: `plutus_version`, when given,
+/// must agree with it. Other formats need it given.
+pub fn load<'a>(
+    arena: &'a Arena,
+    contents: &[u8],
+    plutus_version: Option<PlutusVersion>,
+    protocol_version: u32,
+) -> Result<LoadedScript<'a>, ScriptError> {
+    let (format, encoding, flat, plutus_version) = match detect(contents)? {
+        Detected::Envelope { declared, cbor } => {
+            if let Some(expected) = plutus_version.filter(|expected| *expected != declared) {
+                return Err(ScriptError::VersionMismatch { expected, declared });
+            }
+
+            (
+                ScriptFormat::TextEnvelope,
+                Encoding::Hex,
+                super::unwrap_cbor(&cbor)?.to_vec(),
+                declared,
+            )
+        }
+        Detected::Bytes {
+            format,
+            encoding,
+            flat,
+        } => (
+            format,
+            encoding,
+            flat,
+            plutus_version.ok_or(ScriptError::MissingVersion)?,
+        ),
+    };
+
+    let program = flat::decode_strict(arena, &flat, plutus_version, protocol_version)?;
+
+    Ok(LoadedScript {
+        program,
+        plutus_version,
+        format,
+        encoding,
+    })
+}
+
+/// Encode a program in the given format. Text envelopes are JSON whatever
+/// the encoding.
+pub fn save<'a, V>(
+    program: &'a Program<'a, V>,
+    plutus_version: PlutusVersion,
+    format: ScriptFormat,
+    encoding: Encoding,
+) -> Result<Vec<u8>, ScriptError>
+where
+    V: Binder<'a>,
+{
+    let flat = flat::encode(program)?;
+
+    let bytes = match format {
+        ScriptFormat::Flat => flat,
+        ScriptFormat::Cbor => hash::wrap_cbor(&flat),
+        ScriptFormat::DoubleCbor => hash::wrap_cbor(&hash::wrap_cbor(&flat)),
+        ScriptFormat::TextEnvelope => {
+            let envelope = json!({
+                "type": envelope_type(plutus_version),
+                "description": "",
+                "cborHex": hex::encode(hash::wrap_cbor(&hash::wrap_cbor(&flat))),
+            });
+
+            return Ok(serde_json::to_vec_pretty(&envelope).expect("a JSON value serialises"));
+        }
+    };
+
+    Ok(match encoding {
+        Encoding::Binary => bytes,
+        Encoding::Hex => hex::encode(bytes).into_bytes(),
+        Encoding::Base64 => STANDARD.encode(bytes).into_bytes(),
+    })
+}
+
+enum Detected {
+    Envelope {
+        declared: PlutusVersion,
+        cbor: Vec<u8>,
+    },
+    Bytes {
+        format: ScriptFormat,
+        encoding: Encoding,
+        flat: Vec<u8>,
+    },
+}
+
+fn detect(contents: &[u8]) -> Result<Detected, ScriptError> {
+    // Binary comes first: text never starts like flat or parses as a
+    // single CBOR byte string.
+    if let Some((format, flat)) = classify(contents) {
+        return Ok(Detected::Bytes {
+            format,
+            encoding: Encoding::Binary,
+            flat: flat.to_vec(),
+        });
+    }
+
+    let text = std::str::from_utf8(contents)
+        .map_err(|_| ScriptError::UnknownFormat)?
+        .trim();
+
+    if text.starts_with('{') {
+        return envelope(text);
+    }
+
+    let decoded = [
+        (Encoding::Hex, hex::decode(text).ok()),
+        (Encoding::Base64, STANDARD.decode(text).ok()),
+    ];
+
+    decoded
+        .into_iter()
+        .find_map(|(encoding, bytes)| {
+            let (format, flat) = classify(bytes.as_deref()?)?;
+
+            Some(Detected::Bytes {
+                format,
+                encoding,
+                flat: flat.to_vec(),
+            })
+        })
+        .ok_or(ScriptError::UnknownFormat)
+}
+
+/// A flat program starts with its major version, 1.
+fn classify(bytes: &[u8]) -> Option<(ScriptFormat, &[u8])> {
+    if bytes.first() == Some(&1) {
+        return Some((ScriptFormat::Flat, bytes));
+    }
+
+    let inner = unwrap_once(bytes).ok()?;
+
+    if inner.first() == Some(&1) {
+        return Some((ScriptFormat::Cbor, inner));
+    }
+
+    let flat = unwrap_once(inner).ok()?;
+
+    (flat.first() == Some(&1)).then_some((ScriptFormat::DoubleCbor, flat))
+}
+
+fn envelope(text: &str) -> Result<Detected, ScriptError> {
+    let malformed = |reason: &str| ScriptError::Envelope(reason.to_string());
+
+    let json: Value = serde_json::from_str(text).map_err(|error| malformed(&error.to_string()))?;
+
+    let declared = match json.get("type").and_then(Value::as_str) {
+        Some("PlutusScriptV1") => PlutusVersion::V1,
+        Some("PlutusScriptV2") => PlutusVersion::V2,
+        Some("PlutusScriptV3") => PlutusVersion::V3,
+        Some(other) => return Err(malformed(&format!("unknown type {other}"))),
+        None => return Err(malformed("missing type")),
+    };
+
+    let cbor = json
+        .get("cborHex")
+        .and_then(Value::as_str)
+        .and_then(|cbor| hex::decode(cbor).ok())
+        .ok_or_else(|| malformed("missing or malformed cborHex"))?;
+
+    Ok(Detected::Envelope { declared, cbor })
+}
+
+fn envelope_type(plutus_version: PlutusVersion) -> &'static str {
+    match plutus_version {
+        PlutusVersion::V1 => "PlutusScriptV1",
+        PlutusVersion::V2 => "PlutusScriptV2",
+        PlutusVersion::V3 => "PlutusScriptV3",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena, flat, machine::PlutusVersion, script::ScriptError, syn::parse_program,
+    };
+
+    use super::{load, save, Encoding, ScriptFormat};
+
+    /// A V2 script that always succeeds, in a text envelope.
+    const ENVELOPE: &str = r#"{
+        "type": "PlutusScriptV2",
+        "description": "",
+        "cborHex": "52510100003222253330044a229309b2b2b9a1"
+    }"#;
+
+    #[test]
+    fn loads_text_envelopes() {
+        let arena = Arena::new();
+
+        let loaded = load(&arena, ENVELOPE.as_bytes(), None, 10).unwrap();
+
+        assert_eq!(loaded.plutus_version, PlutusVersion::V2);
+        assert_eq!(loaded.format, ScriptFormat::TextEnvelope);
+        assert_eq!(
+            hex::encode(flat::encode(loaded.program).unwrap()),
+            "0100003222253330044a229309b2b2b9a1"
+        );
+
+        assert!(load(&arena, ENVELOPE.as_bytes(), Some(PlutusVersion::V2), 10).is_ok());
+        assert!(matches!(
+            load(&arena, ENVELOPE.as_bytes(), Some(PlutusVersion::V3), 10),
+            Err(ScriptError::VersionMismatch {
+                expected: PlutusVersion::V3,
+                declared: PlutusVersion::V2
+            })
+        ));
+        assert!(matches!(
+            load(&arena, ENVELOPE.replace("V2", "V4").as_bytes(), None, 10),
+            Err(ScriptError::Envelope(_))
+        ));
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        let arena = Arena::new();
+
+        let program = parse_program(&arena, "(program 1.1.0 (lam ctx (con unit ())))")
+            .into_result()
+            .unwrap();
+
+        let flat = flat::encode(program).unwrap();
+
+        for format in [
+            ScriptFormat::Flat,
+            ScriptFormat::Cbor,
+            ScriptFormat::DoubleCbor,
+        ] {
+            for encoding in [Encoding::Binary, Encoding::Hex, Encoding::Base64] {
+                let saved = save(program, PlutusVersion::V3, format, encoding).unwrap();
+
+                let loaded = load(&arena, &saved, Some(PlutusVersion::V3), 10).unwrap();
+
+                assert_eq!((loaded.format, loaded.encoding), (format, encoding));
+                assert_eq!(flat::encode(loaded.program).unwrap(), flat);
+            }
+        }
+
+        let saved = save(
+            program,
+            PlutusVersion::V3,
+            ScriptFormat::TextEnvelope,
+            Encoding::Hex,
+        )
+        .unwrap();
+
+        let loaded = load(&arena, &saved, None, 10).unwrap();
+
+        assert_eq!(loaded.plutus_version, PlutusVersion::V3);
+        assert_eq!(flat::encode(loaded.program).unwrap(), flat);
+
+        assert!(matches!(
+            load(&arena, &flat, None, 10),
+            Err(ScriptError::MissingVersion)
+        ));
+        assert!(matches!(
+            load(&arena, b"not a script", Some(PlutusVersion::V3), 10),
+            Err(ScriptError::UnknownFormat)
+        ));
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{arena::Arena, constant::Integer, data::PlutusData, machine::OwnedData};
+
+/// Conversion between a ledger type and the [`PlutusData`] scripts see it as.
+///
+/// Decoding is strict: anything `from_data` accepts encodes back to the
+/// exact same data.
+pub trait IsData: Sized {
+    fn to_data(&self) -> OwnedData;
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError>;
+
+    fn to_plutus_data<'a>(&self, arena: &'a Arena) -> &'a PlutusData<'a> {
+        self.to_data().to_data(arena)
+    }
+
+    fn from_plutus_data(data: &PlutusData<'_>) -> Result<Self, ContextError> {
+        Self::from_data(&OwnedData::from(data))
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ContextError {
+    #[error("Expected {expected}{}", at(path))]
+    Expected {
+        expected: &'static str,
+        path: String,
+    },
+    #[error("Unknown {typ} constructor {tag}{}", at(path))]
+    UnknownConstructor {
+        typ: &'static str,
+        tag: u64,
+        path: String,
+    },
+    #[error("Missing field '{field}' of {typ}{}", at(path))]
+    MissingField {
+        typ: &'static str,
+        field: &'static str,
+        path: String,
+    },
+    #[error("Unexpected field {index} of {typ}{}", at(path))]
+    ExtraField {
+        typ: &'static str,
+        index: usize,
+        path: String,
+    },
+}
+
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+impl ContextError {
+    /// Where in the data the error is, from the root of what was decoded.
+    pub fn path(&self) -> &str {
+        match self {
+            ContextError::Expected { path, .. }
+            | ContextError::UnknownConstructor { path, .. }
+            | ContextError::MissingField { path, .. }
+            | ContextError::ExtraField { path, .. } => path,
+        }
+    }
+
+    fn expected(expected: &'static str) -> Self {
+        ContextError::Expected {
+            expected,
+            path: String::new(),
+        }
+    }
+
+    /// Nest the error under `segment`, a field name or a `[index]`.
+    fn at(mut self, segment: &str) -> Self {
+        let path = match &mut self {
+            ContextError::Expected { path, .. }
+            | ContextError::UnknownConstructor { path, .. }
+            | ContextError::MissingField { path, .. }
+            | ContextError::ExtraField { path, .. } => path,
+        };
+
+        *path = match path.as_str() {
+            "" => segment.to_string(),
+            rest if rest.starts_with('[') => format!("{segment}{rest}"),
+            rest => format!("{segment}.{rest}"),
+        };
+
+        self
+    }
+}
+
+/// A map as Plutus sees it, an association list that keeps the order and
+/// duplicates of its entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssocMap<K, V>(pub Vec<(K, V)>);
+
+impl<K, V> Default for AssocMap<K, V> {
+    fn default() -> Self {
+        AssocMap(Vec::new())
+    }
+}
+
+impl<K: PartialEq, V> AssocMap<K, V> {
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+}
+
+pub(crate) fn constr(tag: u64, fields: Vec<OwnedData>) -> OwnedData {
+    OwnedData::Constr { tag, fields }
+}
+
+/// The fields of a constructor, decoded one after the other.
+pub(crate) struct Fields<'d> {
+    typ: &'static str,
+    tag: u64,
+    fields: &'d [OwnedData],
+    next: usize,
+}
+
+impl<'d> Fields<'d> {
+    /// The tag and fields of a constructor of `typ`.
+    pub(crate) fn of(data: &'d OwnedData, typ: &'static str) -> Result<(u64, Self), ContextError> {
+        match data {
+            OwnedData::Constr { tag, fields } => Ok((
+                *tag,
+                Fields {
+                    typ,
+                    tag: *tag,
+                    fields,
+                    next: 0,
+                },
+            )),
+            _ => Err(ContextError::expected("a constructor")),
+        }
+    }
+
+    /// The fields of `typ`, which has a single constructor.
+    pub(crate) fn record(data: &'d OwnedData, typ: &'static str) -> Result<Self, ContextError> {
+        match Fields::of(data, typ)? {
+            (0, fields) => Ok(fields),
+            (_, fields) => fields.unknown(),
+        }
+    }
+
+    pub(crate) fn field<T: IsData>(&mut self, name: &'static str) -> Result<T, ContextError> {
+        let Some(data) = self.fields.get(self.next) else {
+            return Err(ContextError::MissingField {
+                typ: self.typ,
+                field: name,
+                path: String::new(),
+            });
+        };
+
+        self.next += 1;
+
+        T::from_data(data).map_err(|error| error.at(name))
+    }
+
+    /// Finish decoding `value`, there must be no fields left.
+    pub(crate) fn end<T>(self, value: T) -> Result<T, ContextError> {
+        if self.next < self.fields.len() {
+            return Err(ContextError::ExtraField {
+                typ: self.typ,
+                index: self.next,
+                path: String::new(),
+            });
+        }
+
+        Ok(value)
+    }
+
+    /// The constructor is none of those of the type.
+    pub(crate) fn unknown<T>(self) -> Result<T, ContextError> {
+        Err(ContextError::UnknownConstructor {
+            typ: self.typ,
+            tag: self.tag,
+            path: String::new(),
+        })
+    }
+}
+
+impl IsData for OwnedData {
+    fn to_data(&self) -> OwnedData {
+        self.clone()
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        Ok(data.clone())
+    }
+}
+
+impl IsData for Integer {
+    fn to_data(&self) -> OwnedData {
+        OwnedData::Integer(self.clone())
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        match data {
+            OwnedData::Integer(i) => Ok(i.clone()),
+            _ => Err(ContextError::expected("an integer")),
+        }
+    }
+}
+
+impl IsData for Vec<u8> {
+    fn to_data(&self) -> OwnedData {
+        OwnedData::ByteString(self.clone())
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        match data {
+            OwnedData::ByteString(bytes) => Ok(bytes.clone()),
+            _ => Err(ContextError::expected("a byte string")),
+        }
+    }
+}
+
+impl<T: IsData> IsData for Vec<T> {
+    fn to_data(&self) -> OwnedData {
+        OwnedData::List(self.iter().map(IsData::to_data).collect())
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        match data {
+            OwnedData::List(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| T::from_data(item).map_err(|e| e.at(&format!("[{index}]"))))
+                .collect(),
+            _ => Err(ContextError::expected("a list")),
+        }
+    }
+}
+
+impl<K: IsData, V: IsData> IsData for AssocMap<K, V> {
+    fn to_data(&self) -> OwnedData {
+        OwnedData::Map(
+            self.0
+                .iter()
+                .map(|(key, value)| (key.to_data(), value.to_data()))
+                .collect(),
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        match data {
+            OwnedData::Map(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(index, (key, value))| {
+                    let key = K::from_data(key).map_err(|e| e.at(&format!("[{index}].key")))?;
+                    let value =
+                        V::from_data(value).map_err(|e| e.at(&format!("[{index}].value")))?;
+
+                    Ok((key, value))
+                })
+                .collect::<Result<_, _>>()
+                .map(AssocMap),
+            _ => Err(ContextError::expected("a map")),
+        }
+    }
+}
+
+/// `Maybe`, `Just` is constructor 0 and `Nothing` constructor 1.
+impl<T: IsData> IsData for Option<T> {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            Some(value) => constr(0, vec![value.to_data()]),
+            None => constr(1, vec![]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "Maybe")?;
+
+        match tag {
+            0 => {
+                let value = fields.field("just")?;
+
+                fields.end(Some(value))
+            }
+            1 => fields.end(None),
+            _ => fields.unknown(),
+        }
+    }
+}
+
+impl IsData for bool {
+    fn to_data(&self) -> OwnedData {
+        constr(*self as u64, vec![])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, fields) = Fields::of(data, "Bool")?;
+
+        match tag {
+            0 => fields.end(false),
+            1 => fields.end(true),
+            _ => fields.unknown(),
+        }
+    }
+}
+
+/// Tuples are the single constructor of a pair.
+impl<A: IsData, B: IsData> IsData for (A, B) {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.0.to_data(), self.1.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "Tuple")?;
+
+        let pair = (fields.field("0")?, fields.field("1")?);
+
+        fields.end(pair)
+    }
+}
@@ -0,0 +1,9 @@
+//! Typed script contexts, the last argument the ledger applies a validator
+//! to, for every Plutus version.
+
+mod is_data;
+pub mod v1;
+pub mod v2;
+pub mod v3;
+
+pub use is_data::{AssocMap, ContextError, IsData};
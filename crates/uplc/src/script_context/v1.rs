@@ -0,0 +1,594 @@
+//! The script context of Plutus V1, as in `PlutusLedgerApi.V1`. Later
+//! versions reuse most of these types.
+
+use crate::{constant::Integer, machine::OwnedData};
+
+use super::is_data::{constr, AssocMap, ContextError, Fields, IsData};
+
+pub type PubKeyHash = Vec<u8>;
+pub type ScriptHash = Vec<u8>;
+pub type DatumHash = Vec<u8>;
+pub type CurrencySymbol = Vec<u8>;
+pub type TokenName = Vec<u8>;
+pub type Datum = OwnedData;
+pub type Redeemer = OwnedData;
+
+/// A transaction id, wrapped in a constructor up to Plutus V2.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxId(pub Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxOutRef {
+    pub id: TxId,
+    pub index: Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credential {
+    PubKey(PubKeyHash),
+    Script(ScriptHash),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StakingCredential {
+    Hash(Credential),
+    Pointer {
+        slot: Integer,
+        transaction: Integer,
+        certificate: Integer,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Address {
+    pub credential: Credential,
+    pub staking_credential: Option<StakingCredential>,
+}
+
+/// Quantities by currency symbol and token name. Ada has the empty symbol
+/// and name.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Value(pub AssocMap<CurrencySymbol, AssocMap<TokenName, Integer>>);
+
+impl Value {
+    pub fn lovelace(amount: impl Into<Integer>) -> Self {
+        Value(AssocMap(vec![(
+            Vec::new(),
+            AssocMap(vec![(Vec::new(), amount.into())]),
+        )]))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Extended {
+    NegInf,
+    Finite(Integer),
+    PosInf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalBound {
+    pub bound: Extended,
+    pub inclusive: bool,
+}
+
+/// A range of POSIX times in milliseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub from: IntervalBound,
+    pub to: IntervalBound,
+}
+
+impl Interval {
+    pub fn always() -> Self {
+        Interval {
+            from: IntervalBound {
+                bound: Extended::NegInf,
+                inclusive: true,
+            },
+            to: IntervalBound {
+                bound: Extended::PosInf,
+                inclusive: true,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxOut {
+    pub address: Address,
+    pub value: Value,
+    pub datum_hash: Option<DatumHash>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInInfo {
+    pub out_ref: TxOutRef,
+    pub resolved: TxOut,
+}
+
+/// A certificate, up to Plutus V2.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DCert {
+    DelegRegKey(StakingCredential),
+    DelegDeRegKey(StakingCredential),
+    DelegDelegate(StakingCredential, PubKeyHash),
+    PoolRegister { pool: PubKeyHash, vrf: PubKeyHash },
+    PoolRetire { pool: PubKeyHash, epoch: Integer },
+    Genesis,
+    Mir,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptPurpose {
+    Minting(CurrencySymbol),
+    Spending(TxOutRef),
+    Rewarding(StakingCredential),
+    Certifying(DCert),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInfo {
+    pub inputs: Vec<TxInInfo>,
+    pub outputs: Vec<TxOut>,
+    pub fee: Value,
+    pub mint: Value,
+    pub certificates: Vec<DCert>,
+    pub withdrawals: Vec<(StakingCredential, Integer)>,
+    pub valid_range: Interval,
+    pub signatories: Vec<PubKeyHash>,
+    pub data: Vec<(DatumHash, Datum)>,
+    pub id: TxId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptContext {
+    pub tx_info: TxInfo,
+    pub purpose: ScriptPurpose,
+}
+
+impl IsData for TxId {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.0.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxId")?;
+
+        let id = TxId(fields.field("id")?);
+
+        fields.end(id)
+    }
+}
+
+impl IsData for TxOutRef {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.id.to_data(), self.index.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxOutRef")?;
+
+        let out_ref = TxOutRef {
+            id: fields.field("id")?,
+            index: fields.field("index")?,
+        };
+
+        fields.end(out_ref)
+    }
+}
+
+impl IsData for Credential {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            Credential::PubKey(hash) => constr(0, vec![hash.to_data()]),
+            Credential::Script(hash) => constr(1, vec![hash.to_data()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "Credential")?;
+
+        let credential = match tag {
+            0 => Credential::PubKey(fields.field("hash")?),
+            1 => Credential::Script(fields.field("hash")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(credential)
+    }
+}
+
+impl IsData for StakingCredential {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            StakingCredential::Hash(credential) => constr(0, vec![credential.to_data()]),
+            StakingCredential::Pointer {
+                slot,
+                transaction,
+                certificate,
+            } => constr(
+                1,
+                vec![slot.to_data(), transaction.to_data(), certificate.to_data()],
+            ),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "StakingCredential")?;
+
+        let credential = match tag {
+            0 => StakingCredential::Hash(fields.field("credential")?),
+            1 => StakingCredential::Pointer {
+                slot: fields.field("slot")?,
+                transaction: fields.field("transaction")?,
+                certificate: fields.field("certificate")?,
+            },
+            _ => return fields.unknown(),
+        };
+
+        fields.end(credential)
+    }
+}
+
+impl IsData for Address {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![self.credential.to_data(), self.staking_credential.to_data()],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "Address")?;
+
+        let address = Address {
+            credential: fields.field("credential")?,
+            staking_credential: fields.field("staking_credential")?,
+        };
+
+        fields.end(address)
+    }
+}
+
+impl IsData for Value {
+    fn to_data(&self) -> OwnedData {
+        self.0.to_data()
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        AssocMap::from_data(data).map(Value)
+    }
+}
+
+impl IsData for Extended {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            Extended::NegInf => constr(0, vec![]),
+            Extended::Finite(time) => constr(1, vec![time.to_data()]),
+            Extended::PosInf => constr(2, vec![]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "Extended")?;
+
+        let extended = match tag {
+            0 => Extended::NegInf,
+            1 => Extended::Finite(fields.field("time")?),
+            2 => Extended::PosInf,
+            _ => return fields.unknown(),
+        };
+
+        fields.end(extended)
+    }
+}
+
+impl IsData for IntervalBound {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.bound.to_data(), self.inclusive.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "IntervalBound")?;
+
+        let bound = IntervalBound {
+            bound: fields.field("bound")?,
+            inclusive: fields.field("inclusive")?,
+        };
+
+        fields.end(bound)
+    }
+}
+
+impl IsData for Interval {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.from.to_data(), self.to.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "Interval")?;
+
+        let interval = Interval {
+            from: fields.field("from")?,
+            to: fields.field("to")?,
+        };
+
+        fields.end(interval)
+    }
+}
+
+impl IsData for TxOut {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.address.to_data(),
+                self.value.to_data(),
+                self.datum_hash.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxOut")?;
+
+        let output = TxOut {
+            address: fields.field("address")?,
+            value: fields.field("value")?,
+            datum_hash: fields.field("datum_hash")?,
+        };
+
+        fields.end(output)
+    }
+}
+
+impl IsData for TxInInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.out_ref.to_data(), self.resolved.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInInfo")?;
+
+        let input = TxInInfo {
+            out_ref: fields.field("out_ref")?,
+            resolved: fields.field("resolved")?,
+        };
+
+        fields.end(input)
+    }
+}
+
+impl IsData for DCert {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            DCert::DelegRegKey(credential) => constr(0, vec![credential.to_data()]),
+            DCert::DelegDeRegKey(credential) => constr(1, vec![credential.to_data()]),
+            DCert::DelegDelegate(credential, pool) => {
+                constr(2, vec![credential.to_data(), pool.to_data()])
+            }
+            DCert::PoolRegister { pool, vrf } => constr(3, vec![pool.to_data(), vrf.to_data()]),
+            DCert::PoolRetire { pool, epoch } => constr(4, vec![pool.to_data(), epoch.to_data()]),
+            DCert::Genesis => constr(5, vec![]),
+            DCert::Mir => constr(6, vec![]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "DCert")?;
+
+        let certificate = match tag {
+            0 => DCert::DelegRegKey(fields.field("credential")?),
+            1 => DCert::DelegDeRegKey(fields.field("credential")?),
+            2 => DCert::DelegDelegate(fields.field("credential")?, fields.field("pool")?),
+            3 => DCert::PoolRegister {
+                pool: fields.field("pool")?,
+                vrf: fields.field("vrf")?,
+            },
+            4 => DCert::PoolRetire {
+                pool: fields.field("pool")?,
+                epoch: fields.field("epoch")?,
+            },
+            5 => DCert::Genesis,
+            6 => DCert::Mir,
+            _ => return fields.unknown(),
+        };
+
+        fields.end(certificate)
+    }
+}
+
+impl IsData for ScriptPurpose {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            ScriptPurpose::Minting(symbol) => constr(0, vec![symbol.to_data()]),
+            ScriptPurpose::Spending(out_ref) => constr(1, vec![out_ref.to_data()]),
+            ScriptPurpose::Rewarding(credential) => constr(2, vec![credential.to_data()]),
+            ScriptPurpose::Certifying(certificate) => constr(3, vec![certificate.to_data()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "ScriptPurpose")?;
+
+        let purpose = match tag {
+            0 => ScriptPurpose::Minting(fields.field("currency_symbol")?),
+            1 => ScriptPurpose::Spending(fields.field("out_ref")?),
+            2 => ScriptPurpose::Rewarding(fields.field("credential")?),
+            3 => ScriptPurpose::Certifying(fields.field("certificate")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(purpose)
+    }
+}
+
+impl IsData for TxInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.inputs.to_data(),
+                self.outputs.to_data(),
+                self.fee.to_data(),
+                self.mint.to_data(),
+                self.certificates.to_data(),
+                self.withdrawals.to_data(),
+                self.valid_range.to_data(),
+                self.signatories.to_data(),
+                self.data.to_data(),
+                self.id.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInfo")?;
+
+        let tx_info = TxInfo {
+            inputs: fields.field("inputs")?,
+            outputs: fields.field("outputs")?,
+            fee: fields.field("fee")?,
+            mint: fields.field("mint")?,
+            certificates: fields.field("certificates")?,
+            withdrawals: fields.field("withdrawals")?,
+            valid_range: fields.field("valid_range")?,
+            signatories: fields.field("signatories")?,
+            data: fields.field("data")?,
+            id: fields.field("id")?,
+        };
+
+        fields.end(tx_info)
+    }
+}
+
+impl IsData for ScriptContext {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.tx_info.to_data(), self.purpose.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "ScriptContext")?;
+
+        let context = ScriptContext {
+            tx_info: fields.field("tx_info")?,
+            purpose: fields.field("purpose")?,
+        };
+
+        fields.end(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{constant::Integer, machine::OwnedData};
+
+    use super::{
+        Address, ContextError, Credential, IsData, ScriptContext, ScriptPurpose, TxId, TxInInfo,
+        TxInfo, TxOut, TxOutRef, Value,
+    };
+
+    fn bytes(b: &[u8]) -> OwnedData {
+        OwnedData::ByteString(b.to_vec())
+    }
+
+    fn constr(tag: u64, fields: Vec<OwnedData>) -> OwnedData {
+        OwnedData::Constr { tag, fields }
+    }
+
+    fn out_ref() -> TxOutRef {
+        TxOutRef {
+            id: TxId(vec![0xaa; 32]),
+            index: Integer::from(1),
+        }
+    }
+
+    #[test]
+    fn encodes_like_the_ledger() {
+        assert_eq!(
+            out_ref().to_data(),
+            constr(
+                0,
+                vec![
+                    constr(0, vec![bytes(&[0xaa; 32])]),
+                    OwnedData::Integer(Integer::from(1))
+                ]
+            )
+        );
+
+        let address = Address {
+            credential: Credential::Script(vec![0xbb; 28]),
+            staking_credential: None,
+        };
+
+        assert_eq!(
+            address.to_data(),
+            constr(
+                0,
+                vec![constr(1, vec![bytes(&[0xbb; 28])]), constr(1, vec![])]
+            )
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let output = TxOut {
+            address: Address {
+                credential: Credential::PubKey(vec![1; 28]),
+                staking_credential: None,
+            },
+            value: Value::lovelace(2_000_000),
+            datum_hash: Some(vec![2; 32]),
+        };
+
+        let context = ScriptContext {
+            tx_info: TxInfo {
+                inputs: vec![TxInInfo {
+                    out_ref: out_ref(),
+                    resolved: output.clone(),
+                }],
+                outputs: vec![output],
+                fee: Value::lovelace(170_000),
+                mint: Value::default(),
+                certificates: vec![],
+                withdrawals: vec![],
+                valid_range: super::Interval::always(),
+                signatories: vec![vec![1; 28]],
+                data: vec![(vec![2; 32], OwnedData::Integer(Integer::from(42)))],
+                id: TxId(vec![3; 32]),
+            },
+            purpose: ScriptPurpose::Spending(out_ref()),
+        };
+
+        let data = context.to_data();
+
+        assert_eq!(ScriptContext::from_data(&data), Ok(context));
+    }
+
+    #[test]
+    fn reports_where_decoding_fails() {
+        let data = constr(
+            0,
+            vec![constr(0, vec![bytes(&[0; 32])]), bytes(b"not an index")],
+        );
+
+        let error = TxOutRef::from_data(&data).unwrap_err();
+
+        assert_eq!(error.path(), "index");
+        assert_eq!(error.to_string(), "Expected an integer at index");
+
+        let extra = constr(1, vec![bytes(&[0; 28]), bytes(&[])]);
+
+        assert_eq!(
+            Credential::from_data(&extra),
+            Err(ContextError::ExtraField {
+                typ: "Credential",
+                index: 1,
+                path: String::new()
+            })
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! The script context of Plutus V2, as in `PlutusLedgerApi.V2`.
+
+use crate::{constant::Integer, machine::OwnedData};
+
+use super::is_data::{constr, AssocMap, ContextError, Fields, IsData};
+
+pub use super::v1::{
+    Address, Credential, CurrencySymbol, DCert, Datum, DatumHash, Extended, Interval,
+    IntervalBound, PubKeyHash, Redeemer, ScriptHash, ScriptPurpose, StakingCredential, TokenName,
+    TxId, TxOutRef, Value,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputDatum {
+    None,
+    Hash(DatumHash),
+    Inline(Datum),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxOut {
+    pub address: Address,
+    pub value: Value,
+    pub datum: OutputDatum,
+    pub reference_script: Option<ScriptHash>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInInfo {
+    pub out_ref: TxOutRef,
+    pub resolved: TxOut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInfo {
+    pub inputs: Vec<TxInInfo>,
+    pub reference_inputs: Vec<TxInInfo>,
+    pub outputs: Vec<TxOut>,
+    pub fee: Value,
+    pub mint: Value,
+    pub certificates: Vec<DCert>,
+    pub withdrawals: AssocMap<StakingCredential, Integer>,
+    pub valid_range: Interval,
+    pub signatories: Vec<PubKeyHash>,
+    pub redeemers: AssocMap<ScriptPurpose, Redeemer>,
+    pub data: AssocMap<DatumHash, Datum>,
+    pub id: TxId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptContext {
+    pub tx_info: TxInfo,
+    pub purpose: ScriptPurpose,
+}
+
+impl IsData for OutputDatum {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            OutputDatum::None => constr(0, vec![]),
+            OutputDatum::Hash(hash) => constr(1, vec![hash.to_data()]),
+            OutputDatum::Inline(datum) => constr(2, vec![datum.clone()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "OutputDatum")?;
+
+        let datum = match tag {
+            0 => OutputDatum::None,
+            1 => OutputDatum::Hash(fields.field("hash")?),
+            2 => OutputDatum::Inline(fields.field("datum")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(datum)
+    }
+}
+
+impl IsData for TxOut {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.address.to_data(),
+                self.value.to_data(),
+                self.datum.to_data(),
+                self.reference_script.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxOut")?;
+
+        let output = TxOut {
+            address: fields.field("address")?,
+            value: fields.field("value")?,
+            datum: fields.field("datum")?,
+            reference_script: fields.field("reference_script")?,
+        };
+
+        fields.end(output)
+    }
+}
+
+impl IsData for TxInInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.out_ref.to_data(), self.resolved.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInInfo")?;
+
+        let input = TxInInfo {
+            out_ref: fields.field("out_ref")?,
+            resolved: fields.field("resolved")?,
+        };
+
+        fields.end(input)
+    }
+}
+
+impl IsData for TxInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.inputs.to_data(),
+                self.reference_inputs.to_data(),
+                self.outputs.to_data(),
+                self.fee.to_data(),
+                self.mint.to_data(),
+                self.certificates.to_data(),
+                self.withdrawals.to_data(),
+                self.valid_range.to_data(),
+                self.signatories.to_data(),
+                self.redeemers.to_data(),
+                self.data.to_data(),
+                self.id.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInfo")?;
+
+        let tx_info = TxInfo {
+            inputs: fields.field("inputs")?,
+            reference_inputs: fields.field("reference_inputs")?,
+            outputs: fields.field("outputs")?,
+            fee: fields.field("fee")?,
+            mint: fields.field("mint")?,
+            certificates: fields.field("certificates")?,
+            withdrawals: fields.field("withdrawals")?,
+            valid_range: fields.field("valid_range")?,
+            signatories: fields.field("signatories")?,
+            redeemers: fields.field("redeemers")?,
+            data: fields.field("data")?,
+            id: fields.field("id")?,
+        };
+
+        fields.end(tx_info)
+    }
+}
+
+impl IsData for ScriptContext {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.tx_info.to_data(), self.purpose.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "ScriptContext")?;
+
+        let context = ScriptContext {
+            tx_info: fields.field("tx_info")?,
+            purpose: fields.field("purpose")?,
+        };
+
+        fields.end(context)
+    }
+}
@@ -0,0 +1,835 @@
+//! The script context of Plutus V3, as in `PlutusLedgerApi.V3`.
+
+use crate::{constant::Integer, machine::OwnedData};
+
+use super::is_data::{constr, AssocMap, ContextError, Fields, IsData};
+
+pub use super::v1::{
+    Address, Credential, CurrencySymbol, Datum, DatumHash, Extended, Interval, IntervalBound,
+    PubKeyHash, Redeemer, ScriptHash, StakingCredential, TokenName, Value,
+};
+pub use super::v2::{OutputDatum, TxOut};
+
+/// A transaction id, a bare byte string from Plutus V3 on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxId(pub Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxOutRef {
+    pub id: TxId,
+    pub index: Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInInfo {
+    pub out_ref: TxOutRef,
+    pub resolved: TxOut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DRep {
+    Credential(Credential),
+    AlwaysAbstain,
+    AlwaysNoConfidence,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delegatee {
+    Stake(PubKeyHash),
+    Vote(DRep),
+    StakeVote(PubKeyHash, DRep),
+}
+
+/// A certificate, amounts are in lovelace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxCert {
+    RegStaking(Credential, Option<Integer>),
+    UnRegStaking(Credential, Option<Integer>),
+    DelegStaking(Credential, Delegatee),
+    RegDeleg(Credential, Delegatee, Integer),
+    RegDRep(Credential, Integer),
+    UpdateDRep(Credential),
+    UnRegDRep(Credential, Integer),
+    PoolRegister { pool: PubKeyHash, vrf: PubKeyHash },
+    PoolRetire { pool: PubKeyHash, epoch: Integer },
+    AuthHotCommittee { cold: Credential, hot: Credential },
+    ResignColdCommittee(Credential),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Voter {
+    Committee(Credential),
+    DRep(Credential),
+    StakePool(PubKeyHash),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vote {
+    No,
+    Yes,
+    Abstain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GovernanceActionId {
+    pub tx_id: TxId,
+    pub index: Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolVersion {
+    pub major: Integer,
+    pub minor: Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rational {
+    pub numerator: Integer,
+    pub denominator: Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constitution {
+    pub guardrail: Option<ScriptHash>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GovernanceAction {
+    ParameterChange {
+        previous: Option<GovernanceActionId>,
+        parameters: OwnedData,
+        guardrail: Option<ScriptHash>,
+    },
+    HardForkInitiation {
+        previous: Option<GovernanceActionId>,
+        version: ProtocolVersion,
+    },
+    TreasuryWithdrawals {
+        withdrawals: AssocMap<Credential, Integer>,
+        guardrail: Option<ScriptHash>,
+    },
+    NoConfidence {
+        previous: Option<GovernanceActionId>,
+    },
+    UpdateCommittee {
+        previous: Option<GovernanceActionId>,
+        removed: Vec<Credential>,
+        added: AssocMap<Credential, Integer>,
+        quorum: Rational,
+    },
+    NewConstitution {
+        previous: Option<GovernanceActionId>,
+        constitution: Constitution,
+    },
+    InfoAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposalProcedure {
+    pub deposit: Integer,
+    pub return_address: Credential,
+    pub action: GovernanceAction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptPurpose {
+    Minting(CurrencySymbol),
+    Spending(TxOutRef),
+    Rewarding(Credential),
+    Certifying(Integer, TxCert),
+    Voting(Voter),
+    Proposing(Integer, ProposalProcedure),
+}
+
+/// The purpose of the running script, with the datum of the output it
+/// spends if there is one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptInfo {
+    Minting(CurrencySymbol),
+    Spending(TxOutRef, Option<Datum>),
+    Rewarding(Credential),
+    Certifying(Integer, TxCert),
+    Voting(Voter),
+    Proposing(Integer, ProposalProcedure),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxInfo {
+    pub inputs: Vec<TxInInfo>,
+    pub reference_inputs: Vec<TxInInfo>,
+    pub outputs: Vec<TxOut>,
+    pub fee: Integer,
+    pub mint: Value,
+    pub certificates: Vec<TxCert>,
+    pub withdrawals: AssocMap<Credential, Integer>,
+    pub valid_range: Interval,
+    pub signatories: Vec<PubKeyHash>,
+    pub redeemers: AssocMap<ScriptPurpose, Redeemer>,
+    pub data: AssocMap<DatumHash, Datum>,
+    pub id: TxId,
+    pub votes: AssocMap<Voter, AssocMap<GovernanceActionId, Vote>>,
+    pub proposal_procedures: Vec<ProposalProcedure>,
+    pub current_treasury_amount: Option<Integer>,
+    pub treasury_donation: Option<Integer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptContext {
+    pub tx_info: TxInfo,
+    pub redeemer: Redeemer,
+    pub script_info: ScriptInfo,
+}
+
+impl IsData for TxId {
+    fn to_data(&self) -> OwnedData {
+        self.0.to_data()
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        Vec::from_data(data).map(TxId)
+    }
+}
+
+impl IsData for TxOutRef {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.id.to_data(), self.index.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxOutRef")?;
+
+        let out_ref = TxOutRef {
+            id: fields.field("id")?,
+            index: fields.field("index")?,
+        };
+
+        fields.end(out_ref)
+    }
+}
+
+impl IsData for TxInInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.out_ref.to_data(), self.resolved.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInInfo")?;
+
+        let input = TxInInfo {
+            out_ref: fields.field("out_ref")?,
+            resolved: fields.field("resolved")?,
+        };
+
+        fields.end(input)
+    }
+}
+
+impl IsData for DRep {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            DRep::Credential(credential) => constr(0, vec![credential.to_data()]),
+            DRep::AlwaysAbstain => constr(1, vec![]),
+            DRep::AlwaysNoConfidence => constr(2, vec![]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "DRep")?;
+
+        let drep = match tag {
+            0 => DRep::Credential(fields.field("credential")?),
+            1 => DRep::AlwaysAbstain,
+            2 => DRep::AlwaysNoConfidence,
+            _ => return fields.unknown(),
+        };
+
+        fields.end(drep)
+    }
+}
+
+impl IsData for Delegatee {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            Delegatee::Stake(pool) => constr(0, vec![pool.to_data()]),
+            Delegatee::Vote(drep) => constr(1, vec![drep.to_data()]),
+            Delegatee::StakeVote(pool, drep) => constr(2, vec![pool.to_data(), drep.to_data()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "Delegatee")?;
+
+        let delegatee = match tag {
+            0 => Delegatee::Stake(fields.field("pool")?),
+            1 => Delegatee::Vote(fields.field("drep")?),
+            2 => Delegatee::StakeVote(fields.field("pool")?, fields.field("drep")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(delegatee)
+    }
+}
+
+impl IsData for TxCert {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            TxCert::RegStaking(credential, deposit) => {
+                constr(0, vec![credential.to_data(), deposit.to_data()])
+            }
+            TxCert::UnRegStaking(credential, refund) => {
+                constr(1, vec![credential.to_data(), refund.to_data()])
+            }
+            TxCert::DelegStaking(credential, delegatee) => {
+                constr(2, vec![credential.to_data(), delegatee.to_data()])
+            }
+            TxCert::RegDeleg(credential, delegatee, deposit) => constr(
+                3,
+                vec![credential.to_data(), delegatee.to_data(), deposit.to_data()],
+            ),
+            TxCert::RegDRep(credential, deposit) => {
+                constr(4, vec![credential.to_data(), deposit.to_data()])
+            }
+            TxCert::UpdateDRep(credential) => constr(5, vec![credential.to_data()]),
+            TxCert::UnRegDRep(credential, refund) => {
+                constr(6, vec![credential.to_data(), refund.to_data()])
+            }
+            TxCert::PoolRegister { pool, vrf } => constr(7, vec![pool.to_data(), vrf.to_data()]),
+            TxCert::PoolRetire { pool, epoch } => constr(8, vec![pool.to_data(), epoch.to_data()]),
+            TxCert::AuthHotCommittee { cold, hot } => {
+                constr(9, vec![cold.to_data(), hot.to_data()])
+            }
+            TxCert::ResignColdCommittee(cold) => constr(10, vec![cold.to_data()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "TxCert")?;
+
+        let certificate = match tag {
+            0 => TxCert::RegStaking(fields.field("credential")?, fields.field("deposit")?),
+            1 => TxCert::UnRegStaking(fields.field("credential")?, fields.field("refund")?),
+            2 => TxCert::DelegStaking(fields.field("credential")?, fields.field("delegatee")?),
+            3 => TxCert::RegDeleg(
+                fields.field("credential")?,
+                fields.field("delegatee")?,
+                fields.field("deposit")?,
+            ),
+            4 => TxCert::RegDRep(fields.field("credential")?, fields.field("deposit")?),
+            5 => TxCert::UpdateDRep(fields.field("credential")?),
+            6 => TxCert::UnRegDRep(fields.field("credential")?, fields.field("refund")?),
+            7 => TxCert::PoolRegister {
+                pool: fields.field("pool")?,
+                vrf: fields.field("vrf")?,
+            },
+            8 => TxCert::PoolRetire {
+                pool: fields.field("pool")?,
+                epoch: fields.field("epoch")?,
+            },
+            9 => TxCert::AuthHotCommittee {
+                cold: fields.field("cold")?,
+                hot: fields.field("hot")?,
+            },
+            10 => TxCert::ResignColdCommittee(fields.field("cold")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(certificate)
+    }
+}
+
+impl IsData for Voter {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            Voter::Committee(credential) => constr(0, vec![credential.to_data()]),
+            Voter::DRep(credential) => constr(1, vec![credential.to_data()]),
+            Voter::StakePool(pool) => constr(2, vec![pool.to_data()]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "Voter")?;
+
+        let voter = match tag {
+            0 => Voter::Committee(fields.field("credential")?),
+            1 => Voter::DRep(fields.field("credential")?),
+            2 => Voter::StakePool(fields.field("pool")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(voter)
+    }
+}
+
+impl IsData for Vote {
+    fn to_data(&self) -> OwnedData {
+        constr(*self as u64, vec![])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, fields) = Fields::of(data, "Vote")?;
+
+        let vote = match tag {
+            0 => Vote::No,
+            1 => Vote::Yes,
+            2 => Vote::Abstain,
+            _ => return fields.unknown(),
+        };
+
+        fields.end(vote)
+    }
+}
+
+impl IsData for GovernanceActionId {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.tx_id.to_data(), self.index.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "GovernanceActionId")?;
+
+        let id = GovernanceActionId {
+            tx_id: fields.field("tx_id")?,
+            index: fields.field("index")?,
+        };
+
+        fields.end(id)
+    }
+}
+
+impl IsData for ProtocolVersion {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.major.to_data(), self.minor.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "ProtocolVersion")?;
+
+        let version = ProtocolVersion {
+            major: fields.field("major")?,
+            minor: fields.field("minor")?,
+        };
+
+        fields.end(version)
+    }
+}
+
+impl IsData for Rational {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![self.numerator.to_data(), self.denominator.to_data()],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "Rational")?;
+
+        let rational = Rational {
+            numerator: fields.field("numerator")?,
+            denominator: fields.field("denominator")?,
+        };
+
+        fields.end(rational)
+    }
+}
+
+impl IsData for Constitution {
+    fn to_data(&self) -> OwnedData {
+        constr(0, vec![self.guardrail.to_data()])
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "Constitution")?;
+
+        let constitution = Constitution {
+            guardrail: fields.field("guardrail")?,
+        };
+
+        fields.end(constitution)
+    }
+}
+
+impl IsData for GovernanceAction {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            GovernanceAction::ParameterChange {
+                previous,
+                parameters,
+                guardrail,
+            } => constr(
+                0,
+                vec![previous.to_data(), parameters.clone(), guardrail.to_data()],
+            ),
+            GovernanceAction::HardForkInitiation { previous, version } => {
+                constr(1, vec![previous.to_data(), version.to_data()])
+            }
+            GovernanceAction::TreasuryWithdrawals {
+                withdrawals,
+                guardrail,
+            } => constr(2, vec![withdrawals.to_data(), guardrail.to_data()]),
+            GovernanceAction::NoConfidence { previous } => constr(3, vec![previous.to_data()]),
+            GovernanceAction::UpdateCommittee {
+                previous,
+                removed,
+                added,
+                quorum,
+            } => constr(
+                4,
+                vec![
+                    previous.to_data(),
+                    removed.to_data(),
+                    added.to_data(),
+                    quorum.to_data(),
+                ],
+            ),
+            GovernanceAction::NewConstitution {
+                previous,
+                constitution,
+            } => constr(5, vec![previous.to_data(), constitution.to_data()]),
+            GovernanceAction::InfoAction => constr(6, vec![]),
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "GovernanceAction")?;
+
+        let action = match tag {
+            0 => GovernanceAction::ParameterChange {
+                previous: fields.field("previous")?,
+                parameters: fields.field("parameters")?,
+                guardrail: fields.field("guardrail")?,
+            },
+            1 => GovernanceAction::HardForkInitiation {
+                previous: fields.field("previous")?,
+                version: fields.field("version")?,
+            },
+            2 => GovernanceAction::TreasuryWithdrawals {
+                withdrawals: fields.field("withdrawals")?,
+                guardrail: fields.field("guardrail")?,
+            },
+            3 => GovernanceAction::NoConfidence {
+                previous: fields.field("previous")?,
+            },
+            4 => GovernanceAction::UpdateCommittee {
+                previous: fields.field("previous")?,
+                removed: fields.field("removed")?,
+                added: fields.field("added")?,
+                quorum: fields.field("quorum")?,
+            },
+            5 => GovernanceAction::NewConstitution {
+                previous: fields.field("previous")?,
+                constitution: fields.field("constitution")?,
+            },
+            6 => GovernanceAction::InfoAction,
+            _ => return fields.unknown(),
+        };
+
+        fields.end(action)
+    }
+}
+
+impl IsData for ProposalProcedure {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.deposit.to_data(),
+                self.return_address.to_data(),
+                self.action.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "ProposalProcedure")?;
+
+        let procedure = ProposalProcedure {
+            deposit: fields.field("deposit")?,
+            return_address: fields.field("return_address")?,
+            action: fields.field("action")?,
+        };
+
+        fields.end(procedure)
+    }
+}
+
+impl IsData for ScriptPurpose {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            ScriptPurpose::Minting(symbol) => constr(0, vec![symbol.to_data()]),
+            ScriptPurpose::Spending(out_ref) => constr(1, vec![out_ref.to_data()]),
+            ScriptPurpose::Rewarding(credential) => constr(2, vec![credential.to_data()]),
+            ScriptPurpose::Certifying(index, certificate) => {
+                constr(3, vec![index.to_data(), certificate.to_data()])
+            }
+            ScriptPurpose::Voting(voter) => constr(4, vec![voter.to_data()]),
+            ScriptPurpose::Proposing(index, procedure) => {
+                constr(5, vec![index.to_data(), procedure.to_data()])
+            }
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "ScriptPurpose")?;
+
+        let purpose = match tag {
+            0 => ScriptPurpose::Minting(fields.field("currency_symbol")?),
+            1 => ScriptPurpose::Spending(fields.field("out_ref")?),
+            2 => ScriptPurpose::Rewarding(fields.field("credential")?),
+            3 => ScriptPurpose::Certifying(fields.field("index")?, fields.field("certificate")?),
+            4 => ScriptPurpose::Voting(fields.field("voter")?),
+            5 => ScriptPurpose::Proposing(fields.field("index")?, fields.field("procedure")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(purpose)
+    }
+}
+
+impl IsData for ScriptInfo {
+    fn to_data(&self) -> OwnedData {
+        match self {
+            ScriptInfo::Minting(symbol) => constr(0, vec![symbol.to_data()]),
+            ScriptInfo::Spending(out_ref, datum) => {
+                constr(1, vec![out_ref.to_data(), datum.to_data()])
+            }
+            ScriptInfo::Rewarding(credential) => constr(2, vec![credential.to_data()]),
+            ScriptInfo::Certifying(index, certificate) => {
+                constr(3, vec![index.to_data(), certificate.to_data()])
+            }
+            ScriptInfo::Voting(voter) => constr(4, vec![voter.to_data()]),
+            ScriptInfo::Proposing(index, procedure) => {
+                constr(5, vec![index.to_data(), procedure.to_data()])
+            }
+        }
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let (tag, mut fields) = Fields::of(data, "ScriptInfo")?;
+
+        let info = match tag {
+            0 => ScriptInfo::Minting(fields.field("currency_symbol")?),
+            1 => ScriptInfo::Spending(fields.field("out_ref")?, fields.field("datum")?),
+            2 => ScriptInfo::Rewarding(fields.field("credential")?),
+            3 => ScriptInfo::Certifying(fields.field("index")?, fields.field("certificate")?),
+            4 => ScriptInfo::Voting(fields.field("voter")?),
+            5 => ScriptInfo::Proposing(fields.field("index")?, fields.field("procedure")?),
+            _ => return fields.unknown(),
+        };
+
+        fields.end(info)
+    }
+}
+
+impl IsData for TxInfo {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.inputs.to_data(),
+                self.reference_inputs.to_data(),
+                self.outputs.to_data(),
+                self.fee.to_data(),
+                self.mint.to_data(),
+                self.certificates.to_data(),
+                self.withdrawals.to_data(),
+                self.valid_range.to_data(),
+                self.signatories.to_data(),
+                self.redeemers.to_data(),
+                self.data.to_data(),
+                self.id.to_data(),
+                self.votes.to_data(),
+                self.proposal_procedures.to_data(),
+                self.current_treasury_amount.to_data(),
+                self.treasury_donation.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "TxInfo")?;
+
+        let tx_info = TxInfo {
+            inputs: fields.field("inputs")?,
+            reference_inputs: fields.field("reference_inputs")?,
+            outputs: fields.field("outputs")?,
+            fee: fields.field("fee")?,
+            mint: fields.field("mint")?,
+            certificates: fields.field("certificates")?,
+            withdrawals: fields.field("withdrawals")?,
+            valid_range: fields.field("valid_range")?,
+            signatories: fields.field("signatories")?,
+            redeemers: fields.field("redeemers")?,
+            data: fields.field("data")?,
+            id: fields.field("id")?,
+            votes: fields.field("votes")?,
+            proposal_procedures: fields.field("proposal_procedures")?,
+            current_treasury_amount: fields.field("current_treasury_amount")?,
+            treasury_donation: fields.field("treasury_donation")?,
+        };
+
+        fields.end(tx_info)
+    }
+}
+
+impl IsData for ScriptContext {
+    fn to_data(&self) -> OwnedData {
+        constr(
+            0,
+            vec![
+                self.tx_info.to_data(),
+                self.redeemer.clone(),
+                self.script_info.to_data(),
+            ],
+        )
+    }
+
+    fn from_data(data: &OwnedData) -> Result<Self, ContextError> {
+        let mut fields = Fields::record(data, "ScriptContext")?;
+
+        let context = ScriptContext {
+            tx_info: fields.field("tx_info")?,
+            redeemer: fields.field("redeemer")?,
+            script_info: fields.field("script_info")?,
+        };
+
+        fields.end(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena, constant::Integer, data::PlutusData, machine::OwnedData,
+        script_context::AssocMap,
+    };
+
+    use super::{
+        Address, Credential, GovernanceAction, GovernanceActionId, Interval, IsData, OutputDatum,
+        ProposalProcedure, Rational, ScriptContext, ScriptInfo, ScriptPurpose, TxCert, TxId,
+        TxInInfo, TxInfo, TxOut, TxOutRef, Value, Vote, Voter,
+    };
+
+    fn context() -> ScriptContext {
+        let out_ref = TxOutRef {
+            id: TxId(vec![0xaa; 32]),
+            index: Integer::from(0),
+        };
+
+        let output = TxOut {
+            address: Address {
+                credential: Credential::Script(vec![0xbb; 28]),
+                staking_credential: None,
+            },
+            value: Value::lovelace(5_000_000),
+            datum: OutputDatum::Inline(OwnedData::Integer(Integer::from(7))),
+            reference_script: None,
+        };
+
+        let action_id = GovernanceActionId {
+            tx_id: TxId(vec![0xcc; 32]),
+            index: Integer::from(3),
+        };
+
+        let proposal = ProposalProcedure {
+            deposit: Integer::from(100_000_000_000u64),
+            return_address: Credential::PubKey(vec![1; 28]),
+            action: GovernanceAction::UpdateCommittee {
+                previous: Some(action_id.clone()),
+                removed: vec![Credential::Script(vec![2; 28])],
+                added: AssocMap(vec![(Credential::PubKey(vec![3; 28]), Integer::from(400))]),
+                quorum: Rational {
+                    numerator: Integer::from(2),
+                    denominator: Integer::from(3),
+                },
+            },
+        };
+
+        ScriptContext {
+            tx_info: TxInfo {
+                inputs: vec![TxInInfo {
+                    out_ref: out_ref.clone(),
+                    resolved: output.clone(),
+                }],
+                reference_inputs: vec![],
+                outputs: vec![output],
+                fee: Integer::from(180_000),
+                mint: Value::default(),
+                certificates: vec![TxCert::RegStaking(
+                    Credential::PubKey(vec![1; 28]),
+                    Some(Integer::from(2_000_000)),
+                )],
+                withdrawals: AssocMap::default(),
+                valid_range: Interval::always(),
+                signatories: vec![vec![1; 28]],
+                redeemers: AssocMap(vec![(
+                    ScriptPurpose::Spending(out_ref.clone()),
+                    OwnedData::List(vec![]),
+                )]),
+                data: AssocMap::default(),
+                id: TxId(vec![0xdd; 32]),
+                votes: AssocMap(vec![(
+                    Voter::DRep(Credential::PubKey(vec![4; 28])),
+                    AssocMap(vec![(action_id, Vote::Yes)]),
+                )]),
+                proposal_procedures: vec![proposal],
+                current_treasury_amount: Some(Integer::from(1_000)),
+                treasury_donation: None,
+            },
+            redeemer: OwnedData::List(vec![]),
+            script_info: ScriptInfo::Spending(out_ref, None),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_plutus_data() {
+        let arena = Arena::new();
+
+        let context = context();
+        let data = context.to_plutus_data(&arena);
+
+        assert_eq!(ScriptContext::from_plutus_data(data), Ok(context));
+    }
+
+    #[test]
+    fn tx_id_is_a_bare_byte_string() {
+        let arena = Arena::new();
+
+        let data = TxId(vec![1, 2]).to_plutus_data(&arena);
+
+        assert_eq!(data, &PlutusData::ByteString(&[1, 2]));
+    }
+
+    #[test]
+    fn reports_where_decoding_fails() {
+        let mut data = context().to_data();
+
+        // Give the vote an unknown constructor.
+        let OwnedData::Constr { fields, .. } = &mut data else {
+            unreachable!()
+        };
+        let OwnedData::Constr { fields, .. } = &mut fields[0] else {
+            unreachable!()
+        };
+        let OwnedData::Map(votes) = &mut fields[12] else {
+            unreachable!()
+        };
+        let OwnedData::Map(actions) = &mut votes[0].1 else {
+            unreachable!()
+        };
+
+        actions[0].1 = OwnedData::Constr {
+            tag: 3,
+            fields: vec![],
+        };
+
+        let error = ScriptContext::from_data(&data).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Unknown Vote constructor 3 at tx_info.votes[0].value[0].value"
+        );
+    }
+}
@@ -9,6 +9,8 @@ mod types;
 mod utils;
 mod version;
 
+pub(crate) use term::builtin_from_str;
+
 use crate::{
     arena::Arena, binder::DeBruijn, constant::Constant, data::PlutusData, program::Program,
     term::Term,
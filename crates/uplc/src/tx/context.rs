@@ -0,0 +1,590 @@
+//! Translation of a decoded transaction into the script context of every
+//! Plutus version, following the ledger's `TxInfo` translation.
+
+use crate::{
+    constant::Integer,
+    machine::{OwnedData, PlutusVersion},
+    script_context::{
+        v1::{
+            self, Address, Credential, DCert, Extended, Interval, IntervalBound, StakingCredential,
+        },
+        v2::{self, OutputDatum},
+        v3::{self, ProposalProcedure, TxCert, TxOutRef, Voter},
+        AssocMap,
+    },
+};
+
+use super::{
+    decode::{Output, Transaction},
+    RedeemerTag, SlotConfig, TxError,
+};
+
+/// What a redeemer points to.
+pub(super) enum Purpose<'t> {
+    Spend(&'t TxOutRef, &'t Output),
+    Mint(&'t [u8]),
+    Cert(usize, &'t TxCert),
+    Reward(&'t Credential),
+    Vote(&'t Voter),
+    Propose(usize, &'t ProposalProcedure),
+}
+
+impl Purpose<'_> {
+    /// The hash of the script that must validate this purpose.
+    pub fn script_hash(&self) -> Option<&[u8]> {
+        fn credential(credential: &Credential) -> Option<&[u8]> {
+            match credential {
+                Credential::Script(hash) => Some(hash),
+                Credential::PubKey(_) => None,
+            }
+        }
+
+        match self {
+            Purpose::Spend(_, output) => match address(&output.address).ok()?.credential {
+                Credential::Script(_) => Some(&output.address[1..29]),
+                Credential::PubKey(_) => None,
+            },
+            Purpose::Mint(policy) => Some(policy),
+            Purpose::Cert(_, certificate) => match certificate {
+                TxCert::RegStaking(cred, _)
+                | TxCert::UnRegStaking(cred, _)
+                | TxCert::DelegStaking(cred, _)
+                | TxCert::RegDeleg(cred, _, _)
+                | TxCert::RegDRep(cred, _)
+                | TxCert::UpdateDRep(cred)
+                | TxCert::UnRegDRep(cred, _)
+                | TxCert::AuthHotCommittee { cold: cred, .. }
+                | TxCert::ResignColdCommittee(cred) => credential(cred),
+                TxCert::PoolRegister { .. } | TxCert::PoolRetire { .. } => None,
+            },
+            Purpose::Reward(cred) => credential(cred),
+            Purpose::Vote(voter) => match voter {
+                Voter::Committee(cred) | Voter::DRep(cred) => credential(cred),
+                Voter::StakePool(_) => None,
+            },
+            Purpose::Propose(_, proposal) => match &proposal.action {
+                v3::GovernanceAction::ParameterChange { guardrail, .. }
+                | v3::GovernanceAction::TreasuryWithdrawals { guardrail, .. } => {
+                    guardrail.as_deref()
+                }
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A transaction with its inputs resolved and every redeemer matched to
+/// its purpose.
+pub(super) struct TxContext<'t> {
+    tx: &'t Transaction,
+    inputs: Vec<(&'t TxOutRef, &'t Output)>,
+    reference_inputs: Vec<(&'t TxOutRef, &'t Output)>,
+    purposes: Vec<Purpose<'t>>,
+    slot_config: SlotConfig,
+}
+
+impl<'t> TxContext<'t> {
+    pub fn new(
+        tx: &'t Transaction,
+        utxos: &'t [(TxOutRef, Output)],
+        slot_config: SlotConfig,
+    ) -> Result<Self, TxError> {
+        let resolve = |inputs: &'t [TxOutRef]| {
+            inputs
+                .iter()
+                .map(|input| {
+                    utxos
+                        .iter()
+                        .find(|(out_ref, _)| out_ref == input)
+                        .map(|(out_ref, output)| (out_ref, output))
+                        .ok_or_else(|| TxError::MissingInput(out_ref_name(input)))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let inputs = resolve(&tx.inputs)?;
+        let reference_inputs = resolve(&tx.reference_inputs)?;
+
+        let purposes = tx
+            .redeemers
+            .iter()
+            .map(|redeemer| {
+                let index = redeemer.index as usize;
+
+                let purpose = match redeemer.tag {
+                    RedeemerTag::Spend => inputs
+                        .get(index)
+                        .map(|(out_ref, output)| Purpose::Spend(out_ref, output)),
+                    RedeemerTag::Mint => {
+                        tx.mint.get(index).map(|(policy, _)| Purpose::Mint(policy))
+                    }
+                    RedeemerTag::Cert => tx
+                        .certificates
+                        .get(index)
+                        .map(|certificate| Purpose::Cert(index, certificate)),
+                    RedeemerTag::Reward => tx
+                        .withdrawals
+                        .get(index)
+                        .map(|(credential, _)| Purpose::Reward(credential)),
+                    RedeemerTag::Vote => tx.votes.get(index).map(|(voter, _)| Purpose::Vote(voter)),
+                    RedeemerTag::Propose => tx
+                        .proposals
+                        .get(index)
+                        .map(|proposal| Purpose::Propose(index, proposal)),
+                };
+
+                purpose.ok_or(TxError::UnknownTarget {
+                    tag: redeemer.tag,
+                    index: redeemer.index,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(TxContext {
+            tx,
+            inputs,
+            reference_inputs,
+            purposes,
+            slot_config,
+        })
+    }
+
+    pub fn purposes(&self) -> &[Purpose<'t>] {
+        &self.purposes
+    }
+
+    /// Every output a reference script may come from.
+    pub fn resolved(&self) -> impl Iterator<Item = &'t Output> + '_ {
+        self.inputs
+            .iter()
+            .chain(&self.reference_inputs)
+            .map(|(_, output)| *output)
+    }
+
+    /// The datum of the output spent by a purpose, if it has one.
+    pub fn datum(&self, purpose: &Purpose<'_>) -> Result<Option<OwnedData>, TxError> {
+        match purpose {
+            Purpose::Spend(_, output) => match &output.datum {
+                OutputDatum::None => Ok(None),
+                OutputDatum::Hash(hash) => self
+                    .tx
+                    .data
+                    .iter()
+                    .find(|(candidate, _)| candidate == hash)
+                    .map(|(_, datum)| Some(datum.clone()))
+                    .ok_or_else(|| TxError::MissingDatum(hex::encode(hash))),
+                OutputDatum::Inline(datum) => Ok(Some(datum.clone())),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    pub fn v1(&self, redeemer: usize) -> Result<v1::ScriptContext, TxError> {
+        const VERSION: PlutusVersion = PlutusVersion::V1;
+
+        if !self.reference_inputs.is_empty() {
+            return Err(unsupported(VERSION, "reference inputs"));
+        }
+
+        let output = |output: &Output| {
+            let datum_hash = match &output.datum {
+                OutputDatum::None => None,
+                OutputDatum::Hash(hash) => Some(hash.clone()),
+                OutputDatum::Inline(_) => return Err(unsupported(VERSION, "inline datums")),
+            };
+
+            if output.script_ref.is_some() {
+                return Err(unsupported(VERSION, "reference scripts"));
+            }
+
+            Ok(v1::TxOut {
+                address: address(&output.address)?,
+                value: output.value.clone(),
+                datum_hash,
+            })
+        };
+
+        let tx_info = v1::TxInfo {
+            inputs: self
+                .inputs
+                .iter()
+                .map(|(out_ref, resolved)| {
+                    Ok(v1::TxInInfo {
+                        out_ref: legacy_out_ref(out_ref),
+                        resolved: output(resolved)?,
+                    })
+                })
+                .collect::<Result<_, TxError>>()?,
+            outputs: self
+                .tx
+                .outputs
+                .iter()
+                .map(output)
+                .collect::<Result<_, _>>()?,
+            fee: v1::Value::lovelace(self.tx.fee.clone()),
+            mint: self.mint(true),
+            certificates: self
+                .tx
+                .certificates
+                .iter()
+                .map(|certificate| dcert(VERSION, certificate))
+                .collect::<Result<_, _>>()?,
+            withdrawals: self.legacy_withdrawals(),
+            valid_range: self.valid_range(),
+            signatories: self.tx.required_signers.clone(),
+            data: self.tx.data.clone(),
+            id: v1::TxId(self.tx.id.clone()),
+        };
+
+        Ok(v1::ScriptContext {
+            tx_info,
+            purpose: legacy_purpose(VERSION, &self.purposes[redeemer])?,
+        })
+    }
+
+    pub fn v2(&self, redeemer: usize) -> Result<v2::ScriptContext, TxError> {
+        const VERSION: PlutusVersion = PlutusVersion::V2;
+
+        let tx_info = v2::TxInfo {
+            inputs: self.v2_inputs(&self.inputs)?,
+            reference_inputs: self.v2_inputs(&self.reference_inputs)?,
+            outputs: self
+                .tx
+                .outputs
+                .iter()
+                .map(tx_out)
+                .collect::<Result<_, _>>()?,
+            fee: v1::Value::lovelace(self.tx.fee.clone()),
+            mint: self.mint(true),
+            certificates: self
+                .tx
+                .certificates
+                .iter()
+                .map(|certificate| dcert(VERSION, certificate))
+                .collect::<Result<_, _>>()?,
+            withdrawals: AssocMap(self.legacy_withdrawals()),
+            valid_range: self.valid_range(),
+            signatories: self.tx.required_signers.clone(),
+            redeemers: AssocMap(
+                self.purposes
+                    .iter()
+                    .zip(&self.tx.redeemers)
+                    .map(|(purpose, redeemer)| {
+                        Ok((legacy_purpose(VERSION, purpose)?, redeemer.data.clone()))
+                    })
+                    .collect::<Result<_, TxError>>()?,
+            ),
+            data: AssocMap(self.tx.data.clone()),
+            id: v1::TxId(self.tx.id.clone()),
+        };
+
+        Ok(v2::ScriptContext {
+            tx_info,
+            purpose: legacy_purpose(VERSION, &self.purposes[redeemer])?,
+        })
+    }
+
+    pub fn v3(&self, redeemer: usize) -> Result<v3::ScriptContext, TxError> {
+        let inputs = |inputs: &[(&TxOutRef, &Output)]| {
+            inputs
+                .iter()
+                .map(|(out_ref, output)| {
+                    Ok(v3::TxInInfo {
+                        out_ref: (*out_ref).clone(),
+                        resolved: tx_out(output)?,
+                    })
+                })
+                .collect::<Result<_, TxError>>()
+        };
+
+        let tx_info = v3::TxInfo {
+            inputs: inputs(&self.inputs)?,
+            reference_inputs: inputs(&self.reference_inputs)?,
+            outputs: self
+                .tx
+                .outputs
+                .iter()
+                .map(tx_out)
+                .collect::<Result<_, _>>()?,
+            fee: self.tx.fee.clone(),
+            mint: self.mint(false),
+            certificates: self.tx.certificates.clone(),
+            withdrawals: AssocMap(self.tx.withdrawals.clone()),
+            valid_range: self.valid_range(),
+            signatories: self.tx.required_signers.clone(),
+            redeemers: AssocMap(
+                self.purposes
+                    .iter()
+                    .zip(&self.tx.redeemers)
+                    .map(|(purpose, redeemer)| (purpose_v3(purpose), redeemer.data.clone()))
+                    .collect(),
+            ),
+            data: AssocMap(self.tx.data.clone()),
+            id: v3::TxId(self.tx.id.clone()),
+            votes: AssocMap(
+                self.tx
+                    .votes
+                    .iter()
+                    .map(|(voter, votes)| (voter.clone(), AssocMap(votes.clone())))
+                    .collect(),
+            ),
+            proposal_procedures: self.tx.proposals.clone(),
+            current_treasury_amount: self.tx.treasury.clone(),
+            treasury_donation: self.tx.donation.clone(),
+        };
+
+        let purpose = &self.purposes[redeemer];
+
+        let script_info = match purpose_v3(purpose) {
+            v3::ScriptPurpose::Minting(policy) => v3::ScriptInfo::Minting(policy),
+            v3::ScriptPurpose::Spending(out_ref) => {
+                v3::ScriptInfo::Spending(out_ref, self.datum(purpose)?)
+            }
+            v3::ScriptPurpose::Rewarding(credential) => v3::ScriptInfo::Rewarding(credential),
+            v3::ScriptPurpose::Certifying(index, certificate) => {
+                v3::ScriptInfo::Certifying(index, certificate)
+            }
+            v3::ScriptPurpose::Voting(voter) => v3::ScriptInfo::Voting(voter),
+            v3::ScriptPurpose::Proposing(index, proposal) => {
+                v3::ScriptInfo::Proposing(index, proposal)
+            }
+        };
+
+        Ok(v3::ScriptContext {
+            tx_info,
+            redeemer: self.tx.redeemers[redeemer].data.clone(),
+            script_info,
+        })
+    }
+
+    fn v2_inputs(&self, inputs: &[(&TxOutRef, &Output)]) -> Result<Vec<v2::TxInInfo>, TxError> {
+        inputs
+            .iter()
+            .map(|(out_ref, output)| {
+                Ok(v2::TxInInfo {
+                    out_ref: legacy_out_ref(out_ref),
+                    resolved: tx_out(output)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Minted tokens by policy. Up to V2 the ledger also includes a zero ada
+    /// entry.
+    fn mint(&self, with_ada: bool) -> v1::Value {
+        let mut value = if with_ada {
+            v1::Value::lovelace(0)
+        } else {
+            v1::Value::default()
+        };
+
+        value.0 .0.extend(
+            self.tx
+                .mint
+                .iter()
+                .map(|(policy, tokens)| (policy.clone(), AssocMap(tokens.clone()))),
+        );
+
+        value
+    }
+
+    fn legacy_withdrawals(&self) -> Vec<(StakingCredential, Integer)> {
+        self.tx
+            .withdrawals
+            .iter()
+            .map(|(credential, amount)| {
+                (StakingCredential::Hash(credential.clone()), amount.clone())
+            })
+            .collect()
+    }
+
+    /// The validity interval in POSIX milliseconds. The upper bound is
+    /// exclusive.
+    fn valid_range(&self) -> Interval {
+        let time = |slot: u64| {
+            let SlotConfig {
+                zero_time,
+                zero_slot,
+                slot_length,
+            } = self.slot_config;
+
+            Extended::Finite(
+                Integer::from(zero_time)
+                    + (Integer::from(slot) - Integer::from(zero_slot)) * Integer::from(slot_length),
+            )
+        };
+
+        Interval {
+            from: match self.tx.validity_start {
+                Some(slot) => IntervalBound {
+                    bound: time(slot),
+                    inclusive: true,
+                },
+                None => IntervalBound {
+                    bound: Extended::NegInf,
+                    inclusive: true,
+                },
+            },
+            to: match self.tx.ttl {
+                Some(slot) => IntervalBound {
+                    bound: time(slot),
+                    inclusive: false,
+                },
+                None => IntervalBound {
+                    bound: Extended::PosInf,
+                    inclusive: true,
+                },
+            },
+        }
+    }
+}
+
+fn tx_out(output: &Output) -> Result<v2::TxOut, TxError> {
+    Ok(v2::TxOut {
+        address: address(&output.address)?,
+        value: output.value.clone(),
+        datum: output.datum.clone(),
+        reference_script: output.script_ref.as_ref().map(|script| script.hash()),
+    })
+}
+
+fn legacy_out_ref(out_ref: &TxOutRef) -> v1::TxOutRef {
+    v1::TxOutRef {
+        id: v1::TxId(out_ref.id.0.clone()),
+        index: out_ref.index.clone(),
+    }
+}
+
+fn out_ref_name(out_ref: &TxOutRef) -> String {
+    format!("{}#{}", hex::encode(&out_ref.id.0), out_ref.index)
+}
+
+fn unsupported(version: PlutusVersion, feature: &'static str) -> TxError {
+    TxError::Unsupported { version, feature }
+}
+
+fn dcert(version: PlutusVersion, certificate: &TxCert) -> Result<DCert, TxError> {
+    let hash = |credential: &Credential| StakingCredential::Hash(credential.clone());
+
+    match certificate {
+        TxCert::RegStaking(credential, _) => Ok(DCert::DelegRegKey(hash(credential))),
+        TxCert::UnRegStaking(credential, _) => Ok(DCert::DelegDeRegKey(hash(credential))),
+        TxCert::DelegStaking(credential, v3::Delegatee::Stake(pool)) => {
+            Ok(DCert::DelegDelegate(hash(credential), pool.clone()))
+        }
+        TxCert::PoolRegister { pool, vrf } => Ok(DCert::PoolRegister {
+            pool: pool.clone(),
+            vrf: vrf.clone(),
+        }),
+        TxCert::PoolRetire { pool, epoch } => Ok(DCert::PoolRetire {
+            pool: pool.clone(),
+            epoch: epoch.clone(),
+        }),
+        _ => Err(unsupported(version, "governance certificates")),
+    }
+}
+
+fn legacy_purpose(
+    version: PlutusVersion,
+    purpose: &Purpose<'_>,
+) -> Result<v1::ScriptPurpose, TxError> {
+    match purpose {
+        Purpose::Spend(out_ref, _) => Ok(v1::ScriptPurpose::Spending(legacy_out_ref(out_ref))),
+        Purpose::Mint(policy) => Ok(v1::ScriptPurpose::Minting(policy.to_vec())),
+        Purpose::Cert(_, certificate) => {
+            Ok(v1::ScriptPurpose::Certifying(dcert(version, certificate)?))
+        }
+        Purpose::Reward(credential) => Ok(v1::ScriptPurpose::Rewarding(StakingCredential::Hash(
+            (*credential).clone(),
+        ))),
+        Purpose::Vote(_) => Err(unsupported(version, "voting")),
+        Purpose::Propose(..) => Err(unsupported(version, "proposing")),
+    }
+}
+
+fn purpose_v3(purpose: &Purpose<'_>) -> v3::ScriptPurpose {
+    match purpose {
+        Purpose::Spend(out_ref, _) => v3::ScriptPurpose::Spending((*out_ref).clone()),
+        Purpose::Mint(policy) => v3::ScriptPurpose::Minting(policy.to_vec()),
+        Purpose::Cert(index, certificate) => {
+            v3::ScriptPurpose::Certifying(Integer::from(*index), (*certificate).clone())
+        }
+        Purpose::Reward(credential) => v3::ScriptPurpose::Rewarding((*credential).clone()),
+        Purpose::Vote(voter) => v3::ScriptPurpose::Voting((*voter).clone()),
+        Purpose::Propose(index, proposal) => {
+            v3::ScriptPurpose::Proposing(Integer::from(*index), (*proposal).clone())
+        }
+    }
+}
+
+/// Shelley addresses as in CIP-19. The header's high nibble gives the kind
+/// of address, its odd types have a script payment part.
+pub(super) fn address(bytes: &[u8]) -> Result<Address, TxError> {
+    let (header, rest) = bytes.split_first().ok_or(TxError::MalformedAddress)?;
+
+    let kind = header >> 4;
+
+    if kind == 0b1000 {
+        return Err(TxError::ByronAddress);
+    }
+
+    if kind > 0b0111 || rest.len() < 28 {
+        return Err(TxError::MalformedAddress);
+    }
+
+    let (payment, rest) = rest.split_at(28);
+
+    let credential = |script: bool, hash: &[u8]| {
+        if script {
+            Credential::Script(hash.to_vec())
+        } else {
+            Credential::PubKey(hash.to_vec())
+        }
+    };
+
+    let staking_credential = match kind {
+        0..=3 if rest.len() == 28 => {
+            Some(StakingCredential::Hash(credential(kind & 0b10 != 0, rest)))
+        }
+        4 | 5 => {
+            let mut rest = rest;
+
+            let mut pointer = || -> Result<Integer, TxError> {
+                let mut value = Integer::from(0);
+
+                loop {
+                    let (byte, tail) = rest.split_first().ok_or(TxError::MalformedAddress)?;
+
+                    rest = tail;
+
+                    value = (value << 7) + Integer::from(byte & 0x7f);
+
+                    if byte & 0x80 == 0 {
+                        return Ok(value);
+                    }
+                }
+            };
+
+            let slot = pointer()?;
+            let transaction = pointer()?;
+            let certificate = pointer()?;
+
+            if !rest.is_empty() {
+                return Err(TxError::MalformedAddress);
+            }
+
+            Some(StakingCredential::Pointer {
+                slot,
+                transaction,
+                certificate,
+            })
+        }
+        6 | 7 if rest.is_empty() => None,
+        _ => return Err(TxError::MalformedAddress),
+    };
+
+    Ok(Address {
+        credential: credential(kind & 1 != 0, payment),
+        staking_credential,
+    })
+}
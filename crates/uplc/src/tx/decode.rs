@@ -0,0 +1,961 @@
+//! Decoding of Conway-era transactions, keeping only what script contexts
+//! need. Sets and maps are put in the order the ledger keeps them in.
+
+use std::cmp::Ordering;
+
+use minicbor::{data::Type, decode::Error, Decoder};
+
+use crate::{
+    arena::Arena,
+    constant::Integer,
+    data::PlutusData,
+    hash,
+    machine::{ExBudget, OwnedData, PlutusVersion},
+    script_context::{
+        v1::Value,
+        v2::OutputDatum,
+        v3::{
+            Constitution, Credential, DRep, Delegatee, GovernanceAction, GovernanceActionId,
+            ProposalProcedure, ProtocolVersion, Rational, TxCert, TxId, TxOutRef, Vote, Voter,
+        },
+        AssocMap,
+    },
+};
+
+use super::RedeemerTag;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Quantities by policy and asset name.
+pub(super) type MultiAsset = Vec<(Vec<u8>, Vec<(Vec<u8>, Integer)>)>;
+
+/// Votes by voter and governance action.
+pub(super) type Votes = Vec<(Voter, Vec<(GovernanceActionId, Vote)>)>;
+
+pub(super) struct Transaction {
+    pub id: Vec<u8>,
+    pub inputs: Vec<TxOutRef>,
+    pub reference_inputs: Vec<TxOutRef>,
+    pub outputs: Vec<Output>,
+    pub fee: Integer,
+    pub ttl: Option<u64>,
+    pub validity_start: Option<u64>,
+    pub certificates: Vec<TxCert>,
+    pub withdrawals: Vec<(Credential, Integer)>,
+    pub mint: MultiAsset,
+    pub required_signers: Vec<Vec<u8>>,
+    pub votes: Votes,
+    pub proposals: Vec<ProposalProcedure>,
+    pub treasury: Option<Integer>,
+    pub donation: Option<Integer>,
+    pub scripts: Vec<Script>,
+    pub data: Vec<(Vec<u8>, OwnedData)>,
+    pub redeemers: Vec<Redeemer>,
+}
+
+pub(super) struct Output {
+    pub address: Vec<u8>,
+    pub value: Value,
+    pub datum: OutputDatum,
+    pub script_ref: Option<Script>,
+}
+
+/// A script as the ledger stores it. Plutus scripts are the CBOR byte
+/// string wrapping their flat encoding.
+#[derive(Debug, Clone)]
+pub(super) struct Script {
+    pub version: Option<PlutusVersion>,
+    pub bytes: Vec<u8>,
+}
+
+impl Script {
+    pub fn hash(&self) -> Vec<u8> {
+        match self.version {
+            Some(plutus_version) => hash::script_hash_cbor(plutus_version, &self.bytes).to_vec(),
+            None => {
+                let mut preimage = vec![0];
+
+                preimage.extend_from_slice(&self.bytes);
+
+                hash::blake2b_224(&preimage).to_vec()
+            }
+        }
+    }
+}
+
+pub(super) struct Redeemer {
+    pub tag: RedeemerTag,
+    pub index: u32,
+    pub data: OwnedData,
+    pub ex_units: ExBudget,
+}
+
+pub(super) fn transaction(bytes: &[u8]) -> Result<Transaction> {
+    let mut d = Decoder::new(bytes);
+
+    definite(d.array()?, 4, "transaction")?;
+
+    let start = d.position();
+
+    d.skip()?;
+
+    let body = &bytes[start..d.position()];
+
+    let mut tx = Transaction {
+        id: hash::blake2b_256(body).to_vec(),
+        inputs: Vec::new(),
+        reference_inputs: Vec::new(),
+        outputs: Vec::new(),
+        fee: Integer::from(0),
+        ttl: None,
+        validity_start: None,
+        certificates: Vec::new(),
+        withdrawals: Vec::new(),
+        mint: Vec::new(),
+        required_signers: Vec::new(),
+        votes: Vec::new(),
+        proposals: Vec::new(),
+        treasury: None,
+        donation: None,
+        scripts: Vec::new(),
+        data: Vec::new(),
+        redeemers: Vec::new(),
+    };
+
+    witnesses(&mut d, bytes, &mut tx)?;
+
+    transaction_body(&mut Decoder::new(body), &mut tx)?;
+
+    Ok(tx)
+}
+
+fn transaction_body(d: &mut Decoder<'_>, tx: &mut Transaction) -> Result<()> {
+    let len = d.map()?;
+
+    entries(d, len, |d| {
+        match d.u64()? {
+            0 => tx.inputs = sorted_inputs(d)?,
+            1 => tx.outputs = array(d, output)?,
+            2 => tx.fee = Integer::from(d.u64()?),
+            3 => tx.ttl = Some(d.u64()?),
+            4 => tx.certificates = set(d, certificate)?,
+            5 => {
+                let len = d.map()?;
+
+                let mut withdrawals = Vec::new();
+
+                entries(d, len, |d| {
+                    withdrawals.push((reward_account(d)?, Integer::from(d.u64()?)));
+
+                    Ok(())
+                })?;
+
+                withdrawals.sort_by(|(a, _), (b, _)| credential_order(a, b));
+
+                tx.withdrawals = withdrawals;
+            }
+            8 => tx.validity_start = Some(d.u64()?),
+            9 => tx.mint = multi_asset(d)?,
+            14 => {
+                let mut signers = set(d, |d| Ok(d.bytes()?.to_vec()))?;
+
+                signers.sort();
+
+                tx.required_signers = signers;
+            }
+            18 => tx.reference_inputs = sorted_inputs(d)?,
+            19 => tx.votes = voting_procedures(d)?,
+            20 => tx.proposals = set(d, proposal_procedure)?,
+            21 => tx.treasury = Some(Integer::from(d.u64()?)),
+            22 => tx.donation = Some(Integer::from(d.u64()?)),
+            _ => d.skip()?,
+        }
+
+        Ok(())
+    })
+}
+
+fn witnesses(d: &mut Decoder<'_>, bytes: &[u8], tx: &mut Transaction) -> Result<()> {
+    let len = d.map()?;
+
+    entries(d, len, |d| {
+        match d.u64()? {
+            3 => plutus_scripts(d, PlutusVersion::V1, &mut tx.scripts)?,
+            4 => {
+                let mut data = set(d, |d| {
+                    let start = d.position();
+
+                    d.skip()?;
+
+                    let raw = &bytes[start..d.position()];
+
+                    Ok((hash::blake2b_256(raw).to_vec(), plutus_data(raw)?))
+                })?;
+
+                data.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                tx.data = data;
+            }
+            5 => tx.redeemers = redeemers(d, bytes)?,
+            6 => plutus_scripts(d, PlutusVersion::V2, &mut tx.scripts)?,
+            7 => plutus_scripts(d, PlutusVersion::V3, &mut tx.scripts)?,
+            _ => d.skip()?,
+        }
+
+        Ok(())
+    })
+}
+
+fn plutus_scripts(
+    d: &mut Decoder<'_>,
+    version: PlutusVersion,
+    scripts: &mut Vec<Script>,
+) -> Result<()> {
+    let found = set(d, |d| {
+        Ok(Script {
+            version: Some(version),
+            bytes: d.bytes()?.to_vec(),
+        })
+    })?;
+
+    scripts.extend(found);
+
+    Ok(())
+}
+
+fn redeemers(d: &mut Decoder<'_>, bytes: &[u8]) -> Result<Vec<Redeemer>> {
+    let mut redeemers = Vec::new();
+
+    let data = |d: &mut Decoder<'_>| -> Result<OwnedData> {
+        let start = d.position();
+
+        d.skip()?;
+
+        plutus_data(&bytes[start..d.position()])
+    };
+
+    match d.datatype()? {
+        Type::Map | Type::MapIndef => {
+            let len = d.map()?;
+
+            entries(d, len, |d| {
+                definite(d.array()?, 2, "redeemer key")?;
+
+                let tag = redeemer_tag(d.u64()?)?;
+                let index = d.u32()?;
+
+                definite(d.array()?, 2, "redeemer value")?;
+
+                redeemers.push(Redeemer {
+                    tag,
+                    index,
+                    data: data(d)?,
+                    ex_units: ex_units(d)?,
+                });
+
+                Ok(())
+            })?;
+        }
+        _ => {
+            redeemers = array(d, |d| {
+                definite(d.array()?, 4, "redeemer")?;
+
+                Ok(Redeemer {
+                    tag: redeemer_tag(d.u64()?)?,
+                    index: d.u32()?,
+                    data: data(d)?,
+                    ex_units: ex_units(d)?,
+                })
+            })?;
+        }
+    }
+
+    redeemers.sort_by_key(|redeemer| (redeemer.tag, redeemer.index));
+
+    Ok(redeemers)
+}
+
+fn redeemer_tag(tag: u64) -> Result<RedeemerTag> {
+    match tag {
+        0 => Ok(RedeemerTag::Spend),
+        1 => Ok(RedeemerTag::Mint),
+        2 => Ok(RedeemerTag::Cert),
+        3 => Ok(RedeemerTag::Reward),
+        4 => Ok(RedeemerTag::Vote),
+        5 => Ok(RedeemerTag::Propose),
+        _ => Err(Error::message(format!("unknown redeemer tag {tag}"))),
+    }
+}
+
+fn ex_units(d: &mut Decoder<'_>) -> Result<ExBudget> {
+    definite(d.array()?, 2, "ex units")?;
+
+    let mem = d.u64()?;
+    let cpu = d.u64()?;
+
+    Ok(ExBudget::new(
+        i64::try_from(mem).map_err(Error::message)?,
+        i64::try_from(cpu).map_err(Error::message)?,
+    ))
+}
+
+fn plutus_data(bytes: &[u8]) -> Result<OwnedData> {
+    let arena = Arena::new();
+
+    PlutusData::from_cbor_strict(&arena, bytes)
+        .map(OwnedData::from)
+        .map_err(Error::message)
+}
+
+pub(super) fn input(d: &mut Decoder<'_>) -> Result<TxOutRef> {
+    definite(d.array()?, 2, "transaction input")?;
+
+    Ok(TxOutRef {
+        id: TxId(d.bytes()?.to_vec()),
+        index: Integer::from(d.u64()?),
+    })
+}
+
+fn sorted_inputs(d: &mut Decoder<'_>) -> Result<Vec<TxOutRef>> {
+    let mut inputs = set(d, input)?;
+
+    inputs.sort_by(|a, b| (&a.id.0, &a.index).cmp(&(&b.id.0, &b.index)));
+
+    Ok(inputs)
+}
+
+pub(super) fn output(d: &mut Decoder<'_>) -> Result<Output> {
+    match d.datatype()? {
+        Type::Array | Type::ArrayIndef => {
+            let len = d.array()?;
+
+            let address = d.bytes()?.to_vec();
+            let value = value(d)?;
+
+            let datum = match len {
+                Some(3) => OutputDatum::Hash(d.bytes()?.to_vec()),
+                Some(2) => OutputDatum::None,
+                _ => return Err(Error::message("malformed legacy transaction output")),
+            };
+
+            Ok(Output {
+                address,
+                value,
+                datum,
+                script_ref: None,
+            })
+        }
+        _ => {
+            let len = d.map()?;
+
+            let mut address = None;
+            let mut amount = None;
+            let mut datum = OutputDatum::None;
+            let mut script_ref = None;
+
+            entries(d, len, |d| {
+                match d.u64()? {
+                    0 => address = Some(d.bytes()?.to_vec()),
+                    1 => amount = Some(value(d)?),
+                    2 => {
+                        definite(d.array()?, 2, "datum option")?;
+
+                        datum = match d.u64()? {
+                            0 => OutputDatum::Hash(d.bytes()?.to_vec()),
+                            1 => OutputDatum::Inline(plutus_data(embedded(d)?)?),
+                            tag => {
+                                return Err(Error::message(format!("unknown datum option {tag}")))
+                            }
+                        };
+                    }
+                    3 => {
+                        let raw = embedded(d)?;
+
+                        let mut inner = Decoder::new(raw);
+
+                        definite(inner.array()?, 2, "script reference")?;
+
+                        let version = match inner.u64()? {
+                            0 => None,
+                            1 => Some(PlutusVersion::V1),
+                            2 => Some(PlutusVersion::V2),
+                            3 => Some(PlutusVersion::V3),
+                            tag => return Err(Error::message(format!("unknown script tag {tag}"))),
+                        };
+
+                        let bytes = match version {
+                            // Native scripts are hashed over their CBOR encoding.
+                            None => {
+                                let start = inner.position();
+
+                                inner.skip()?;
+
+                                raw[start..inner.position()].to_vec()
+                            }
+                            Some(_) => inner.bytes()?.to_vec(),
+                        };
+
+                        script_ref = Some(Script { version, bytes });
+                    }
+                    _ => d.skip()?,
+                }
+
+                Ok(())
+            })?;
+
+            Ok(Output {
+                address: address.ok_or_else(|| Error::message("output without address"))?,
+                value: amount.ok_or_else(|| Error::message("output without value"))?,
+                datum,
+                script_ref,
+            })
+        }
+    }
+}
+
+/// The bytes of a CBOR item embedded with tag 24.
+fn embedded<'b>(d: &mut Decoder<'b>) -> Result<&'b [u8]> {
+    if d.tag()?.as_u64() != 24 {
+        return Err(Error::message("expected embedded CBOR"));
+    }
+
+    d.bytes()
+}
+
+fn value(d: &mut Decoder<'_>) -> Result<Value> {
+    let (coin, assets) = match d.datatype()? {
+        Type::Array | Type::ArrayIndef => {
+            definite(d.array()?, 2, "value")?;
+
+            (d.u64()?, multi_asset(d)?)
+        }
+        _ => (d.u64()?, Vec::new()),
+    };
+
+    let mut value = Value::lovelace(coin);
+
+    value.0 .0.extend(
+        assets
+            .into_iter()
+            .map(|(policy, tokens)| (policy, AssocMap(tokens))),
+    );
+
+    Ok(value)
+}
+
+fn multi_asset(d: &mut Decoder<'_>) -> Result<MultiAsset> {
+    let len = d.map()?;
+
+    let mut policies = Vec::new();
+
+    entries(d, len, |d| {
+        let policy = d.bytes()?.to_vec();
+
+        let len = d.map()?;
+
+        let mut tokens = Vec::new();
+
+        entries(d, len, |d| {
+            let name = d.bytes()?.to_vec();
+            let quantity: i128 = d.int()?.into();
+
+            tokens.push((name, Integer::from(quantity)));
+
+            Ok(())
+        })?;
+
+        tokens.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        policies.push((policy, tokens));
+
+        Ok(())
+    })?;
+
+    policies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(policies)
+}
+
+fn credential(d: &mut Decoder<'_>) -> Result<Credential> {
+    definite(d.array()?, 2, "credential")?;
+
+    match d.u64()? {
+        0 => Ok(Credential::PubKey(d.bytes()?.to_vec())),
+        1 => Ok(Credential::Script(d.bytes()?.to_vec())),
+        tag => Err(Error::message(format!("unknown credential tag {tag}"))),
+    }
+}
+
+/// The ledger orders script credentials before key credentials.
+pub(super) fn credential_order(a: &Credential, b: &Credential) -> Ordering {
+    let key = |credential: &Credential| match credential {
+        Credential::Script(hash) => (0, hash.clone()),
+        Credential::PubKey(hash) => (1, hash.clone()),
+    };
+
+    key(a).cmp(&key(b))
+}
+
+fn reward_account(d: &mut Decoder<'_>) -> Result<Credential> {
+    let bytes = d.bytes()?;
+
+    match bytes.split_first() {
+        Some((header, hash)) if hash.len() == 28 => match header >> 4 {
+            0b1110 => Ok(Credential::PubKey(hash.to_vec())),
+            0b1111 => Ok(Credential::Script(hash.to_vec())),
+            _ => Err(Error::message("malformed reward account")),
+        },
+        _ => Err(Error::message("malformed reward account")),
+    }
+}
+
+fn drep(d: &mut Decoder<'_>) -> Result<DRep> {
+    let len = d.array()?;
+
+    let drep = match d.u64()? {
+        0 => DRep::Credential(Credential::PubKey(d.bytes()?.to_vec())),
+        1 => DRep::Credential(Credential::Script(d.bytes()?.to_vec())),
+        2 => DRep::AlwaysAbstain,
+        3 => DRep::AlwaysNoConfidence,
+        tag => return Err(Error::message(format!("unknown drep tag {tag}"))),
+    };
+
+    end(d, len)?;
+
+    Ok(drep)
+}
+
+fn certificate(d: &mut Decoder<'_>) -> Result<TxCert> {
+    let len = d.array()?;
+
+    let coin = |d: &mut Decoder<'_>| -> Result<Integer> { Ok(Integer::from(d.u64()?)) };
+    let pool = |d: &mut Decoder<'_>| -> Result<Vec<u8>> { Ok(d.bytes()?.to_vec()) };
+
+    let certificate = match d.u64()? {
+        0 => TxCert::RegStaking(credential(d)?, None),
+        1 => TxCert::UnRegStaking(credential(d)?, None),
+        2 => TxCert::DelegStaking(credential(d)?, Delegatee::Stake(pool(d)?)),
+        3 => {
+            let operator = pool(d)?;
+            let vrf = pool(d)?;
+
+            // Pledge, cost, margin, reward account, owners, relays and
+            // metadata.
+            for _ in 0..7 {
+                d.skip()?;
+            }
+
+            TxCert::PoolRegister {
+                pool: operator,
+                vrf,
+            }
+        }
+        4 => TxCert::PoolRetire {
+            pool: pool(d)?,
+            epoch: Integer::from(d.u64()?),
+        },
+        7 => TxCert::RegStaking(credential(d)?, Some(coin(d)?)),
+        8 => TxCert::UnRegStaking(credential(d)?, Some(coin(d)?)),
+        9 => TxCert::DelegStaking(credential(d)?, Delegatee::Vote(drep(d)?)),
+        10 => {
+            let credential = credential(d)?;
+            let pool = pool(d)?;
+
+            TxCert::DelegStaking(credential, Delegatee::StakeVote(pool, drep(d)?))
+        }
+        11 => {
+            let credential = credential(d)?;
+            let pool = pool(d)?;
+
+            TxCert::RegDeleg(credential, Delegatee::Stake(pool), coin(d)?)
+        }
+        12 => {
+            let credential = credential(d)?;
+            let drep = drep(d)?;
+
+            TxCert::RegDeleg(credential, Delegatee::Vote(drep), coin(d)?)
+        }
+        13 => {
+            let credential = credential(d)?;
+            let pool = pool(d)?;
+            let drep = drep(d)?;
+
+            TxCert::RegDeleg(credential, Delegatee::StakeVote(pool, drep), coin(d)?)
+        }
+        14 => TxCert::AuthHotCommittee {
+            cold: credential(d)?,
+            hot: credential(d)?,
+        },
+        15 => {
+            let cold = credential(d)?;
+
+            d.skip()?;
+
+            TxCert::ResignColdCommittee(cold)
+        }
+        16 => {
+            let drep = credential(d)?;
+            let deposit = coin(d)?;
+
+            d.skip()?;
+
+            TxCert::RegDRep(drep, deposit)
+        }
+        17 => TxCert::UnRegDRep(credential(d)?, coin(d)?),
+        18 => {
+            let drep = credential(d)?;
+
+            d.skip()?;
+
+            TxCert::UpdateDRep(drep)
+        }
+        tag => return Err(Error::message(format!("unknown certificate tag {tag}"))),
+    };
+
+    end(d, len)?;
+
+    Ok(certificate)
+}
+
+fn voter(d: &mut Decoder<'_>) -> Result<Voter> {
+    definite(d.array()?, 2, "voter")?;
+
+    let tag = d.u64()?;
+    let hash = d.bytes()?.to_vec();
+
+    match tag {
+        0 => Ok(Voter::Committee(Credential::PubKey(hash))),
+        1 => Ok(Voter::Committee(Credential::Script(hash))),
+        2 => Ok(Voter::DRep(Credential::PubKey(hash))),
+        3 => Ok(Voter::DRep(Credential::Script(hash))),
+        4 => Ok(Voter::StakePool(hash)),
+        _ => Err(Error::message(format!("unknown voter tag {tag}"))),
+    }
+}
+
+pub(super) fn voter_order(a: &Voter, b: &Voter) -> Ordering {
+    let rank = |voter: &Voter| match voter {
+        Voter::Committee(_) => 0,
+        Voter::DRep(_) => 1,
+        Voter::StakePool(_) => 2,
+    };
+
+    match (a, b) {
+        (Voter::Committee(a), Voter::Committee(b)) | (Voter::DRep(a), Voter::DRep(b)) => {
+            credential_order(a, b)
+        }
+        (Voter::StakePool(a), Voter::StakePool(b)) => a.cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn governance_action_id(d: &mut Decoder<'_>) -> Result<GovernanceActionId> {
+    definite(d.array()?, 2, "governance action id")?;
+
+    Ok(GovernanceActionId {
+        tx_id: TxId(d.bytes()?.to_vec()),
+        index: Integer::from(d.u64()?),
+    })
+}
+
+fn voting_procedures(d: &mut Decoder<'_>) -> Result<Votes> {
+    let len = d.map()?;
+
+    let mut votes = Vec::new();
+
+    entries(d, len, |d| {
+        let voter = voter(d)?;
+
+        let len = d.map()?;
+
+        let mut procedures = Vec::new();
+
+        entries(d, len, |d| {
+            let id = governance_action_id(d)?;
+
+            definite(d.array()?, 2, "voting procedure")?;
+
+            let vote = match d.u64()? {
+                0 => Vote::No,
+                1 => Vote::Yes,
+                2 => Vote::Abstain,
+                tag => return Err(Error::message(format!("unknown vote {tag}"))),
+            };
+
+            // The anchor.
+            d.skip()?;
+
+            procedures.push((id, vote));
+
+            Ok(())
+        })?;
+
+        procedures.sort_by(|(a, _), (b, _)| (&a.tx_id.0, &a.index).cmp(&(&b.tx_id.0, &b.index)));
+
+        votes.push((voter, procedures));
+
+        Ok(())
+    })?;
+
+    votes.sort_by(|(a, _), (b, _)| voter_order(a, b));
+
+    Ok(votes)
+}
+
+fn proposal_procedure(d: &mut Decoder<'_>) -> Result<ProposalProcedure> {
+    definite(d.array()?, 4, "proposal procedure")?;
+
+    let deposit = Integer::from(d.u64()?);
+    let return_address = reward_account(d)?;
+    let action = governance_action(d)?;
+
+    // The anchor.
+    d.skip()?;
+
+    Ok(ProposalProcedure {
+        deposit,
+        return_address,
+        action,
+    })
+}
+
+fn governance_action(d: &mut Decoder<'_>) -> Result<GovernanceAction> {
+    let len = d.array()?;
+
+    let previous = |d: &mut Decoder<'_>| nullable(d, governance_action_id);
+    let guardrail = |d: &mut Decoder<'_>| nullable(d, |d| Ok(d.bytes()?.to_vec()));
+
+    let action = match d.u64()? {
+        0 => GovernanceAction::ParameterChange {
+            previous: previous(d)?,
+            parameters: generic_data(d)?,
+            guardrail: guardrail(d)?,
+        },
+        1 => {
+            let previous = previous(d)?;
+
+            definite(d.array()?, 2, "protocol version")?;
+
+            GovernanceAction::HardForkInitiation {
+                previous,
+                version: ProtocolVersion {
+                    major: Integer::from(d.u64()?),
+                    minor: Integer::from(d.u64()?),
+                },
+            }
+        }
+        2 => {
+            let len = d.map()?;
+
+            let mut withdrawals = Vec::new();
+
+            entries(d, len, |d| {
+                withdrawals.push((reward_account(d)?, Integer::from(d.u64()?)));
+
+                Ok(())
+            })?;
+
+            withdrawals.sort_by(|(a, _), (b, _)| credential_order(a, b));
+
+            GovernanceAction::TreasuryWithdrawals {
+                withdrawals: AssocMap(withdrawals),
+                guardrail: guardrail(d)?,
+            }
+        }
+        3 => GovernanceAction::NoConfidence {
+            previous: previous(d)?,
+        },
+        4 => {
+            let previous = previous(d)?;
+
+            let mut removed = set(d, credential)?;
+
+            removed.sort_by(credential_order);
+
+            let len = d.map()?;
+
+            let mut added = Vec::new();
+
+            entries(d, len, |d| {
+                added.push((credential(d)?, Integer::from(d.u64()?)));
+
+                Ok(())
+            })?;
+
+            added.sort_by(|(a, _), (b, _)| credential_order(a, b));
+
+            if d.tag()?.as_u64() != 30 {
+                return Err(Error::message("expected a unit interval"));
+            }
+
+            definite(d.array()?, 2, "unit interval")?;
+
+            GovernanceAction::UpdateCommittee {
+                previous,
+                removed,
+                added: AssocMap(added),
+                quorum: Rational {
+                    numerator: Integer::from(d.u64()?),
+                    denominator: Integer::from(d.u64()?),
+                },
+            }
+        }
+        5 => {
+            let previous = previous(d)?;
+
+            definite(d.array()?, 2, "constitution")?;
+
+            // The anchor.
+            d.skip()?;
+
+            GovernanceAction::NewConstitution {
+                previous,
+                constitution: Constitution {
+                    guardrail: guardrail(d)?,
+                },
+            }
+        }
+        6 => GovernanceAction::InfoAction,
+        tag => return Err(Error::message(format!("unknown governance action {tag}"))),
+    };
+
+    end(d, len)?;
+
+    Ok(action)
+}
+
+/// Protocol parameter updates, as data: integers stay integers, rationals
+/// become two-element lists and everything else keeps its structure.
+fn generic_data(d: &mut Decoder<'_>) -> Result<OwnedData> {
+    match d.datatype()? {
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64
+        | Type::Int => {
+            let i: i128 = d.int()?.into();
+
+            Ok(OwnedData::Integer(Integer::from(i)))
+        }
+        Type::Bytes => Ok(OwnedData::ByteString(d.bytes()?.to_vec())),
+        Type::Array | Type::ArrayIndef => Ok(OwnedData::List(array(d, generic_data)?)),
+        Type::Map | Type::MapIndef => {
+            let len = d.map()?;
+
+            let mut items = Vec::new();
+
+            entries(d, len, |d| {
+                items.push((generic_data(d)?, generic_data(d)?));
+
+                Ok(())
+            })?;
+
+            Ok(OwnedData::Map(items))
+        }
+        Type::Tag => {
+            d.tag()?;
+
+            generic_data(d)
+        }
+        other => Err(Error::message(format!(
+            "unexpected {other} in protocol parameters"
+        ))),
+    }
+}
+
+fn nullable<T>(
+    d: &mut Decoder<'_>,
+    item: impl FnOnce(&mut Decoder<'_>) -> Result<T>,
+) -> Result<Option<T>> {
+    if d.datatype()? == Type::Null {
+        d.skip()?;
+
+        Ok(None)
+    } else {
+        item(d).map(Some)
+    }
+}
+
+/// A set, optionally tagged 258.
+fn set<T>(d: &mut Decoder<'_>, item: impl FnMut(&mut Decoder<'_>) -> Result<T>) -> Result<Vec<T>> {
+    if d.datatype()? == Type::Tag {
+        d.tag()?;
+    }
+
+    array(d, item)
+}
+
+fn array<T>(
+    d: &mut Decoder<'_>,
+    mut item: impl FnMut(&mut Decoder<'_>) -> Result<T>,
+) -> Result<Vec<T>> {
+    let len = d.array()?;
+
+    let mut items = Vec::new();
+
+    match len {
+        Some(len) => {
+            for _ in 0..len {
+                items.push(item(d)?);
+            }
+        }
+        None => {
+            while d.datatype()? != Type::Break {
+                items.push(item(d)?);
+            }
+
+            d.skip()?;
+        }
+    }
+
+    Ok(items)
+}
+
+fn entries(
+    d: &mut Decoder<'_>,
+    len: Option<u64>,
+    mut entry: impl FnMut(&mut Decoder<'_>) -> Result<()>,
+) -> Result<()> {
+    match len {
+        Some(len) => {
+            for _ in 0..len {
+                entry(d)?;
+            }
+        }
+        None => {
+            while d.datatype()? != Type::Break {
+                entry(d)?;
+            }
+
+            d.skip()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn definite(len: Option<u64>, expected: u64, what: &str) -> Result<()> {
+    match len {
+        Some(len) if len == expected => Ok(()),
+        _ => Err(Error::message(format!(
+            "expected {what} of {expected} elements"
+        ))),
+    }
+}
+
+/// Skip the break of an indefinite array.
+fn end(d: &mut Decoder<'_>, len: Option<u64>) -> Result<()> {
+    if len.is_none() {
+        if d.datatype()? != Type::Break {
+            return Err(Error::message("unexpected trailing element"));
+        }
+
+        d.skip()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,630 @@
+//! Phase-2 validation of Conway-era transactions: every redeemer is matched
+//! to its script, applied to the script context the ledger would build and
+//! evaluated within its declared execution units.
+
+mod context;
+mod decode;
+
+use crate::{
+    arena::Arena,
+    binder::DeBruijn,
+    constant::Constant,
+    flat,
+    machine::{CostModelError, ExBudget, OwnedData, PlutusVersion},
+    script_context::IsData,
+    term::Term,
+};
+
+use context::{Purpose, TxContext};
+
+/// The ledger's mapping from slots to POSIX time in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotConfig {
+    pub zero_time: u64,
+    pub zero_slot: u64,
+    pub slot_length: u64,
+}
+
+impl Default for SlotConfig {
+    /// Mainnet, from the start of the Shelley era.
+    fn default() -> Self {
+        SlotConfig {
+            zero_time: 1_596_059_091_000,
+            zero_slot: 4_492_800,
+            slot_length: 1_000,
+        }
+    }
+}
+
+/// Ledger parameters for [`eval_phase_two`].
+///
+/// Versions without an explicit cost model are evaluated with their default
+/// one.
+#[derive(Debug, Clone)]
+pub struct PhaseTwoConfig<'c> {
+    pub protocol_version: u32,
+    pub cost_models: Vec<(PlutusVersion, &'c [i64])>,
+    pub slot_config: SlotConfig,
+}
+
+impl<'c> PhaseTwoConfig<'c> {
+    pub fn new(protocol_version: u32) -> Self {
+        PhaseTwoConfig {
+            protocol_version,
+            cost_models: Vec::new(),
+            slot_config: SlotConfig::default(),
+        }
+    }
+
+    pub fn with_cost_model(mut self, plutus_version: PlutusVersion, cost_model: &'c [i64]) -> Self {
+        self.cost_models
+            .retain(|(version, _)| *version != plutus_version);
+
+        self.cost_models.push((plutus_version, cost_model));
+
+        self
+    }
+
+    pub fn with_slot_config(self, slot_config: SlotConfig) -> Self {
+        PhaseTwoConfig {
+            slot_config,
+            ..self
+        }
+    }
+
+    fn cost_model(&self, plutus_version: PlutusVersion) -> Option<&'c [i64]> {
+        self.cost_models
+            .iter()
+            .find(|(version, _)| *version == plutus_version)
+            .map(|(_, cost_model)| *cost_model)
+    }
+}
+
+/// A resolved input: the CBOR of a transaction input `[id, index]` and of
+/// the output it points to.
+#[derive(Debug, Clone, Copy)]
+pub struct Utxo<'b> {
+    pub input: &'b [u8],
+    pub output: &'b [u8],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RedeemerTag {
+    Spend,
+    Mint,
+    Cert,
+    Reward,
+    Vote,
+    Propose,
+}
+
+/// The outcome of running the script of one redeemer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedeemerResult {
+    pub tag: RedeemerTag,
+    pub index: u32,
+    pub plutus_version: PlutusVersion,
+    /// The execution units declared by the redeemer.
+    pub budget: ExBudget,
+    pub consumed: ExBudget,
+    pub logs: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl RedeemerResult {
+    pub fn success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TxError {
+    #[error("Malformed transaction: {0}")]
+    Cbor(String),
+    #[error("Malformed UTxO: {0}")]
+    Utxo(String),
+    #[error("Input {0} is not among the resolved UTxOs")]
+    MissingInput(String),
+    #[error("Redeemer {tag:?} {index} points past the end of the transaction")]
+    UnknownTarget { tag: RedeemerTag, index: u32 },
+    #[error("Redeemer {tag:?} {index} is not for a Plutus script")]
+    NotAScript { tag: RedeemerTag, index: u32 },
+    #[error("No script with hash {0} is provided")]
+    MissingScript(String),
+    #[error("No datum with hash {0} is provided")]
+    MissingDatum(String),
+    #[error("Redeemer {tag:?} {index} spends an output without a datum")]
+    NoDatum { tag: RedeemerTag, index: u32 },
+    #[error("Script {hash} cannot be decoded: {reason}")]
+    MalformedScript { hash: String, reason: String },
+    #[error("Byron addresses cannot be given to scripts")]
+    ByronAddress,
+    #[error("Malformed address")]
+    MalformedAddress,
+    #[error("Transactions with {feature} cannot be given to {version:?} scripts")]
+    Unsupported {
+        version: PlutusVersion,
+        feature: &'static str,
+    },
+    #[error(transparent)]
+    CostModel(#[from] CostModelError),
+}
+
+/// Run every script of a transaction, in the order of its redeemers.
+///
+/// `utxos` must resolve every spent and reference input. Scripts come from
+/// the witness set or from reference scripts of those inputs. A script
+/// failing or exceeding its execution units is reported in its result, the
+/// error is for transactions that cannot be evaluated at all.
+pub fn eval_phase_two(
+    tx: &[u8],
+    utxos: &[Utxo<'_>],
+    config: &PhaseTwoConfig<'_>,
+) -> Result<Vec<RedeemerResult>, TxError> {
+    let tx = decode::transaction(tx).map_err(|error| TxError::Cbor(error.to_string()))?;
+
+    let utxos = utxos
+        .iter()
+        .map(|utxo| {
+            let input = decode::input(&mut minicbor::Decoder::new(utxo.input))?;
+            let output = decode::output(&mut minicbor::Decoder::new(utxo.output))?;
+
+            Ok((input, output))
+        })
+        .collect::<Result<Vec<_>, minicbor::decode::Error>>()
+        .map_err(|error| TxError::Utxo(error.to_string()))?;
+
+    let context = TxContext::new(&tx, &utxos, config.slot_config)?;
+
+    let scripts: Vec<_> = tx
+        .scripts
+        .iter()
+        .chain(
+            context
+                .resolved()
+                .filter_map(|output| output.script_ref.as_ref()),
+        )
+        .collect();
+
+    context
+        .purposes()
+        .iter()
+        .zip(&tx.redeemers)
+        .enumerate()
+        .map(|(i, (purpose, redeemer))| {
+            let not_a_script = TxError::NotAScript {
+                tag: redeemer.tag,
+                index: redeemer.index,
+            };
+
+            let hash = purpose.script_hash().ok_or(not_a_script.clone())?;
+
+            let script = scripts
+                .iter()
+                .find(|script| script.hash() == hash)
+                .ok_or_else(|| TxError::MissingScript(hex::encode(hash)))?;
+
+            let plutus_version = script.version.ok_or(not_a_script)?;
+
+            let arguments = match plutus_version {
+                PlutusVersion::V1 | PlutusVersion::V2 => {
+                    let script_context = if plutus_version == PlutusVersion::V1 {
+                        context.v1(i)?.to_data()
+                    } else {
+                        context.v2(i)?.to_data()
+                    };
+
+                    match purpose {
+                        Purpose::Spend(..) => {
+                            let datum = context.datum(purpose)?.ok_or(TxError::NoDatum {
+                                tag: redeemer.tag,
+                                index: redeemer.index,
+                            })?;
+
+                            vec![datum, redeemer.data.clone(), script_context]
+                        }
+                        _ => vec![redeemer.data.clone(), script_context],
+                    }
+                }
+                PlutusVersion::V3 => vec![context.v3(i)?.to_data()],
+            };
+
+            eval(
+                script,
+                plutus_version,
+                &arguments,
+                redeemer.ex_units,
+                config,
+            )
+            .map(|(consumed, logs, error)| RedeemerResult {
+                tag: redeemer.tag,
+                index: redeemer.index,
+                plutus_version,
+                budget: redeemer.ex_units,
+                consumed,
+                logs,
+                error,
+            })
+        })
+        .collect()
+}
+
+type Outcome = (ExBudget, Vec<String>, Option<String>);
+
+fn eval(
+    script: &decode::Script,
+    plutus_version: PlutusVersion,
+    arguments: &[OwnedData],
+    budget: ExBudget,
+    config: &PhaseTwoConfig<'_>,
+) -> Result<Outcome, TxError> {
+    let malformed = |reason: String| TxError::MalformedScript {
+        hash: hex::encode(script.hash()),
+        reason,
+    };
+
+    let flat = minicbor::Decoder::new(&script.bytes)
+        .bytes()
+        .map_err(|error| malformed(error.to_string()))?;
+
+    let arena = Arena::new();
+
+    let program =
+        flat::decode_strict::<DeBruijn>(&arena, flat, plutus_version, config.protocol_version)
+            .map_err(|error| malformed(error.to_string()))?;
+
+    let program = arguments.iter().fold(program, |program, argument| {
+        program.apply(&arena, Term::data(&arena, argument.to_data(&arena)))
+    });
+
+    let result = match config.cost_model(plutus_version) {
+        Some(cost_model) => program.eval_with_params(&arena, plutus_version, cost_model, budget)?,
+        None => program.eval_version_budget(&arena, plutus_version, budget),
+    };
+
+    // From V3 on a script succeeds only by returning unit.
+    let error = match result.term {
+        Err(error) => Some(error.to_string()),
+        Ok(Term::Constant(Constant::Unit)) => None,
+        Ok(_) if plutus_version == PlutusVersion::V3 => {
+            Some("Plutus V3 script did not return unit".to_string())
+        }
+        Ok(_) => None,
+    };
+
+    Ok((result.info.consumed_budget, result.info.logs, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use minicbor::Encoder;
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        arena::Arena,
+        constant::Integer,
+        flat, hash,
+        machine::{ExBudget, OwnedData, PlutusVersion},
+        script_context::{
+            v1::Extended,
+            v3::{ScriptInfo, TxId, TxOutRef},
+        },
+        syn::parse_program,
+    };
+
+    use super::{
+        context::TxContext, decode, eval_phase_two, PhaseTwoConfig, RedeemerTag, SlotConfig,
+        TxError, Utxo,
+    };
+
+    const TX_ID: [u8; 32] = [7; 32];
+
+    /// Fails unless the redeemer of a V3 context is 42.
+    const V3_REDEEMER_IS_42: &str = r#"(program 1.1.0
+        (lam ctx
+          (force
+            [(force (builtin ifThenElse))
+              [(builtin equalsInteger)
+                (con integer 42)
+                [(builtin unIData)
+                  [(force (builtin headList))
+                    [(force (builtin tailList))
+                      [(force (force (builtin sndPair))) [(builtin unConstrData) ctx]]]]]]
+              (delay (con unit ()))
+              (delay (error))])))"#;
+
+    /// Fails unless the datum equals the redeemer.
+    const V2_DATUM_IS_REDEEMER: &str = r#"(program 1.0.0
+        (lam datum (lam redeemer (lam ctx
+          (force
+            [(force (builtin ifThenElse))
+              [(builtin equalsData) datum redeemer]
+              (delay (con unit ()))
+              (delay (error))])))))"#;
+
+    /// The CBOR byte string wrapping the flat encoding of a program.
+    fn script(src: &str) -> Vec<u8> {
+        let arena = Arena::new();
+
+        let program = parse_program(&arena, src).into_result().unwrap();
+
+        hash::wrap_cbor(&flat::encode(program).unwrap())
+    }
+
+    fn script_hash(version: u8, script: &[u8]) -> Vec<u8> {
+        let version = match version {
+            1 => PlutusVersion::V1,
+            2 => PlutusVersion::V2,
+            _ => PlutusVersion::V3,
+        };
+
+        hash::script_hash_cbor(version, script).to_vec()
+    }
+
+    fn integer_data(i: u64) -> Vec<u8> {
+        let mut e = Encoder::new(Vec::new());
+
+        e.u64(i).unwrap();
+
+        e.into_writer()
+    }
+
+    fn input() -> Vec<u8> {
+        let mut e = Encoder::new(Vec::new());
+
+        e.array(2).unwrap().bytes(&TX_ID).unwrap().u8(0).unwrap();
+
+        e.into_writer()
+    }
+
+    /// An output locked by an enterprise script address.
+    fn output(script_hash: &[u8], datum_hash: Option<&[u8]>) -> Vec<u8> {
+        let mut address = vec![0x71];
+
+        address.extend_from_slice(script_hash);
+
+        let mut e = Encoder::new(Vec::new());
+
+        e.map(if datum_hash.is_some() { 3 } else { 2 }).unwrap();
+        e.u8(0).unwrap().bytes(&address).unwrap();
+        e.u8(1).unwrap().u64(2_000_000).unwrap();
+
+        if let Some(hash) = datum_hash {
+            e.u8(2).unwrap();
+            e.array(2).unwrap().u8(0).unwrap().bytes(hash).unwrap();
+        }
+
+        e.into_writer()
+    }
+
+    /// A transaction spending [`input`] with one redeemer, and `script` in
+    /// its witnesses when given.
+    fn transaction(
+        script: Option<(u8, &[u8])>,
+        datum: Option<&[u8]>,
+        redeemer: &[u8],
+        ex_units: ExBudget,
+    ) -> Vec<u8> {
+        let mut e = Encoder::new(Vec::new());
+
+        e.array(4).unwrap();
+
+        e.map(4).unwrap();
+        e.u8(0).unwrap().array(1).unwrap();
+        e.writer_mut().extend_from_slice(&input());
+        e.u8(1).unwrap().array(0).unwrap();
+        e.u8(2).unwrap().u64(170_000).unwrap();
+        e.u8(3).unwrap().u64(4_492_810).unwrap();
+
+        let entries = 1 + script.is_some() as u64 + datum.is_some() as u64;
+
+        e.map(entries).unwrap();
+
+        if let Some((version, bytes)) = script {
+            let key = match version {
+                1 => 3,
+                2 => 6,
+                _ => 7,
+            };
+
+            e.u8(key).unwrap().array(1).unwrap().bytes(bytes).unwrap();
+        }
+
+        if let Some(datum) = datum {
+            e.u8(4).unwrap().array(1).unwrap();
+            e.writer_mut().extend_from_slice(datum);
+        }
+
+        e.u8(5).unwrap().array(1).unwrap();
+        e.array(4).unwrap().u8(0).unwrap().u8(0).unwrap();
+        e.writer_mut().extend_from_slice(redeemer);
+        e.array(2)
+            .unwrap()
+            .i64(ex_units.mem)
+            .unwrap()
+            .i64(ex_units.cpu)
+            .unwrap();
+
+        e.bool(true).unwrap().null().unwrap();
+
+        e.into_writer()
+    }
+
+    #[test]
+    fn evaluates_v3_spend() {
+        let script = script(V3_REDEEMER_IS_42);
+        let output = output(&script_hash(3, &script), None);
+        let input = input();
+
+        let utxos = [Utxo {
+            input: &input,
+            output: &output,
+        }];
+
+        let config = PhaseTwoConfig::new(10);
+
+        let budget = ExBudget::new(1_000_000, 500_000_000);
+
+        let tx = transaction(Some((3, &script)), None, &integer_data(42), budget);
+
+        let results = eval_phase_two(&tx, &utxos, &config).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tag, RedeemerTag::Spend);
+        assert_eq!(results[0].plutus_version, PlutusVersion::V3);
+        assert_eq!(results[0].budget, budget);
+        assert!(results[0].success(), "{:?}", results[0].error);
+        assert!(results[0].consumed.cpu > 0 && results[0].consumed.cpu <= budget.cpu);
+
+        let tx = transaction(Some((3, &script)), None, &integer_data(41), budget);
+
+        let results = eval_phase_two(&tx, &utxos, &config).unwrap();
+
+        assert!(!results[0].success());
+
+        let tx = transaction(
+            Some((3, &script)),
+            None,
+            &integer_data(42),
+            ExBudget::new(100, 10_000),
+        );
+
+        let results = eval_phase_two(&tx, &utxos, &config).unwrap();
+
+        assert!(!results[0].success());
+    }
+
+    #[test]
+    fn v3_scripts_must_return_unit() {
+        let input = input();
+        let config = PhaseTwoConfig::new(10);
+        let budget = ExBudget::new(1_000_000, 500_000_000);
+
+        for src in [
+            "(program 1.1.0 (lam ctx (con bool False)))",
+            "(program 1.1.0 (lam ctx (lam x x)))",
+        ] {
+            let script = script(src);
+            let output = output(&script_hash(3, &script), None);
+
+            let utxos = [Utxo {
+                input: &input,
+                output: &output,
+            }];
+
+            let tx = transaction(Some((3, &script)), None, &integer_data(42), budget);
+
+            let results = eval_phase_two(&tx, &utxos, &config).unwrap();
+
+            assert_eq!(
+                results[0].error.as_deref(),
+                Some("Plutus V3 script did not return unit")
+            );
+        }
+    }
+
+    #[test]
+    fn looks_up_witness_datums() {
+        let script = script(V2_DATUM_IS_REDEEMER);
+        let datum = integer_data(7);
+        let output = output(&script_hash(2, &script), Some(&hash::blake2b_256(&datum)));
+        let input = input();
+
+        let utxos = [Utxo {
+            input: &input,
+            output: &output,
+        }];
+
+        let config = PhaseTwoConfig::new(10);
+
+        let budget = ExBudget::new(1_000_000, 500_000_000);
+
+        let tx = transaction(Some((2, &script)), Some(&datum), &integer_data(7), budget);
+
+        let results = eval_phase_two(&tx, &utxos, &config).unwrap();
+
+        assert_eq!(results[0].plutus_version, PlutusVersion::V2);
+        assert!(results[0].success(), "{:?}", results[0].error);
+
+        let tx = transaction(Some((2, &script)), None, &integer_data(7), budget);
+
+        assert_eq!(
+            eval_phase_two(&tx, &utxos, &config),
+            Err(TxError::MissingDatum(hex::encode(hash::blake2b_256(
+                &datum
+            ))))
+        );
+    }
+
+    #[test]
+    fn reports_missing_scripts_and_inputs() {
+        let script = script(V3_REDEEMER_IS_42);
+        let hash = script_hash(3, &script);
+        let output = output(&hash, None);
+        let input = input();
+
+        let config = PhaseTwoConfig::new(10);
+
+        let tx = transaction(None, None, &integer_data(42), ExBudget::default());
+
+        let utxos = [Utxo {
+            input: &input,
+            output: &output,
+        }];
+
+        assert_eq!(
+            eval_phase_two(&tx, &utxos, &config),
+            Err(TxError::MissingScript(hex::encode(&hash)))
+        );
+
+        assert_eq!(
+            eval_phase_two(&tx, &[], &config),
+            Err(TxError::MissingInput(format!("{}#0", hex::encode(TX_ID))))
+        );
+    }
+
+    #[test]
+    fn builds_v3_context() {
+        let script = script(V3_REDEEMER_IS_42);
+        let output = output(&script_hash(3, &script), None);
+        let input = input();
+
+        let tx = transaction(
+            Some((3, &script)),
+            None,
+            &integer_data(42),
+            ExBudget::default(),
+        );
+
+        let tx = decode::transaction(&tx).unwrap();
+
+        let utxos = [(
+            decode::input(&mut minicbor::Decoder::new(&input)).unwrap(),
+            decode::output(&mut minicbor::Decoder::new(&output)).unwrap(),
+        )];
+
+        let context = TxContext::new(&tx, &utxos, SlotConfig::default())
+            .unwrap()
+            .v3(0)
+            .unwrap();
+
+        let out_ref = TxOutRef {
+            id: TxId(TX_ID.to_vec()),
+            index: Integer::from(0),
+        };
+
+        assert_eq!(context.redeemer, OwnedData::Integer(Integer::from(42)));
+        assert_eq!(
+            context.script_info,
+            ScriptInfo::Spending(out_ref.clone(), None)
+        );
+        assert_eq!(context.tx_info.inputs[0].out_ref, out_ref);
+        assert_eq!(context.tx_info.fee, Integer::from(170_000));
+        assert_eq!(context.tx_info.id.0, tx.id);
+        assert_eq!(
+            context.tx_info.valid_range.to.bound,
+            Extended::Finite(Integer::from(1_596_059_101_000_u64))
+        );
+        assert!(!context.tx_info.valid_range.to.inclusive);
+    }
+}
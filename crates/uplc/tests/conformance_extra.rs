@@ -34,12 +34,14 @@ fn run_conformance_with_params(file_contents: &str, expected_output: &str, expec
         return;
     };
 
-    let result = program.eval_with_params(
-        &arena,
-        PlutusVersion::V3,
-        EXTRA_V3_COSTS,
-        ExBudget::default(),
-    );
+    let result = program
+        .eval_with_params(
+            &arena,
+            PlutusVersion::V3,
+            EXTRA_V3_COSTS,
+            ExBudget::default(),
+        )
+        .unwrap();
 
     let info = result.info;
 
@@ -96,3 +98,19 @@ regression_case!(
     builtin_semantics_verifysignature_legacy_alias_test_vector_25_regression,
     "conformance_extra/textual/builtin/semantics/verifySignature/legacy-alias-test-vector-25/legacy-alias-test-vector-25.uplc"
 );
+regression_case!(
+    term_closure_closure_case_regression,
+    "conformance_extra/textual/term/closure/closure-case/closure-case.uplc"
+);
+regression_case!(
+    term_closure_closure_constr_regression,
+    "conformance_extra/textual/term/closure/closure-constr/closure-constr.uplc"
+);
+regression_case!(
+    term_closure_closure_case_nested_regression,
+    "conformance_extra/textual/term/closure/closure-case-nested/closure-case-nested.uplc"
+);
+regression_case!(
+    builtin_semantics_shiftbytestring_whole_byte_left_regression,
+    "conformance_extra/textual/builtin/semantics/shiftByteString/whole-byte-left/whole-byte-left.uplc"
+);
@@ -0,0 +1,92 @@
+use amaru_uplc::{
+    builtin::DefaultFunction,
+    machine::{
+        mainnet_params, BuiltinCostsV1, BuiltinCostsV2, BuiltinCostsV3, CostModel, CostModelError,
+        PlutusVersion,
+    },
+};
+
+#[test]
+fn builds_mainnet_cost_models() {
+    assert!(CostModel::<BuiltinCostsV1>::for_network(&PlutusVersion::V1, 10).is_ok());
+    assert!(CostModel::<BuiltinCostsV2>::for_network(&PlutusVersion::V2, 10).is_ok());
+    assert_eq!(
+        CostModel::<BuiltinCostsV3>::for_network(&PlutusVersion::V3, 10).unwrap(),
+        CostModel::<BuiltinCostsV3>::default()
+    );
+    assert_eq!(
+        CostModel::<BuiltinCostsV3>::for_network(&PlutusVersion::V3, 8).err(),
+        Some(CostModelError::UnavailableVersion {
+            version: PlutusVersion::V3,
+            protocol_version: 8,
+        })
+    );
+    assert_eq!(
+        mainnet_params(&PlutusVersion::V3, 9).map(<[i64]>::len),
+        Ok(251)
+    );
+}
+
+#[test]
+fn loads_cost_models_from_json() {
+    let version = PlutusVersion::V3;
+
+    let model = CostModel::<BuiltinCostsV3>::from_plutus_json(
+        &version,
+        include_str!("cost_models/builtinCostModelC.json"),
+        include_str!("cost_models/cekMachineCostsC.json"),
+    )
+    .unwrap();
+
+    assert_eq!(model, CostModel::<BuiltinCostsV3>::default());
+
+    let params = mainnet_params(&version, 10).unwrap();
+
+    assert_eq!(
+        CostModel::<BuiltinCostsV3>::from_named_params(&version, model.to_named_params()).as_ref(),
+        Ok(&model)
+    );
+    assert_eq!(
+        CostModel::<BuiltinCostsV3>::initialize_cost_model(&version, params).as_ref(),
+        Ok(&model)
+    );
+
+    let json = serde_json::Value::Object(
+        model
+            .to_named_params()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.into()))
+            .collect(),
+    );
+
+    assert_eq!(
+        CostModel::<BuiltinCostsV3>::from_named_json(&version, &json.to_string()),
+        Ok(model)
+    );
+}
+
+#[test]
+fn exports_cost_models() {
+    let model = CostModel::<BuiltinCostsV2>::for_network(&PlutusVersion::V2, 9).unwrap();
+
+    let named = model.to_named_params();
+
+    assert_eq!(named.len(), 175);
+    assert_eq!(named[0], ("add_integer-cpu-arguments-intercept", 100788));
+
+    let builtin_costs = model.builtin_costs_json();
+
+    assert_eq!(builtin_costs["addInteger"]["cpu"]["type"], "max_size");
+    assert_eq!(
+        model.machine_costs_json()["cekStartupCost"]["exBudgetCPU"],
+        100
+    );
+
+    let formula = model
+        .formulas()
+        .into_iter()
+        .find(|formula| formula.builtin == DefaultFunction::AddInteger)
+        .unwrap();
+
+    assert_eq!(formula.cpu, "100788 + 420 * max(x, y)");
+}